@@ -0,0 +1,87 @@
+//! Measures the typing latency with syntax highlighting.
+//!
+//! A keystroke is measured as the edit itself plus rendering the viewport,
+//! which is where rows are highlighted. The `synoptic` group re-tokenizes the
+//! whole file on each keystroke, as the editor did before highlighting became
+//! incremental.
+//!
+//! Run with `cargo bench -p edtui-papier --bench highlight`.
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use edtui_papier::{
+    actions::{DeleteChar, Execute, InsertChar},
+    EditorState, EditorView, Index2, Lines,
+};
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+const ROWS: [usize; 2] = [1_000, 100_000];
+
+fn sample_text(rows: usize) -> String {
+    (0..rows)
+        .map(|i| {
+            match i % 4 {
+                0 => "/* A block comment */\n".to_string(),
+                1 => format!("fn f{i}(x: usize) -> usize {{\n"),
+                2 => "    let y = x * 2; // double\n".to_string(),
+                _ => "}\n".to_string(),
+            }
+        })
+        .collect()
+}
+
+fn render(state: &mut EditorState, buf: &mut Buffer) {
+    EditorView::new(state).render(buf.area, buf);
+}
+
+fn typing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("typing");
+    for rows in ROWS {
+        let mut state = EditorState::new(Lines::from(sample_text(rows).as_str()), "rs");
+        let mut buf = Buffer::empty(Rect::new(0, 0, 120, 40));
+        state.cursor = Index2::new(rows / 2 + 2, 10);
+        render(&mut state, &mut buf);
+
+        group.bench_function(BenchmarkId::new("incremental", rows), |b| {
+            b.iter(|| {
+                InsertChar('=').execute(&mut state);
+                render(&mut state, &mut buf);
+                DeleteChar(1).execute(&mut state);
+                render(&mut state, &mut buf);
+            });
+        });
+
+        let lines = sample_text(rows).lines().map(ToString::to_string).collect::<Vec<_>>();
+        let mut highlighter = synoptic::from_extension("rs", 4).unwrap();
+        highlighter.run(&lines);
+        let row = rows / 2 + 2;
+        let (edited, original) = (format!("={}", lines[row]), lines[row].clone());
+
+        group.bench_function(BenchmarkId::new("synoptic", rows), |b| {
+            b.iter(|| {
+                highlighter.edit(row, black_box(&edited));
+                highlighter.edit(row, black_box(&original));
+            });
+        });
+    }
+    group.finish();
+}
+
+fn open(c: &mut Criterion) {
+    let mut group = c.benchmark_group("open");
+    for rows in ROWS {
+        let text = Lines::from(sample_text(rows).as_str());
+        let mut buf = Buffer::empty(Rect::new(0, 0, 120, 40));
+
+        group.bench_with_input(BenchmarkId::new("incremental", rows), &text, |b, text| {
+            b.iter(|| {
+                let mut state = EditorState::new(text.clone(), "rs");
+                render(&mut state, &mut buf);
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, typing, open);
+criterion_main!(benches);
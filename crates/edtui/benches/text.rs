@@ -0,0 +1,65 @@
+//! Compares the text storage backends of the editor.
+//!
+//! Run with `cargo bench -p edtui-papier --bench text`.
+use std::{hint::black_box, io};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use edtui_papier::{
+    text::{Rope, TextStorage},
+    Index2,
+};
+use jagged::Jagged;
+
+const ROWS: [usize; 2] = [1_000, 100_000];
+
+fn sample_text(rows: usize) -> String {
+    (0..rows).map(|i| format!("{i:>8}: The quick brown fox jumps over the lazy dog.\n")).collect()
+}
+
+fn bench_backend<T: TextStorage>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(name);
+    for rows in ROWS {
+        let raw = sample_text(rows);
+        let text = T::from(raw.as_str());
+        let middle = Index2::new(rows / 2, 10);
+
+        group.bench_with_input(BenchmarkId::new("load", rows), &raw, |b, raw| b.iter(|| T::from(black_box(raw))));
+        group.bench_with_input(BenchmarkId::new("clone", rows), &text, |b, text| b.iter(|| black_box(text.clone())));
+        group.bench_with_input(BenchmarkId::new("save", rows), &text, |b, text| {
+            b.iter(|| text.write_to(io::sink()).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("insert_remove_char", rows), &text, |b, text| {
+            let mut text = text.clone();
+            b.iter(|| {
+                text.insert_char(middle, 'x');
+                text.remove_char(middle)
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("split_join_line", rows), &text, |b, text| {
+            let mut text = text.clone();
+            b.iter(|| {
+                text.split_line(middle);
+                text.join_lines(middle.row);
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("index_conversion", rows), &text, |b, text| {
+            b.iter(|| {
+                let char_idx = text.index_to_char(black_box(middle));
+                let byte_idx = text.char_to_byte(char_idx);
+                text.char_to_index(text.byte_to_char(byte_idx))
+            });
+        });
+    }
+    group.finish();
+}
+
+fn rope(c: &mut Criterion) {
+    bench_backend::<Rope>(c, "rope");
+}
+
+fn jagged(c: &mut Criterion) {
+    bench_backend::<Jagged<char>>(c, "jagged");
+}
+
+criterion_group!(benches, rope, jagged);
+criterion_main!(benches);
@@ -1,5 +1,6 @@
 //! Editor actions such as move, insert, delete
 pub mod cpaste;
+pub mod cursor;
 pub mod delete;
 pub mod insert;
 pub mod motion;
@@ -12,19 +13,18 @@ use serde::{Deserialize, Serialize};
 use self::motion::{MoveToFirstLine, MoveToLastLine};
 pub use self::{
     cpaste::{CopySelection, Paste},
+    cursor::{AddCursorAbove, AddCursorAtNextMatch, AddCursorBelow, RemoveCursors, SelectAllMatches},
     delete::{DeleteChar, DeleteLine, DeleteSelection, RemoveChar},
-    insert::{AppendNewline, InsertChar, InsertNewline, LineBreak},
-    motion::MoveWordForwardEnd,
+    insert::{AppendNewline, InsertChar, InsertNewline, InsertText, LineBreak, ReplaceText},
     motion::{
         MoveBackward, MoveDown, MoveForward, MoveToEnd, MoveToFirst, MoveToStart, MoveUp, MoveWordBackward,
-        MoveWordForwardStart,
+        MoveWordForwardEnd, MoveWordForwardStart,
     },
     search::{
         AppendCharToSearch, FindNext, FindPrevious, RemoveCharFromSearch, StartSearch, StopSearch, TriggerSearch,
     },
     select::SelectBetween,
 };
-
 use crate::{helper::clamp_column, state::selection::Selection, EditorMode, EditorState};
 
 #[enum_dispatch(Execute, Clone, Serialize, Deserialize)]
@@ -46,6 +46,7 @@ pub enum Action<I: Clone + Execute> {
     MoveToFirstLine(MoveToFirstLine),
     MoveToLastLine(MoveToLastLine),
     InsertChar(InsertChar),
+    InsertText(InsertText),
     LineBreak(LineBreak),
     AppendNewline(AppendNewline),
     InsertNewline(InsertNewline),
@@ -66,9 +67,49 @@ pub enum Action<I: Clone + Execute> {
     FindPrevious(FindPrevious),
     AppendCharToSearch(AppendCharToSearch),
     RemoveCharFromSearch(RemoveCharFromSearch),
+    AddCursorAtNextMatch(AddCursorAtNextMatch),
+    AddCursorBelow(AddCursorBelow),
+    AddCursorAbove(AddCursorAbove),
+    SelectAllMatches(SelectAllMatches),
+    RemoveCursors(RemoveCursors),
     Custom(Custom<I>),
 }
 
+impl<I: Clone + Execute> Action<I> {
+    /// Whether the action applies at every cursor, see
+    /// [`EditorState::execute_at_cursors`].
+    #[must_use]
+    pub fn at_cursors(&self) -> bool {
+        matches!(
+            self,
+            Self::SwitchMode(_)
+                | Self::Append(_)
+                | Self::MoveForward(_)
+                | Self::MoveBackward(_)
+                | Self::MoveUp(_)
+                | Self::MoveDown(_)
+                | Self::MoveWordForwardStart(_)
+                | Self::MoveWordFowardEnd(_)
+                | Self::MoveWordBackward(_)
+                | Self::MoveToStart(_)
+                | Self::MoveToFirst(_)
+                | Self::MoveToEnd(_)
+                | Self::InsertChar(_)
+                | Self::InsertText(_)
+                | Self::LineBreak(_)
+                | Self::AppendNewline(_)
+                | Self::InsertNewline(_)
+                | Self::RemoveChar(_)
+                | Self::DeleteChar(_)
+                | Self::DeleteLine(_)
+                | Self::DeleteSelection(_)
+                | Self::SelectBetween(_)
+                | Self::Paste(_)
+                | Self::Composed(_)
+        )
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Custom<I: Clone + Execute>(pub I);
 
@@ -11,15 +11,19 @@ pub struct Paste;
 
 impl Execute for Paste {
     fn execute(&mut self, state: &mut EditorState) {
+        let text = state.clip.get_text();
+        state.mode = EditorMode::Normal;
+        if text.is_empty() && state.selection.is_none() {
+            return;
+        }
         state.capture();
         clamp_column(state);
         if let Some(selection) = state.selection.take() {
             delete_selection(state, &selection);
-            insert_str(&mut state.lines, &mut state.cursor, &state.clip.get_text(), &mut state.highlighter);
+            insert_str(&mut state.lines, &mut state.cursor, &text, &mut state.highlighter);
         } else {
-            append_str(&mut state.lines, &mut state.cursor, &state.clip.get_text(), &mut state.highlighter);
+            append_str(&mut state.lines, &mut state.cursor, &text, &mut state.highlighter);
         }
-        state.mode = EditorMode::Normal;
     }
 }
 
@@ -76,4 +80,13 @@ mod tests {
         assert_eq!(state.lines, Lines::from("Hello World!\n\n123."));
         assert_eq!(state.mode, EditorMode::Normal);
     }
+
+    #[test]
+    fn test_paste_nothing() {
+        let mut state = test_state();
+        let revision = state.revision();
+        Paste.execute(&mut state);
+        assert_eq!(state.revision(), revision);
+        assert_eq!(state.lines, Lines::from("Hello World!\n\n123."));
+    }
 }
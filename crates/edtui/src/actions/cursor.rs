@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+use super::Execute;
+use crate::{
+    helper::word_at,
+    state::{cursor::Cursor, selection::Selection},
+    text::TextStorage,
+    EditorMode, EditorState, Index2,
+};
+
+/// Adds a cursor at the next match of the selection. Without a selection,
+/// selects the word under the cursor first.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AddCursorAtNextMatch;
+
+impl Execute for AddCursorAtNextMatch {
+    fn execute(&mut self, state: &mut EditorState) {
+        let Some(selection) = state.selection.clone() else {
+            if let Some((start, end)) = word_at(&state.lines, state.cursor) {
+                state.mode = EditorMode::Visual;
+                state.selection = Some(Selection::new(start, end));
+                state.cursor = end;
+            }
+            return;
+        };
+        let pattern = state.lines.slice(selection.start(), selection.end()).chars().collect::<Vec<_>>();
+        let selected = state
+            .cursors
+            .iter()
+            .filter_map(|cursor| cursor.selection.as_ref())
+            .chain(Some(&selection))
+            .map(Selection::start)
+            .collect::<Vec<_>>();
+        let last = selected.iter().copied().reduce(|a, b| if b > a { b } else { a }).unwrap_or_default();
+        let matches = state.lines.match_indices(&pattern);
+        let unselected = |start: &&Index2| !selected.contains(start);
+        let next = matches.iter().filter(unselected).find(|start| **start > last);
+        if let Some(&start) = next.or_else(|| matches.iter().find(unselected)) {
+            let end = Index2::new(start.row, start.col + pattern.len().saturating_sub(1));
+            state.add_cursor(end, Some(Selection::new(start, end)));
+            state.mode = EditorMode::Visual;
+        }
+    }
+}
+
+/// Adds a cursor in the row below the cursor.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AddCursorBelow;
+
+impl Execute for AddCursorBelow {
+    fn execute(&mut self, state: &mut EditorState) {
+        if state.cursor.row + 1 < state.lines.len() {
+            add_cursor_in_row(state, state.cursor.row + 1);
+        }
+    }
+}
+
+/// Adds a cursor in the row above the cursor.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AddCursorAbove;
+
+impl Execute for AddCursorAbove {
+    fn execute(&mut self, state: &mut EditorState) {
+        if state.cursor.row > 0 {
+            add_cursor_in_row(state, state.cursor.row - 1);
+        }
+    }
+}
+
+fn add_cursor_in_row(state: &mut EditorState, row: usize) {
+    let position = Index2::new(row, state.cursor.col);
+    let selection = (state.mode == EditorMode::Visual).then(|| Selection::new(position, position));
+    state.add_cursor(position, selection);
+}
+
+/// Selects all matches of the last search, each with its own cursor.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SelectAllMatches;
+
+impl Execute for SelectAllMatches {
+    fn execute(&mut self, state: &mut EditorState) {
+        state.search.trigger_search(&state.lines);
+        let len = state.search.pattern.chars().count();
+        let mut cursors = state
+            .search
+            .matches()
+            .iter()
+            .map(|&start| {
+                let end = Index2::new(start.row, start.col + len.saturating_sub(1));
+                Cursor::new(end, Some(Selection::new(start, end)))
+            })
+            .collect::<Vec<_>>();
+        if cursors.is_empty() {
+            return;
+        }
+        // The first match behind the cursor becomes the primary cursor.
+        let primary = cursors.iter().position(|cursor| cursor.position >= state.cursor).unwrap_or_default();
+        let primary = cursors.remove(primary);
+        state.cursor = primary.position;
+        state.selection = primary.selection;
+        state.cursors = cursors;
+        state.mode = EditorMode::Visual;
+    }
+}
+
+/// Removes all secondary cursors.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RemoveCursors;
+
+impl Execute for RemoveCursors {
+    fn execute(&mut self, state: &mut EditorState) {
+        state.remove_cursors();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{search::StartSearch, Append, AppendCharToSearch, InsertChar, SwitchMode, TriggerSearch},
+        Lines,
+    };
+
+    fn test_state() -> EditorState {
+        EditorState::new(Lines::from("foo bar\nbar foo\nfoo"), "txt")
+    }
+
+    fn selections(state: &EditorState) -> Vec<Selection> {
+        let mut selections = state.cursors.iter().filter_map(|c| c.selection.clone()).collect::<Vec<_>>();
+        selections.extend(state.selection.clone());
+        selections.sort_by_key(|selection| (selection.start().row, selection.start().col));
+        selections
+    }
+
+    #[test]
+    fn test_add_cursor_at_next_match() {
+        let mut state = test_state();
+        AddCursorAtNextMatch.execute(&mut state);
+        assert_eq!(state.selection, Some(Selection::new(Index2::new(0, 0), Index2::new(0, 2))));
+        assert!(state.cursors.is_empty());
+
+        AddCursorAtNextMatch.execute(&mut state);
+        AddCursorAtNextMatch.execute(&mut state);
+        assert_eq!(state.cursor, Index2::new(2, 2));
+        assert_eq!(state.cursors.len(), 2);
+
+        // Wraps around without adding a match twice
+        AddCursorAtNextMatch.execute(&mut state);
+        assert_eq!(state.cursors.len(), 2);
+
+        state.execute_at_cursors(&mut SwitchMode(EditorMode::Normal));
+        state.execute_at_cursors(&mut Append);
+        state.execute_at_cursors(&mut InsertChar('!'));
+        assert_eq!(state.lines, Lines::from("foo! bar\nbar foo!\nfoo!"));
+    }
+
+    #[test]
+    fn test_add_cursor_below_and_above() {
+        let mut state = test_state();
+        state.cursor = Index2::new(1, 1);
+        AddCursorBelow.execute(&mut state);
+        AddCursorBelow.execute(&mut state);
+        assert_eq!(state.cursor, Index2::new(2, 1));
+        assert_eq!(state.cursors, vec![Cursor::new(Index2::new(1, 1), None)]);
+
+        AddCursorAbove.execute(&mut state);
+        assert_eq!(state.cursor, Index2::new(1, 1));
+        RemoveCursors.execute(&mut state);
+        assert!(state.cursors.is_empty());
+    }
+
+    #[test]
+    fn test_select_all_matches() {
+        let mut state = test_state();
+        state.cursor = Index2::new(1, 0);
+        StartSearch.execute(&mut state);
+        for c in "foo".chars() {
+            AppendCharToSearch(c).execute(&mut state);
+        }
+        TriggerSearch.execute(&mut state);
+        SelectAllMatches.execute(&mut state);
+
+        assert_eq!(state.mode, EditorMode::Visual);
+        assert_eq!(state.cursor, Index2::new(1, 6));
+        let expected = [(0, 0), (1, 4), (2, 0)]
+            .map(|(row, col)| Selection::new(Index2::new(row, col), Index2::new(row, col + 2)))
+            .to_vec();
+        assert_eq!(selections(&state), expected);
+    }
+}
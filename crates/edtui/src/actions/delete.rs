@@ -1,11 +1,9 @@
-use jagged::index::RowIndex;
 use serde::{Deserialize, Serialize};
-use synoptic::Highlighter;
 
 use super::Execute;
 use crate::{
-    clipboard::ClipboardTrait, helper::clamp_column, state::selection::Selection, EditorMode, EditorState, Index2,
-    Lines,
+    clipboard::ClipboardTrait, helper::clamp_column, highlight::Highlighter, state::selection::Selection,
+    text::TextStorage, EditorMode, EditorState, Index2,
 };
 
 /// Deletes a character at the current cursor position. Does not
@@ -16,6 +14,9 @@ pub struct RemoveChar(pub usize);
 impl Execute for RemoveChar {
     fn execute(&mut self, state: &mut EditorState) {
         clamp_column(state);
+        if state.lines.len_col(state.cursor.row).unwrap_or_default() == 0 {
+            return;
+        }
         state.capture();
         for _ in 0..self.0 {
             let lines = &mut state.lines;
@@ -23,13 +24,11 @@ impl Execute for RemoveChar {
             if lines.len_col(index.row) == Some(0) {
                 return;
             }
-            let _ = lines.remove(*index);
-            if let Some(len) = lines.len_col(index.row) {
+            let _ = lines.remove_char(*index);
+            if let Some(len) = lines.len_col(index.row).filter(|_| state.mode != EditorMode::Insert) {
                 index.col = index.col.min(len.saturating_sub(1));
             }
-
-            let y = index.row;
-            state.highlighter.edit(y, &lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>()[y]);
+            state.highlighter.edit(index.row);
         }
     }
 }
@@ -41,17 +40,18 @@ pub struct DeleteChar(pub usize);
 
 impl Execute for DeleteChar {
     fn execute(&mut self, state: &mut EditorState) {
+        if state.cursor == Index2::new(0, 0) {
+            return;
+        }
         state.capture();
         for _ in 0..self.0 {
             delete_char(&mut state.lines, &mut state.cursor, &mut state.highlighter);
-            let y = state.cursor.row;
-            state.highlighter.edit(y, &state.lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>()[y]);
         }
     }
 }
 
-fn delete_char(lines: &mut Lines, index: &mut Index2, highlighter: &mut Highlighter) {
-    fn move_left(lines: &Lines, index: &mut Index2) {
+fn delete_char<T: TextStorage>(lines: &mut T, index: &mut Index2, highlighter: &mut Highlighter) {
+    fn move_left<T: TextStorage>(lines: &T, index: &mut Index2) {
         if index.col > 0 {
             index.col -= 1;
         } else if index.row > 0 {
@@ -68,16 +68,15 @@ fn delete_char(lines: &mut Lines, index: &mut Index2, highlighter: &mut Highligh
 
     // If the cursor is at the beginning of the line, merge the current line with the previous one
     if index.col == 0 {
-        let mut rest = lines.split_off(*index);
         move_left(lines, index);
-        lines.merge(&mut rest);
-        highlighter.edit(index.row, &lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>()[index.row]);
+        lines.join_lines(index.row);
+        highlighter.edit(index.row);
         highlighter.remove_line(index.row + 1);
     } else {
         // Otherwise, just remove the character to the left
         move_left(lines, index);
-        let _ = lines.remove(*index);
-        highlighter.edit(index.row, &lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>()[index.row]);
+        let _ = lines.remove_char(*index);
+        highlighter.edit(index.row);
     }
 }
 
@@ -87,16 +86,26 @@ pub struct DeleteLine(pub usize);
 
 impl Execute for DeleteLine {
     fn execute(&mut self, state: &mut EditorState) {
+        if state.cursor.row >= state.lines.len() || state.lines.is_empty() {
+            return;
+        }
         state.capture();
         for _ in 0..self.0 {
-            if state.cursor.row >= state.lines.len() {
+            let row = state.cursor.row;
+            if row >= state.lines.len() || state.lines.is_empty() {
                 break;
             }
-            state.lines.remove(RowIndex::new(state.cursor.row));
+            let last_line = state.lines.len() == 1;
+            state.lines.remove_line(row);
             state.cursor.col = 0;
-            state.cursor.row = state.cursor.row.min(state.lines.len().saturating_sub(1));
+            state.cursor.row = row.min(state.lines.len().saturating_sub(1));
 
-            state.highlighter.remove_line(state.cursor.row);
+            if last_line {
+                // Removing the only line leaves a single empty line.
+                state.highlighter.edit(0);
+            } else {
+                state.highlighter.remove_line(row);
+            }
         }
     }
 }
@@ -109,8 +118,8 @@ impl Execute for DeleteSelection {
     // TODO: Implement a better way to delete a selection,
     // possibly using a drain iterator.
     fn execute(&mut self, state: &mut EditorState) {
-        state.capture();
         if let Some(selection) = state.selection.take() {
+            state.capture();
             state.clip.set_text(selection.extract(&state.lines).into());
             delete_selection(state, &selection);
         }
@@ -133,6 +142,7 @@ pub(crate) fn delete_selection(state: &mut EditorState, selection: &Selection) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Lines;
     fn test_state() -> EditorState {
         EditorState::new(Lines::from("Hello World!\n\n123."), "txt")
     }
@@ -196,4 +206,19 @@ mod tests {
         assert_eq!(state.cursor, Index2::new(0, 1));
         assert_eq!(state.lines, Lines::from("H23."));
     }
+
+    #[test]
+    fn test_delete_nothing() {
+        let mut state = test_state();
+        let revision = state.revision();
+
+        state.cursor = Index2::new(1, 0);
+        RemoveChar(1).execute(&mut state);
+        state.cursor = Index2::new(0, 0);
+        DeleteChar(1).execute(&mut state);
+        DeleteSelection.execute(&mut state);
+        state.lines = Lines::from("");
+        DeleteLine(1).execute(&mut state);
+        assert_eq!(state.revision(), revision);
+    }
 }
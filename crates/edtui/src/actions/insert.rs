@@ -1,10 +1,10 @@
-use jagged::index::RowIndex;
 use serde::{Deserialize, Serialize};
 
-use super::{Execute, SwitchMode};
+use super::{delete::delete_selection, search::AppendCharToSearch, Execute, SwitchMode};
 use crate::{
-    helper::{insert_char, line_break},
-    EditorMode, EditorState,
+    helper::{append_str, clamp_column, insert_char, insert_str, insert_text, line_break},
+    text::TextStorage,
+    EditorMode, EditorState, Lines,
 };
 
 /// Inserts a single character at the current cursor position
@@ -14,6 +14,44 @@ pub struct InsertChar(pub char);
 impl Execute for InsertChar {
     fn execute(&mut self, state: &mut EditorState) {
         insert_char(&mut state.lines, &mut state.cursor, self.0, false, &mut state.highlighter);
+        state.touch();
+    }
+}
+
+/// Inserts a text with a single edit, e.g. a bracketed paste. In insert
+/// mode the text is inserted at the cursor, in normal mode it is put after
+/// the cursor and in visual mode it replaces the selection. Line endings are
+/// normalized to `\n`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InsertText(pub String);
+
+impl Execute for InsertText {
+    fn execute(&mut self, state: &mut EditorState) {
+        let text = self.0.replace("\r\n", "\n").replace('\r', "\n");
+        if state.mode == EditorMode::Search {
+            for c in text.chars().filter(|c| *c != '\n') {
+                AppendCharToSearch(c).execute(state);
+            }
+            return;
+        }
+        if text.is_empty() || state.mode == EditorMode::Command {
+            return;
+        }
+        state.capture();
+        match state.mode {
+            EditorMode::Insert => {
+                state.cursor = insert_text(&mut state.lines, &state.cursor, &text, &mut state.highlighter);
+            },
+            _ => {
+                if let Some(selection) = state.selection.take() {
+                    delete_selection(state, &selection);
+                    insert_str(&mut state.lines, &mut state.cursor, &text, &mut state.highlighter);
+                } else {
+                    append_str(&mut state.lines, &mut state.cursor, &text, &mut state.highlighter);
+                }
+                state.mode = EditorMode::Normal;
+            },
+        }
     }
 }
 
@@ -26,6 +64,7 @@ impl Execute for LineBreak {
         for _ in 0..self.0 {
             line_break(&mut state.lines, &mut state.cursor, &mut state.highlighter);
         }
+        state.touch();
     }
 }
 
@@ -39,9 +78,10 @@ impl Execute for AppendNewline {
         state.cursor.col = 0;
         for _ in 0..self.0 {
             state.cursor.row += 1;
-            state.lines.insert(RowIndex::new(state.cursor.row), vec![]);
-            state.highlighter.insert_line(state.cursor.row, &"".to_string());
+            state.lines.insert_line(state.cursor.row, "");
+            state.highlighter.insert_line(state.cursor.row);
         }
+        state.touch();
         SwitchMode(EditorMode::Insert).execute(state);
     }
 }
@@ -55,9 +95,10 @@ impl Execute for InsertNewline {
     fn execute(&mut self, state: &mut EditorState) {
         state.cursor.col = 0;
         for _ in 0..self.0 {
-            state.lines.insert(RowIndex::new(state.cursor.row), vec![]);
-            state.highlighter.insert_line(state.cursor.row, &"".to_string());
+            state.lines.insert_line(state.cursor.row, "");
+            state.highlighter.insert_line(state.cursor.row);
         }
+        state.touch();
         SwitchMode(EditorMode::Insert).execute(state);
     }
 }
@@ -69,16 +110,37 @@ pub struct PushLine<'a>(pub &'a str);
 
 impl Execute for PushLine<'_> {
     fn execute(&mut self, state: &mut EditorState) {
-        let chars: Vec<char> = self.0.chars().collect();
-        state.lines.push(chars.clone());
-        state.highlighter.append(&chars.iter().collect::<String>());
+        state.lines.push_line(self.0);
+        state.highlighter.insert_line(state.lines.len() - 1);
+        state.touch();
+    }
+}
+
+/// Replaces the whole text with a single edit, e.g. by the content of a
+/// file. The cursor stays in place as far as the new text allows.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReplaceText(pub String);
+
+impl Execute for ReplaceText {
+    fn execute(&mut self, state: &mut EditorState) {
+        let lines = Lines::from(self.0.as_str());
+        if lines == state.lines {
+            return;
+        }
+        state.capture();
+        state.highlighter.replace(&state.lines, &lines);
+        state.lines = lines;
+        state.selection = None;
+        state.cursors.clear();
+        state.cursor.row = state.cursor.row.min(state.lines.len().saturating_sub(1));
+        clamp_column(state);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Index2, Lines};
+    use crate::{actions::Undo, Index2, Lines};
     fn test_state() -> EditorState {
         EditorState::new(Lines::from("Hello World!\n\n123."), "txt")
     }
@@ -97,6 +159,42 @@ mod tests {
         assert_eq!(state.lines, Lines::from("!Hello World!!\n\n123."));
     }
 
+    #[test]
+    fn test_replace_text() {
+        let mut state = test_state();
+        state.cursor = Index2::new(2, 3);
+        ReplaceText("Hello\nWorld".to_string()).execute(&mut state);
+        assert_eq!(state.lines, Lines::from("Hello\nWorld"));
+        assert_eq!(state.cursor, Index2::new(1, 3));
+
+        Undo.execute(&mut state);
+        assert_eq!(state.lines, Lines::from("Hello World!\n\n123."));
+    }
+
+    #[test]
+    fn test_insert_text() {
+        let mut state = test_state();
+        state.mode = EditorMode::Insert;
+        state.cursor = Index2::new(0, 6);
+
+        InsertText("big\r\nnew ".to_string()).execute(&mut state);
+        assert_eq!(state.cursor, Index2::new(1, 4));
+        assert_eq!(state.lines, Lines::from("Hello big\nnew World!\n\n123."));
+
+        Undo.execute(&mut state);
+        assert_eq!(state.lines, Lines::from("Hello World!\n\n123."));
+    }
+
+    #[test]
+    fn test_insert_text_normal_mode() {
+        let mut state = test_state();
+        state.cursor = Index2::new(0, 4);
+
+        InsertText("!\r".to_string()).execute(&mut state);
+        assert_eq!(state.lines, Lines::from("Hello!\n World!\n\n123."));
+        assert_eq!(state.mode, EditorMode::Normal);
+    }
+
     #[test]
     fn test_linebreak() {
         let mut state = test_state();
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use super::Execute;
 use crate::{
     helper::{max_col, max_row, set_selection, skip_whitespace, skip_whitespace_rev},
+    text::TextStorage,
     EditorMode, EditorState,
 };
 
@@ -229,7 +230,7 @@ impl Execute for MoveWordBackward {
 }
 
 /// Whether two characters are considered of the same class.
-fn is_same_word_class(a: Option<&char>, b: Option<&char>) -> bool {
+pub(crate) fn is_same_word_class(a: Option<char>, b: Option<char>) -> bool {
     match (a, b) {
         (Some(a), Some(b)) => {
             a.is_ascii_alphanumeric() && b.is_ascii_alphanumeric()
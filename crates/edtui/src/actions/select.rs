@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::Execute;
-use crate::{state::selection::Selection, EditorMode, EditorState, Index2};
+use crate::{state::selection::Selection, text::TextStorage, EditorMode, EditorState, Index2};
 /// Selects text between specified delimiter characters.
 ///
 /// It searches for the first occurrence of a delimiter character in the text to
@@ -17,7 +17,7 @@ impl Execute for SelectBetween {
         let mut end: Option<Index2> = None;
         let mut prev = cursor;
         for (value, index) in state.lines.iter().from(cursor) {
-            if let Some(&c) = value {
+            if let Some(c) = value {
                 if self.0.iter().any(|(start, _)| *start == c) {
                     end = Some(prev);
                     break;
@@ -27,7 +27,7 @@ impl Execute for SelectBetween {
         }
         prev = cursor;
         for (value, index) in state.lines.iter().from(cursor).rev() {
-            if let Some(&c) = value {
+            if let Some(c) = value {
                 if self.0.iter().any(|(_, end)| *end == c) {
                     start = Some(prev);
                     break;
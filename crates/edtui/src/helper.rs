@@ -1,62 +1,90 @@
-use jagged::index::RowIndex;
-use synoptic::Highlighter;
-
-use crate::{state::selection::Selection, EditorMode, EditorState, Index2, Lines};
+use crate::{
+    actions::motion::is_same_word_class, highlight::Highlighter, state::selection::Selection, text::TextStorage,
+    EditorMode, EditorState, Index2,
+};
 
 /// Inserts a character into the lines data at the given `index`.
-pub fn insert_char(lines: &mut Lines, index: &mut Index2, ch: char, skip_move: bool, highlighter: &mut Highlighter) {
-    if lines.is_empty() {
-        lines.push(Vec::new());
-        highlighter.insert_line(0, &"".to_string());
+pub fn insert_char<T: TextStorage>(
+    lines: &mut T,
+    index: &mut Index2,
+    ch: char,
+    skip_move: bool,
+    highlighter: &mut Highlighter,
+) {
+    if lines.len() == 0 {
+        lines.push_line("");
+        highlighter.insert_line(0);
     }
     if ch == '\n' {
         line_break(lines, index, highlighter);
     } else {
         index.col = index.col.min(max_col(lines, index, EditorMode::Insert));
-        lines.insert(*index, ch);
-        let y = index.row;
-        highlighter.edit(y, &lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>()[y]);
+        lines.insert_char(*index, ch);
+        highlighter.edit(index.row);
         if !skip_move {
             index.col += 1;
         }
     }
 }
 
-/// Inserts a string into the lines data at the given `index`.
-pub fn insert_str(lines: &mut Lines, index: &mut Index2, text: &str, highlighter: &mut Highlighter) {
-    for (i, ch) in text.chars().enumerate() {
-        let is_last = i == text.len().saturating_sub(1);
-        insert_char(lines, index, ch, is_last, highlighter);
+/// Inserts a string into the lines data at the given `index`. The cursor
+/// is placed on the last inserted character.
+pub fn insert_str<T: TextStorage>(lines: &mut T, index: &mut Index2, text: &str, highlighter: &mut Highlighter) {
+    let end = insert_text(lines, index, text, highlighter);
+    *index = end;
+    if !text.is_empty() && !text.ends_with('\n') {
+        index.col = index.col.saturating_sub(1);
     }
 }
 
 /// Appends a string into the lines data next to a given `index`.
-pub fn append_str(lines: &mut Lines, index: &mut Index2, text: &str, highlighter: &mut Highlighter) {
+pub fn append_str<T: TextStorage>(lines: &mut T, index: &mut Index2, text: &str, highlighter: &mut Highlighter) {
     if !lines.is_empty() && lines.len_col(index.row) > Some(0) {
         index.col += 1;
     }
-    for ch in text.chars() {
-        insert_char(lines, index, ch, false, highlighter);
-    }
+    *index = insert_text(lines, index, text, highlighter);
     index.col = index.col.saturating_sub(1);
 }
 
+/// Inserts a string at a given index with a single edit of the lines data.
+/// Returns the index right behind the inserted text.
+pub(crate) fn insert_text<T: TextStorage>(
+    lines: &mut T,
+    index: &Index2,
+    text: &str,
+    highlighter: &mut Highlighter,
+) -> Index2 {
+    if lines.len() == 0 {
+        lines.push_line("");
+        highlighter.insert_line(0);
+    }
+    let mut start = *index;
+    start.col = start.col.min(max_col(lines, &start, EditorMode::Insert));
+    lines.insert_str(start, text);
+
+    let line_breaks = text.matches('\n').count();
+    let last_len = text.rsplit('\n').next().map_or(0, |s| s.chars().count());
+    highlighter.edit(start.row);
+    highlighter.insert_lines(start.row + 1, line_breaks);
+    if line_breaks == 0 {
+        Index2::new(start.row, start.col + last_len)
+    } else {
+        Index2::new(start.row + line_breaks, last_len)
+    }
+}
+
 /// Inserts a line break at a given index. Forces a splitting of lines if
 /// the index is in the middle of a line.
-pub(crate) fn line_break(lines: &mut Lines, index: &mut Index2, highlighter: &mut Highlighter) {
+pub(crate) fn line_break<T: TextStorage>(lines: &mut T, index: &mut Index2, highlighter: &mut Highlighter) {
     if index.col == 0 {
-        lines.insert(RowIndex::new(index.row), vec![]);
-        highlighter.insert_line(index.row, &"".to_string());
+        lines.insert_line(index.row, "");
+        highlighter.insert_line(index.row);
     } else {
         // Split the line at the cursor position
-        let mut rest = lines.split_off(*index);
-        // Create a new line with the rest of the characters
-        lines.append(&mut rest);
+        lines.split_line(*index);
         // Notify highlighter that the current line has been edited and that the next line has been inserted
-        let y = index.row;
-        let lines = lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>();
-        highlighter.insert_line(y + 1, &lines[y + 1]);
-        highlighter.edit(y, &lines[y]);
+        highlighter.edit(index.row);
+        highlighter.insert_line(index.row + 1);
     }
     index.row += 1;
     index.col = 0;
@@ -64,7 +92,7 @@ pub(crate) fn line_break(lines: &mut Lines, index: &mut Index2, highlighter: &mu
 
 /// Returns the maximum permissible column value. In normal or visual
 /// mode the limit is len() - 1, in insert mode the limit is len().
-pub(crate) fn max_col(lines: &Lines, index: &Index2, mode: EditorMode) -> usize {
+pub(crate) fn max_col<T: TextStorage>(lines: &T, index: &Index2, mode: EditorMode) -> usize {
     if lines.is_empty() {
         return 0;
     }
@@ -100,9 +128,9 @@ pub(crate) fn set_selection(selection: &mut Option<Selection>, end: Index2) {
 }
 
 /// Skip whitespaces moving to the right. Stop at the end of the line.
-pub(crate) fn skip_whitespace(lines: &Lines, index: &mut Index2) {
-    if let Some(line) = lines.get(RowIndex::new(index.row)) {
-        for (i, &ch) in line.iter().enumerate().skip(index.col) {
+pub(crate) fn skip_whitespace<T: TextStorage>(lines: &T, index: &mut Index2) {
+    if let Some(line) = lines.line(index.row) {
+        for (i, ch) in line.chars().enumerate().skip(index.col) {
             if !ch.is_ascii_whitespace() {
                 index.col = i;
                 break;
@@ -112,10 +140,10 @@ pub(crate) fn skip_whitespace(lines: &Lines, index: &mut Index2) {
 }
 
 /// Skip whitespaces moving to the left. Stop at the start of the line.
-pub(crate) fn skip_whitespace_rev(lines: &Lines, index: &mut Index2) {
-    if let Some(line) = lines.get(RowIndex::new(index.row)) {
-        let skip = line.len().saturating_sub(index.col + 1);
-        for &ch in line.iter().rev().skip(skip) {
+pub(crate) fn skip_whitespace_rev<T: TextStorage>(lines: &T, index: &mut Index2) {
+    if let Some(line) = lines.line(index.row) {
+        let skip = line.chars().count().saturating_sub(index.col + 1);
+        for ch in line.chars().rev().skip(skip) {
             if !ch.is_ascii_whitespace() {
                 break;
             }
@@ -124,6 +152,16 @@ pub(crate) fn skip_whitespace_rev(lines: &Lines, index: &mut Index2) {
     }
 }
 
+/// Returns the first and the last position of the word under an index.
+pub(crate) fn word_at<T: TextStorage>(lines: &T, index: Index2) -> Option<(Index2, Index2)> {
+    let chars = lines.line(index.row)?.chars().collect::<Vec<_>>();
+    let class = *chars.get(index.col)?;
+    let same_class = |col: &usize| is_same_word_class(chars.get(*col).copied(), Some(class));
+    let start = (0..index.col).rev().take_while(same_class).last().unwrap_or(index.col);
+    let end = (index.col + 1..chars.len()).take_while(same_class).last().unwrap_or(index.col);
+    Some((Index2::new(index.row, start), Index2::new(index.row, end)))
+}
+
 /// Get the number of columns in the current line.
 #[must_use]
 pub(crate) fn len_col(state: &EditorState) -> usize {
@@ -133,11 +171,12 @@ pub(crate) fn len_col(state: &EditorState) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Lines;
 
     fn test_lines() -> (Lines, Highlighter) {
         let lines = Lines::from("Hello World!\n\n123.");
-        let mut highlighter = Highlighter::new(4);
-        highlighter.run(&lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>());
+        let mut highlighter = Highlighter::default();
+        highlighter.reset(lines.len());
 
         (lines, highlighter)
     }
@@ -0,0 +1,160 @@
+//! Syntax highlighting
+//!
+//! ## Backends
+//!
+//! All highlighting goes through the [`HighlighterTrait`]. Edits only notify the
+//! highlighter about the rows that changed, the actual tokenization happens lazily
+//! in [`HighlighterTrait::update`], which the view calls with the visible rows.
+//! Two backends are provided:
+//!
+//! - [`SynopticHighlighter`]: Regex based rules from the `synoptic` crate. Supports
+//!   many languages, but the rules can misfire on nested constructs.
+//! - `TreeSitterHighlighter`: Parses the text with a tree-sitter grammar. Requires
+//!   the "tree-sitter" feature flag, which compiles in the grammars for Rust,
+//!   Python, JSON and TOML.
+//!
+//! Tokens are named after the colors in [`Highlighting`](crate::view::theme::Highlighting).
+//!
+//! ## Example: Choosing a backend
+//!
+//! ```
+//! use edtui_papier::{highlight::SynopticHighlighter, EditorState, Lines};
+//!
+//! let mut state = EditorState::new(Lines::from("fn main() {}"), "rs");
+//! state.set_highlighter(SynopticHighlighter::new("rs"));
+//! ```
+mod synoptic;
+#[cfg(feature = "tree-sitter")]
+mod tree_sitter;
+
+use std::ops::Range;
+
+use ::synoptic::TokOpt;
+
+pub use self::synoptic::SynopticHighlighter;
+#[cfg(feature = "tree-sitter")]
+pub use self::tree_sitter::TreeSitterHighlighter;
+use crate::{text::TextStorage, Lines};
+
+/// Trait defining syntax highlighting operations.
+pub trait HighlighterTrait {
+    /// Invalidates all rows of a text with `len` rows.
+    fn reset(&mut self, len: usize);
+
+    /// Marks a row as edited.
+    fn edit(&mut self, row: usize);
+
+    /// Notifies the highlighter that `count` rows were inserted at `row`.
+    fn insert_lines(&mut self, row: usize, count: usize);
+
+    /// Notifies the highlighter that `count` rows were removed at `row`.
+    fn remove_lines(&mut self, row: usize, count: usize);
+
+    /// Highlights the invalid rows within `rows`.
+    fn update(&mut self, lines: &Lines, rows: Range<usize>);
+
+    /// Returns the tokens of a row. Returns None if the row was not
+    /// highlighted yet, see [`HighlighterTrait::update`].
+    fn line(&self, row: usize) -> Option<&[TokOpt]>;
+}
+
+/// A syntax highlighter for the editor.
+///
+/// This struct can hold any type that implements [`HighlighterTrait`].
+pub struct Highlighter(Box<dyn HighlighterTrait>);
+
+impl Default for Highlighter {
+    /// Creates a highlighter without syntax definitions.
+    fn default() -> Self {
+        Self::new(SynopticHighlighter::default())
+    }
+}
+
+impl Highlighter {
+    /// Creates a new `Highlighter` instance with a provided backend.
+    #[must_use]
+    pub fn new(highlighter: impl HighlighterTrait + 'static) -> Self {
+        Self(Box::new(highlighter))
+    }
+
+    /// Invalidates all rows of a text with `len` rows.
+    pub fn reset(&mut self, len: usize) {
+        self.0.reset(len);
+    }
+
+    /// Marks a row as edited.
+    pub fn edit(&mut self, row: usize) {
+        self.0.edit(row);
+    }
+
+    /// Notifies the highlighter that `count` rows were inserted at `row`.
+    pub fn insert_lines(&mut self, row: usize, count: usize) {
+        if count > 0 {
+            self.0.insert_lines(row, count);
+        }
+    }
+
+    /// Notifies the highlighter that a row was inserted.
+    pub fn insert_line(&mut self, row: usize) {
+        self.insert_lines(row, 1);
+    }
+
+    /// Notifies the highlighter that `count` rows were removed at `row`.
+    pub fn remove_lines(&mut self, row: usize, count: usize) {
+        if count > 0 {
+            self.0.remove_lines(row, count);
+        }
+    }
+
+    /// Notifies the highlighter that a row was removed.
+    pub fn remove_line(&mut self, row: usize) {
+        self.remove_lines(row, 1);
+    }
+
+    /// Notifies the highlighter that the text `old` was replaced with `new`
+    /// as a whole, e.g. by an undo. Only the rows that differ are invalidated.
+    pub fn replace(&mut self, old: &Lines, new: &Lines) {
+        let min_len = old.len().min(new.len());
+        let prefix = (0..min_len).take_while(|&row| old.line(row) == new.line(row)).count();
+        let suffix =
+            (0..min_len - prefix).take_while(|&i| old.line(old.len() - 1 - i) == new.line(new.len() - 1 - i)).count();
+        self.remove_lines(prefix, old.len() - prefix - suffix);
+        self.insert_lines(prefix, new.len() - prefix - suffix);
+        self.edit(prefix);
+    }
+
+    /// Highlights the invalid rows within `rows`.
+    pub fn update(&mut self, lines: &Lines, rows: Range<usize>) {
+        self.0.update(lines, rows);
+    }
+
+    /// Returns the tokens of a row. Returns None if the row was not
+    /// highlighted yet, see [`Highlighter::update`].
+    #[must_use]
+    pub fn line(&self, row: usize) -> Option<&[TokOpt]> {
+        self.0.line(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_comment(highlighter: &Highlighter, row: usize) -> bool {
+        matches!(highlighter.line(row), Some([TokOpt::Some(_, name)]) if name == "comment")
+    }
+
+    #[test]
+    fn test_replace() {
+        let old = Lines::from("/*\nlet a = 1;\n*/\nlet b = 2;");
+        let new = Lines::from("/*\nlet a = 1;\nlet b = 2;");
+        let mut highlighter = Highlighter::new(SynopticHighlighter::new("rs"));
+        highlighter.update(&old, 0..4);
+
+        highlighter.replace(&old, &new);
+        assert!(highlighter.line(0).is_some());
+        assert!(highlighter.line(2).is_none());
+        highlighter.update(&new, 0..3);
+        assert!(is_comment(&highlighter, 2));
+    }
+}
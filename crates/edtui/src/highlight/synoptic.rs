@@ -0,0 +1,194 @@
+//! Regex based highlighting with `synoptic`
+//!
+//! Tokens that span multiple rows, such as block comments, are tracked through
+//! the name of the token that is still open at the end of a row. Re-tokenizing
+//! stops as soon as a row ends in the same state in which the next row was
+//! tokenized before. Interpolations spanning multiple rows are not tracked.
+use std::ops::Range;
+
+use synoptic::{from_extension, TokOpt, TokenRef};
+
+use super::HighlighterTrait;
+use crate::{text::TextStorage, Lines};
+
+/// The display width of a tab.
+const TAB_WIDTH: usize = 4;
+
+/// The cached tokens of a single row.
+#[derive(Debug, Clone, Default)]
+struct Row {
+    /// The tokens of the row. Tabs are expanded to spaces.
+    tokens: Vec<TokOpt>,
+    /// The token that was open at the start of the row.
+    open_in: Option<String>,
+    /// The token that is still open at the end of the row.
+    open_out: Option<String>,
+    /// Whether the tokens are up to date.
+    valid: bool,
+}
+
+/// A syntax highlighter based on the regex rules of `synoptic`.
+pub struct SynopticHighlighter {
+    /// The language definitions, also used as scratch space for tokenizing.
+    syntax: synoptic::Highlighter,
+    /// The cached rows.
+    rows: Vec<Row>,
+    /// The first row that is not up to date.
+    first_invalid: usize,
+}
+
+impl Default for SynopticHighlighter {
+    /// Creates a highlighter without syntax definitions.
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl SynopticHighlighter {
+    /// Creates a new highlighter for a file extension. Falls back to
+    /// plain text if the extension is not known.
+    #[must_use]
+    pub fn new(ext: &str) -> Self {
+        Self {
+            syntax: from_extension(ext, TAB_WIDTH).unwrap_or(synoptic::Highlighter::new(TAB_WIDTH)),
+            rows: Vec::new(),
+            first_invalid: 0,
+        }
+    }
+
+    /// Returns the name of the token that is open after the last appended row.
+    fn open_token(&self) -> Option<String> {
+        match self.syntax.tokens.last() {
+            Some(TokenRef::Bounded { name, end: None, .. }) => Some(name.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl HighlighterTrait for SynopticHighlighter {
+    fn reset(&mut self, len: usize) {
+        self.rows = vec![Row::default(); len];
+        self.first_invalid = 0;
+    }
+
+    fn edit(&mut self, row: usize) {
+        if let Some(cached) = self.rows.get_mut(row) {
+            cached.valid = false;
+            self.first_invalid = self.first_invalid.min(row);
+        }
+    }
+
+    fn insert_lines(&mut self, row: usize, count: usize) {
+        let row = row.min(self.rows.len());
+        self.rows.splice(row..row, (0..count).map(|_| Row::default()));
+        self.first_invalid = self.first_invalid.min(row);
+    }
+
+    fn remove_lines(&mut self, row: usize, count: usize) {
+        let end = (row + count).min(self.rows.len());
+        if row >= end {
+            return;
+        }
+        self.rows.drain(row..end);
+        // The following row starts in the state of the removed rows.
+        self.edit(row);
+    }
+
+    /// Highlights all invalid rows up to the end of `rows`.
+    ///
+    /// Rows behind `rows` are only re-tokenized until the state of multi-row
+    /// tokens settles, everything else is left for later calls.
+    fn update(&mut self, lines: &Lines, rows: Range<usize>) {
+        if self.rows.len() != lines.len() {
+            self.reset(lines.len());
+        }
+        let end = rows.end.min(self.rows.len());
+        while self.first_invalid < end {
+            // The tokenizer cannot start within a multi-row token, so
+            // rewind to the row where the token was opened.
+            let target = self.first_invalid;
+            let mut row = target;
+            while row > 0 && self.rows[row - 1].open_out.is_some() {
+                row -= 1;
+            }
+            self.syntax.run(&Vec::new());
+            let mut open = None;
+            loop {
+                let text = lines.line(row).unwrap_or_default();
+                self.syntax.append(&text);
+                let tokens = self.syntax.line(self.syntax.atoms.len() - 1, &text);
+                let open_out = self.open_token();
+                if open_out.is_none() {
+                    // Drop the scratch state as soon as no token is open.
+                    self.syntax.run(&Vec::new());
+                }
+                self.rows[row] = Row { tokens, open_in: open, open_out: open_out.clone(), valid: true };
+                open = open_out;
+                row += 1;
+
+                let Some(next) = self.rows.get_mut(row) else { break };
+                if row > target && next.valid && next.open_in == open {
+                    break;
+                }
+                if row >= end {
+                    next.valid = false;
+                    break;
+                }
+            }
+            self.first_invalid =
+                self.rows[row..].iter().position(|cached| !cached.valid).map_or(self.rows.len(), |i| row + i);
+        }
+    }
+
+    fn line(&self, row: usize) -> Option<&[TokOpt]> {
+        self.rows.get(row).filter(|cached| cached.valid).map(|cached| cached.tokens.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highlight::Highlighter;
+
+    fn names(highlighter: &Highlighter, row: usize) -> Vec<String> {
+        highlighter
+            .line(row)
+            .unwrap()
+            .iter()
+            .filter_map(|token| {
+                match token {
+                    TokOpt::Some(_, name) => Some(name.clone()),
+                    TokOpt::None(_) => None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_update_lazily() {
+        let lines = Lines::from("let a = 1;\nlet b = 2;\nlet c = 3;");
+        let mut highlighter = Highlighter::new(SynopticHighlighter::new("rs"));
+        highlighter.update(&lines, 0..2);
+        assert!(highlighter.line(1).is_some());
+        assert!(highlighter.line(2).is_none());
+        assert_eq!(names(&highlighter, 0), vec!["keyword", "operator", "digit"]);
+    }
+
+    #[test]
+    fn test_multi_row_token() {
+        let mut lines = Lines::from("let a = 1;\nlet b = 2;\nlet c = 3;");
+        let mut highlighter = Highlighter::new(SynopticHighlighter::new("rs"));
+        highlighter.update(&lines, 0..3);
+
+        lines.insert_str(crate::Index2::new(0, 0), "/* ");
+        highlighter.edit(0);
+        highlighter.update(&lines, 0..3);
+        assert_eq!(names(&highlighter, 2), vec!["comment"]);
+
+        lines.insert_str(crate::Index2::new(1, 0), "*/ ");
+        highlighter.edit(1);
+        highlighter.update(&lines, 0..3);
+        assert_eq!(names(&highlighter, 1), vec!["comment", "keyword", "operator", "digit"]);
+        assert_eq!(names(&highlighter, 2), vec!["keyword", "operator", "digit"]);
+    }
+}
@@ -0,0 +1,337 @@
+//! Highlighting with tree-sitter grammars
+//!
+//! Edits are collected into a single changed row range, which is applied to
+//! the previous syntax tree before the text is parsed again. Only the rows
+//! that were edited or whose syntax changed are queried again.
+use std::ops::Range;
+
+use synoptic::TokOpt;
+use tree_sitter::{InputEdit, Language, Parser, Point, Query, QueryCursor, Tree};
+
+use super::HighlighterTrait;
+use crate::{text::TextStorage, Lines};
+
+/// The display width of a tab.
+const TAB_WIDTH: usize = 4;
+
+/// Returns the grammar and the highlight query for a file extension.
+fn language(ext: &str) -> Option<(Language, &'static str)> {
+    match ext.to_lowercase().as_str() {
+        "rs" => Some((tree_sitter_rust::language(), tree_sitter_rust::HIGHLIGHT_QUERY)),
+        "py" | "pyw" => Some((tree_sitter_python::language(), tree_sitter_python::HIGHLIGHT_QUERY)),
+        "json" => Some((tree_sitter_json::language(), tree_sitter_json::HIGHLIGHT_QUERY)),
+        "toml" => Some((tree_sitter_toml::language(), tree_sitter_toml::HIGHLIGHT_QUERY)),
+        _ => None,
+    }
+}
+
+/// Maps a tree-sitter capture name to a [`Highlighting`](crate::view::theme::Highlighting)
+/// name. Returns None for captures that are not highlighted.
+fn highlighting_name(capture: &str) -> Option<&'static str> {
+    let name = match capture {
+        "function.macro" => "macro",
+        "string.special.key" | "property" => "key",
+        "escape" => "character",
+        "number" | "constant" | "constant.builtin" => "digit",
+        "constructor" => "struct",
+        "variable.builtin" => "reference",
+        "label" => "tag",
+        "module" => "namespace",
+        _ => {
+            match capture.split('.').next().unwrap_or_default() {
+                "keyword" => "keyword",
+                "function" => "function",
+                "type" => "type",
+                "string" => "string",
+                "comment" => "comment",
+                "attribute" => "attribute",
+                "operator" => "operator",
+                "tag" => "tag",
+                _ => return None,
+            }
+        },
+    };
+    Some(name)
+}
+
+/// The rows changed since the last parse.
+#[derive(Debug, Clone, Copy)]
+struct Changes {
+    /// The first changed row.
+    start: usize,
+    /// The number of unchanged rows at the end of the text.
+    unchanged_tail: usize,
+}
+
+/// A syntax highlighter based on tree-sitter grammars.
+pub struct TreeSitterHighlighter {
+    parser: Parser,
+    query: Query,
+    /// The highlighting name of each capture of the query.
+    names: Vec<Option<&'static str>>,
+    /// The syntax tree and the text it was parsed from.
+    tree: Option<(Tree, Lines)>,
+    /// The cached tokens of each row, None if the row is invalid.
+    rows: Vec<Option<Vec<TokOpt>>>,
+    /// The rows changed since the last parse.
+    changes: Option<Changes>,
+}
+
+impl TreeSitterHighlighter {
+    /// Creates a new highlighter for a file extension. Returns None if no
+    /// grammar is compiled in for the extension.
+    #[must_use]
+    pub fn new(ext: &str) -> Option<Self> {
+        let (language, source) = language(ext)?;
+        let mut parser = Parser::new();
+        parser.set_language(language).ok()?;
+        let query = Query::new(language, source).ok()?;
+        let names = query.capture_names().iter().map(|name| highlighting_name(name)).collect();
+        Some(Self { parser, query, names, tree: None, rows: Vec::new(), changes: None })
+    }
+
+    /// Records that the rows from `start` up to the last `unchanged_tail` rows
+    /// have changed.
+    fn change(&mut self, start: usize, unchanged_tail: usize) {
+        let changes = self.changes.get_or_insert(Changes { start, unchanged_tail });
+        changes.start = changes.start.min(start);
+        changes.unchanged_tail = changes.unchanged_tail.min(unchanged_tail);
+    }
+
+    /// Parses the text, reusing the previous syntax tree if possible.
+    /// Invalidates the rows whose syntax changed.
+    fn parse(&mut self, lines: &Lines) {
+        let rope = lines.inner();
+        let mut read = |byte: usize, _: Point| {
+            if byte >= rope.len_bytes() {
+                return &[][..];
+            }
+            let (chunk, chunk_byte, _, _) = rope.chunk_at_byte(byte);
+            &chunk.as_bytes()[byte - chunk_byte..]
+        };
+
+        let old_tree = match (self.tree.take(), self.changes.take()) {
+            (Some((mut tree, old)), Some(changes)) => {
+                // The changed ranges of the tree do not cover the edit itself.
+                let end = self.rows.len().saturating_sub(changes.unchanged_tail);
+                self.rows[changes.start.min(end)..end].fill(None);
+                tree.edit(&input_edit(&old, lines, changes));
+                Some(tree)
+            },
+            _ => None,
+        };
+        let Some(tree) = self.parser.parse_with(&mut read, old_tree.as_ref()) else {
+            return;
+        };
+        if let Some(old_tree) = &old_tree {
+            for range in tree.changed_ranges(old_tree) {
+                let end = (range.end_point.row + 1).min(self.rows.len());
+                for row in range.start_point.row.min(end)..end {
+                    self.rows[row] = None;
+                }
+            }
+        }
+        self.tree = Some((tree, lines.clone()));
+    }
+
+    /// Queries the tokens of the rows in `rows`.
+    fn query(&mut self, lines: &Lines, rows: Range<usize>) {
+        let Some((tree, _)) = &self.tree else { return };
+        let rope = lines.inner();
+        let texts: Vec<String> = rows.clone().filter_map(|row| lines.line(row)).collect();
+
+        // The capture painted on every char, along with the size of the
+        // captured node and the index of its pattern. Smaller nodes win over
+        // the nodes they are nested in, earlier patterns win for the same node.
+        let mut painted: Vec<Vec<Option<(usize, usize, usize)>>> =
+            texts.iter().map(|text| vec![None; text.chars().count()]).collect();
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_point_range(Point::new(rows.start, 0)..Point::new(rows.end, 0));
+        let text_provider = |node: tree_sitter::Node| rope.byte_slice(node.byte_range()).chunks().map(str::as_bytes);
+        for (query_match, i) in cursor.captures(&self.query, tree.root_node(), text_provider) {
+            let capture = query_match.captures[i];
+            if self.names[capture.index as usize].is_none() {
+                continue;
+            }
+            let node = capture.node;
+            let paint = (node.byte_range().len(), query_match.pattern_index, capture.index as usize);
+            let (start, end) = (node.start_position(), node.end_position());
+            for row in start.row.max(rows.start)..=end.row.min(rows.end - 1) {
+                let text = &texts[row - rows.start];
+                let start_col = if row == start.row { char_col(text, start.column) } else { 0 };
+                let end_col = if row == end.row { char_col(text, end.column) } else { text.chars().count() };
+                for slot in &mut painted[row - rows.start][start_col..end_col] {
+                    if slot.is_none_or(|(size, pattern, _)| (paint.0, paint.1) < (size, pattern)) {
+                        *slot = Some(paint);
+                    }
+                }
+            }
+        }
+
+        for (i, text) in texts.iter().enumerate() {
+            let names: Vec<Option<&str>> =
+                painted[i].iter().map(|slot| slot.and_then(|(_, _, capture)| self.names[capture])).collect();
+            self.rows[rows.start + i] = Some(tokenize(text, &names));
+        }
+    }
+}
+
+impl HighlighterTrait for TreeSitterHighlighter {
+    fn reset(&mut self, len: usize) {
+        self.rows = vec![None; len];
+        self.tree = None;
+        self.changes = None;
+    }
+
+    fn edit(&mut self, row: usize) {
+        if row < self.rows.len() {
+            self.rows[row] = None;
+            self.change(row, self.rows.len() - row - 1);
+        }
+    }
+
+    fn insert_lines(&mut self, row: usize, count: usize) {
+        let row = row.min(self.rows.len());
+        self.rows.splice(row..row, (0..count).map(|_| None));
+        self.change(row, self.rows.len() - row - count);
+    }
+
+    fn remove_lines(&mut self, row: usize, count: usize) {
+        let end = (row + count).min(self.rows.len());
+        if row >= end {
+            return;
+        }
+        self.rows.drain(row..end);
+        self.change(row, self.rows.len() - row);
+    }
+
+    fn update(&mut self, lines: &Lines, rows: Range<usize>) {
+        if self.rows.len() != lines.len() {
+            self.reset(lines.len());
+        }
+        if self.tree.is_none() || self.changes.is_some() {
+            self.parse(lines);
+        }
+        // Query the invalid rows in one go, from the first to the last one.
+        let end = rows.end.min(self.rows.len());
+        let first = (rows.start..end).find(|&row| self.rows[row].is_none());
+        let last = (rows.start..end).rev().find(|&row| self.rows[row].is_none());
+        if let (Some(first), Some(last)) = (first, last) {
+            self.query(lines, first..last + 1);
+        }
+    }
+
+    fn line(&self, row: usize) -> Option<&[TokOpt]> {
+        self.rows.get(row)?.as_deref()
+    }
+}
+
+/// Returns the edit that turns the text `old` into `new`.
+fn input_edit(old: &Lines, new: &Lines, changes: Changes) -> InputEdit {
+    let start_byte = byte_of_row(old, changes.start);
+    let old_end_byte = byte_of_row(old, old.len().saturating_sub(changes.unchanged_tail));
+    let new_end_byte = byte_of_row(new, new.len().saturating_sub(changes.unchanged_tail));
+    InputEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte,
+        start_position: point_of_byte(old, start_byte),
+        old_end_position: point_of_byte(old, old_end_byte),
+        new_end_position: point_of_byte(new, new_end_byte),
+    }
+}
+
+/// Returns the byte offset of the start of a row. Rows out of bounds
+/// return the end of the text.
+fn byte_of_row(lines: &Lines, row: usize) -> usize {
+    if row >= lines.len() {
+        return lines.len_bytes();
+    }
+    lines.inner().line_to_byte(row)
+}
+
+/// Converts a byte offset into a tree-sitter point.
+fn point_of_byte(lines: &Lines, byte: usize) -> Point {
+    let row = lines.inner().byte_to_line(byte);
+    Point::new(row, byte - lines.inner().line_to_byte(row))
+}
+
+/// Converts a byte column into a char column.
+fn char_col(text: &str, byte_col: usize) -> usize {
+    text.char_indices().take_while(|(i, _)| *i < byte_col).count()
+}
+
+/// Splits a row into tokens, given the highlighting name of every char.
+/// Tabs are expanded to spaces.
+fn tokenize(text: &str, names: &[Option<&str>]) -> Vec<TokOpt> {
+    let mut tokens: Vec<TokOpt> = Vec::new();
+    for (ch, name) in text.chars().zip(names) {
+        let ch = if ch == '\t' { " ".repeat(TAB_WIDTH) } else { ch.to_string() };
+        match (tokens.last_mut(), name) {
+            (Some(TokOpt::Some(last, last_name)), Some(name)) if last_name == name => last.push_str(&ch),
+            (Some(TokOpt::None(last)), None) => last.push_str(&ch),
+            (_, Some(name)) => tokens.push(TokOpt::Some(ch, (*name).to_string())),
+            (_, None) => tokens.push(TokOpt::None(ch)),
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{highlight::Highlighter, Index2};
+
+    fn names(highlighter: &Highlighter, row: usize) -> Vec<String> {
+        highlighter
+            .line(row)
+            .unwrap()
+            .iter()
+            .filter_map(|token| {
+                match token {
+                    TokOpt::Some(text, name) => Some(format!("{text}:{name}")),
+                    TokOpt::None(_) => None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight() {
+        let lines = Lines::from("fn main() {\n    let s = \"a // b\";\n}");
+        let mut highlighter = Highlighter::new(TreeSitterHighlighter::new("rs").unwrap());
+        highlighter.update(&lines, 0..3);
+        assert_eq!(names(&highlighter, 0), vec!["fn:keyword", "main:function"]);
+        assert_eq!(names(&highlighter, 1), vec!["let:keyword", "\"a // b\":string"]);
+    }
+
+    #[test]
+    fn test_incremental_edit() {
+        let mut lines = Lines::from("let a = 1;\nlet b = 2;\nlet c = 3;");
+        let mut highlighter = Highlighter::new(TreeSitterHighlighter::new("rs").unwrap());
+        highlighter.update(&lines, 0..3);
+
+        lines.insert_str(Index2::new(0, 0), "/* ");
+        highlighter.edit(0);
+        lines.insert_str(Index2::new(1, 10), " */");
+        highlighter.edit(1);
+        highlighter.update(&lines, 0..3);
+        assert_eq!(names(&highlighter, 0), vec!["/* let a = 1;:comment"]);
+        assert_eq!(names(&highlighter, 1), vec!["let b = 2; */:comment"]);
+        assert_eq!(names(&highlighter, 2), vec!["let:keyword", "3:digit"]);
+
+        lines.remove_line(0);
+        highlighter.remove_line(0);
+        lines.insert_line(1, "let s = \"é\";");
+        highlighter.insert_line(1);
+        highlighter.update(&lines, 0..3);
+
+        let mut fresh = Highlighter::new(TreeSitterHighlighter::new("rs").unwrap());
+        fresh.update(&lines, 0..3);
+        for row in 0..3 {
+            assert_eq!(names(&highlighter, row), names(&fresh, row));
+        }
+        assert_eq!(names(&highlighter, 1), vec!["let:keyword", "\"é\":string"]);
+    }
+}
@@ -1,24 +1,27 @@
 //! Handles key input events
 pub mod key;
+mod mouse;
 pub mod register;
 
 use std::fmt::Debug;
 
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
 use serde::{Deserialize, Serialize};
 
 use self::{
     key::Key,
+    mouse::Mouse,
     register::{Register, RegisterKey},
 };
 use crate::{
     actions::{
         motion::{MoveToFirstLine, MoveToLastLine, MoveWordForwardEnd},
         search::StartSearch,
-        Action, Append, AppendCharToSearch, AppendNewline, Composed, CopySelection, Custom, DeleteChar, DeleteLine,
-        DeleteSelection, Execute, FindNext, FindPrevious, InsertChar, InsertNewline, LineBreak, MoveBackward, MoveDown,
-        MoveForward, MoveToEnd, MoveToFirst, MoveToStart, MoveUp, MoveWordBackward, MoveWordForwardStart, Paste, Redo,
-        RemoveChar, RemoveCharFromSearch, SelectBetween, StopSearch, SwitchMode, TriggerSearch, Undo,
+        Action, AddCursorAbove, AddCursorAtNextMatch, AddCursorBelow, Append, AppendCharToSearch, AppendNewline,
+        Composed, CopySelection, Custom, DeleteChar, DeleteLine, DeleteSelection, Execute, FindNext, FindPrevious,
+        InsertChar, InsertNewline, InsertText, LineBreak, MoveBackward, MoveDown, MoveForward, MoveToEnd, MoveToFirst,
+        MoveToStart, MoveUp, MoveWordBackward, MoveWordForwardStart, Paste, Redo, RemoveChar, RemoveCharFromSearch,
+        RemoveCursors, SelectAllMatches, SelectBetween, StopSearch, SwitchMode, TriggerSearch, Undo,
     },
     state::command::CommandState,
     EditorMode, EditorState,
@@ -31,6 +34,7 @@ where
 {
     pub register: Register<I>,
     pub command: CommandState<I>,
+    mouse: Mouse,
 }
 
 impl<I> Default for Input<I>
@@ -42,129 +46,147 @@ where
         let mut r = Register::<I>::new();
 
         // Go into normal mode
-        r.insert(RegisterKey::i(vec![Key::Esc]), Action::SwitchMode(SwitchMode(EditorMode::Normal)));
-        r.insert(RegisterKey::v(vec![Key::Esc]), SwitchMode(EditorMode::Normal));
+        r.insert(RegisterKey::i(vec![Key::ESC]), Action::SwitchMode(SwitchMode(EditorMode::Normal)));
+        r.insert(RegisterKey::v(vec![Key::ESC]), SwitchMode(EditorMode::Normal));
 
         // Go into insert mode
-        r.insert(RegisterKey::n(vec![Key::Char('i')]), SwitchMode(EditorMode::Insert));
+        r.insert(RegisterKey::n(vec![Key::char('i')]), SwitchMode(EditorMode::Insert));
 
         // Go into visual mode
-        r.insert(RegisterKey::n(vec![Key::Char('v')]), SwitchMode(EditorMode::Visual));
+        r.insert(RegisterKey::n(vec![Key::char('v')]), SwitchMode(EditorMode::Visual));
 
         // Goes into search mode and starts of a new search.
-        r.insert(RegisterKey::n(vec![Key::Char('/')]), StartSearch);
+        r.insert(RegisterKey::n(vec![Key::char('/')]), StartSearch);
         // Trigger initial search
-        r.insert(RegisterKey::s(vec![Key::Enter]), TriggerSearch);
+        r.insert(RegisterKey::s(vec![Key::ENTER]), TriggerSearch);
         // Find next
-        r.insert(RegisterKey::n(vec![Key::Char('n')]), FindNext);
+        r.insert(RegisterKey::n(vec![Key::char('n')]), FindNext);
         // Find previous
-        r.insert(RegisterKey::n(vec![Key::Char('N')]), FindPrevious);
+        r.insert(RegisterKey::n(vec![Key::char('N')]), FindPrevious);
         // Clear search
-        r.insert(RegisterKey::s(vec![Key::Esc]), StopSearch);
+        r.insert(RegisterKey::s(vec![Key::ESC]), StopSearch);
         // Delete last character from search
-        r.insert(RegisterKey::s(vec![Key::Backspace]), RemoveCharFromSearch);
+        r.insert(RegisterKey::s(vec![Key::BACKSPACE]), RemoveCharFromSearch);
 
         // Go into insert mode and move one char forward
-        r.insert(RegisterKey::n(vec![Key::Char('a')]), Append);
+        r.insert(RegisterKey::n(vec![Key::char('a')]), Append);
 
         // Move cursor right
-        r.insert(RegisterKey::n(vec![Key::Char('l')]), MoveForward(1));
-        r.insert(RegisterKey::n(vec![Key::Right]), MoveForward(1));
-        r.insert(RegisterKey::v(vec![Key::Char('l')]), MoveForward(1));
-        r.insert(RegisterKey::v(vec![Key::Right]), MoveForward(1));
-        r.insert(RegisterKey::i(vec![Key::Right]), MoveForward(1));
+        r.insert(RegisterKey::n(vec![Key::char('l')]), MoveForward(1));
+        r.insert(RegisterKey::n(vec![Key::RIGHT]), MoveForward(1));
+        r.insert(RegisterKey::v(vec![Key::char('l')]), MoveForward(1));
+        r.insert(RegisterKey::v(vec![Key::RIGHT]), MoveForward(1));
+        r.insert(RegisterKey::i(vec![Key::RIGHT]), MoveForward(1));
 
         // Move cursor left
-        r.insert(RegisterKey::n(vec![Key::Char('h')]), MoveBackward(1));
-        r.insert(RegisterKey::n(vec![Key::Left]), MoveBackward(1));
-        r.insert(RegisterKey::v(vec![Key::Char('h')]), MoveBackward(1));
-        r.insert(RegisterKey::v(vec![Key::Left]), MoveBackward(1));
-        r.insert(RegisterKey::i(vec![Key::Left]), MoveBackward(1));
+        r.insert(RegisterKey::n(vec![Key::char('h')]), MoveBackward(1));
+        r.insert(RegisterKey::n(vec![Key::LEFT]), MoveBackward(1));
+        r.insert(RegisterKey::v(vec![Key::char('h')]), MoveBackward(1));
+        r.insert(RegisterKey::v(vec![Key::LEFT]), MoveBackward(1));
+        r.insert(RegisterKey::i(vec![Key::LEFT]), MoveBackward(1));
 
         // Move cursor up
-        r.insert(RegisterKey::n(vec![Key::Char('k')]), MoveUp(1));
-        r.insert(RegisterKey::n(vec![Key::Up]), MoveUp(1));
-        r.insert(RegisterKey::v(vec![Key::Char('k')]), MoveUp(1));
-        r.insert(RegisterKey::v(vec![Key::Up]), MoveUp(1));
-        r.insert(RegisterKey::i(vec![Key::Up]), MoveUp(1));
+        r.insert(RegisterKey::n(vec![Key::char('k')]), MoveUp(1));
+        r.insert(RegisterKey::n(vec![Key::UP]), MoveUp(1));
+        r.insert(RegisterKey::v(vec![Key::char('k')]), MoveUp(1));
+        r.insert(RegisterKey::v(vec![Key::UP]), MoveUp(1));
+        r.insert(RegisterKey::i(vec![Key::UP]), MoveUp(1));
 
         // Move cursor down
-        r.insert(RegisterKey::n(vec![Key::Char('j')]), MoveDown(1));
-        r.insert(RegisterKey::n(vec![Key::Down]), MoveDown(1));
-        r.insert(RegisterKey::v(vec![Key::Char('j')]), MoveDown(1));
-        r.insert(RegisterKey::v(vec![Key::Down]), MoveDown(1));
-        r.insert(RegisterKey::i(vec![Key::Down]), MoveDown(1));
+        r.insert(RegisterKey::n(vec![Key::char('j')]), MoveDown(1));
+        r.insert(RegisterKey::n(vec![Key::DOWN]), MoveDown(1));
+        r.insert(RegisterKey::v(vec![Key::char('j')]), MoveDown(1));
+        r.insert(RegisterKey::v(vec![Key::DOWN]), MoveDown(1));
+        r.insert(RegisterKey::i(vec![Key::DOWN]), MoveDown(1));
 
         // Move one word forward/backward
-        r.insert(RegisterKey::n(vec![Key::Char('w')]), MoveWordForwardStart(1));
-        r.insert(RegisterKey::n(vec![Key::Char('e')]), MoveWordForwardEnd(1));
-        r.insert(RegisterKey::n(vec![Key::Char('b')]), MoveWordBackward(1));
-        r.insert(RegisterKey::v(vec![Key::Char('w')]), MoveWordForwardStart(1));
-        r.insert(RegisterKey::v(vec![Key::Char('e')]), MoveWordForwardEnd(1));
-        r.insert(RegisterKey::v(vec![Key::Char('b')]), MoveWordBackward(1));
+        r.insert(RegisterKey::n(vec![Key::char('w')]), MoveWordForwardStart(1));
+        r.insert(RegisterKey::n(vec![Key::char('e')]), MoveWordForwardEnd(1));
+        r.insert(RegisterKey::n(vec![Key::char('b')]), MoveWordBackward(1));
+        r.insert(RegisterKey::v(vec![Key::char('w')]), MoveWordForwardStart(1));
+        r.insert(RegisterKey::v(vec![Key::char('e')]), MoveWordForwardEnd(1));
+        r.insert(RegisterKey::v(vec![Key::char('b')]), MoveWordBackward(1));
 
         // Move cursor to start/first/last position
-        r.insert(RegisterKey::n(vec![Key::Char('0')]), MoveToStart());
-        r.insert(RegisterKey::n(vec![Key::Char('_')]), MoveToFirst());
-        r.insert(RegisterKey::n(vec![Key::Char('$')]), MoveToEnd());
-        r.insert(RegisterKey::v(vec![Key::Char('0')]), MoveToStart());
-        r.insert(RegisterKey::v(vec![Key::Char('_')]), MoveToFirst());
-        r.insert(RegisterKey::v(vec![Key::Char('$')]), MoveToEnd());
-        r.insert(RegisterKey::n(vec![Key::Char('g'), Key::Char('g')]), MoveToFirstLine());
-        r.insert(RegisterKey::n(vec![Key::Char('G')]), MoveToLastLine());
+        r.insert(RegisterKey::n(vec![Key::char('0')]), MoveToStart());
+        r.insert(RegisterKey::n(vec![Key::char('_')]), MoveToFirst());
+        r.insert(RegisterKey::n(vec![Key::char('$')]), MoveToEnd());
+        r.insert(RegisterKey::v(vec![Key::char('0')]), MoveToStart());
+        r.insert(RegisterKey::v(vec![Key::char('_')]), MoveToFirst());
+        r.insert(RegisterKey::v(vec![Key::char('$')]), MoveToEnd());
+        r.insert(RegisterKey::n(vec![Key::HOME]), MoveToStart());
+        r.insert(RegisterKey::v(vec![Key::HOME]), MoveToStart());
+        r.insert(RegisterKey::i(vec![Key::HOME]), MoveToStart());
+        r.insert(RegisterKey::n(vec![Key::END]), MoveToEnd());
+        r.insert(RegisterKey::v(vec![Key::END]), MoveToEnd());
+        r.insert(RegisterKey::i(vec![Key::END]), MoveToEnd());
+        r.insert(RegisterKey::n(vec![Key::char('g'), Key::char('g')]), MoveToFirstLine());
+        r.insert(RegisterKey::n(vec![Key::char('G')]), MoveToLastLine());
 
         // Move cursor to start/first/last position and enter insert mode
         r.insert(
-            RegisterKey::n(vec![Key::Char('I')]),
+            RegisterKey::n(vec![Key::char('I')]),
             Composed::new(MoveToFirst()).chain(SwitchMode(EditorMode::Insert)),
         );
-        r.insert(RegisterKey::n(vec![Key::Char('A')]), Composed::new(MoveToEnd()).chain(Append));
+        r.insert(RegisterKey::n(vec![Key::char('A')]), Composed::new(MoveToEnd()).chain(Append));
 
         // Append/insert new line and switch into insert mode
         r.insert(
-            RegisterKey::n(vec![Key::Char('o')]),
+            RegisterKey::n(vec![Key::char('o')]),
             Composed::new(AppendNewline(1)).chain(SwitchMode(EditorMode::Insert)),
         );
         r.insert(
-            RegisterKey::n(vec![Key::Char('O')]),
+            RegisterKey::n(vec![Key::char('O')]),
             Composed::new(InsertNewline(1)).chain(SwitchMode(EditorMode::Insert)),
         );
 
         // Insert a line break
-        r.insert(RegisterKey::i(vec![Key::Enter]), LineBreak(1));
+        r.insert(RegisterKey::i(vec![Key::ENTER]), LineBreak(1));
 
         // Remove the current character
-        r.insert(RegisterKey::n(vec![Key::Char('x')]), RemoveChar(1));
+        r.insert(RegisterKey::n(vec![Key::char('x')]), RemoveChar(1));
+        r.insert(RegisterKey::n(vec![Key::DELETE]), RemoveChar(1));
+        r.insert(RegisterKey::i(vec![Key::DELETE]), RemoveChar(1));
 
         // Delete the previous character
-        r.insert(RegisterKey::i(vec![Key::Backspace]), DeleteChar(1));
+        r.insert(RegisterKey::i(vec![Key::BACKSPACE]), DeleteChar(1));
 
         // Delete the current line
-        r.insert(RegisterKey::n(vec![Key::Char('d'), Key::Char('d')]), DeleteLine(1));
+        r.insert(RegisterKey::n(vec![Key::char('d'), Key::char('d')]), DeleteLine(1));
 
         // Delete the current selection
-        r.insert(RegisterKey::v(vec![Key::Char('d')]), DeleteSelection);
+        r.insert(RegisterKey::v(vec![Key::char('d')]), DeleteSelection);
 
         // Select inner word between delimiters
         r.insert(
-            RegisterKey::n(vec![Key::Char('c'), Key::Char('i'), Key::Char('w')]),
+            RegisterKey::n(vec![Key::char('c'), Key::char('i'), Key::char('w')]),
             SelectBetween(vec![('(', ')'), ('[', ']'), ('{', '}'), ('<', '>'), ('"', '"'), ('\'', '\'')]),
         );
 
         // Undo
-        r.insert(RegisterKey::n(vec![Key::Char('u')]), Undo);
+        r.insert(RegisterKey::n(vec![Key::char('u')]), Undo);
 
         // Redo
-        r.insert(RegisterKey::n(vec![Key::Char('r')]), Redo);
+        r.insert(RegisterKey::n(vec![Key::char('r')]), Redo);
+        r.insert(RegisterKey::n(vec![Key::ctrl('r')]), Redo);
 
         // Copy
-        r.insert(RegisterKey::v(vec![Key::Char('y')]), CopySelection);
+        r.insert(RegisterKey::v(vec![Key::char('y')]), CopySelection);
 
         // Paste
-        r.insert(RegisterKey::n(vec![Key::Char('p')]), Paste);
-        r.insert(RegisterKey::v(vec![Key::Char('p')]), Paste);
-
-        Self { register: r, command: CommandState::default() }
+        r.insert(RegisterKey::n(vec![Key::char('p')]), Paste);
+        r.insert(RegisterKey::v(vec![Key::char('p')]), Paste);
+
+        // Multiple cursors
+        r.insert(RegisterKey::n(vec![Key::ctrl('n')]), AddCursorAtNextMatch);
+        r.insert(RegisterKey::v(vec![Key::ctrl('n')]), AddCursorAtNextMatch);
+        r.insert(RegisterKey::n(vec![Key::new(KeyCode::Down, KeyModifiers::CONTROL)]), AddCursorBelow);
+        r.insert(RegisterKey::n(vec![Key::new(KeyCode::Up, KeyModifiers::CONTROL)]), AddCursorAbove);
+        r.insert(RegisterKey::n(vec![Key::alt('n')]), SelectAllMatches);
+        r.insert(RegisterKey::s(vec![Key::new(KeyCode::Enter, KeyModifiers::ALT)]), SelectAllMatches);
+        r.insert(RegisterKey::n(vec![Key::ESC]), RemoveCursors);
+
+        Self { register: r, command: CommandState::default(), mouse: Mouse::default() }
     }
 }
 
@@ -176,26 +198,90 @@ where
     where
         T: Into<KeyEvent> + Copy,
     {
+        let custom = match key.into().code {
+            // Esc aborts a pending key sequence
+            KeyCode::Esc if !self.register.pending().is_empty() => {
+                self.register.abort();
+                None
+            },
+            _ => self.on_key_inner(key.into(), state),
+        };
+        state.pending_keys = self.register.pending().iter().map(|key| format_pending(*key)).collect();
+        custom
+    }
+
+    /// Handles a mouse event: clicks move the cursor, a drag selects text,
+    /// a double click selects a word and the wheel scrolls the viewport.
+    pub fn on_mouse(&mut self, event: MouseEvent, state: &mut EditorState) {
+        self.register.abort();
+        state.pending_keys.clear();
+        self.mouse.on_mouse(event, state);
+    }
+
+    /// Handles a bracketed paste. The text is inserted with a single edit,
+    /// see [`InsertText`], or appended to the command in command mode.
+    pub fn on_paste(&mut self, text: &str, state: &mut EditorState) {
+        self.register.abort();
+        state.pending_keys.clear();
+        if state.mode == EditorMode::Command {
+            for c in text.chars().filter(|c| !c.is_control()) {
+                self.command.push_char(c);
+            }
+            state.command.clone_from(&self.command.input);
+        } else {
+            state.execute_at_cursors(&mut InsertText(text.to_string()));
+        }
+    }
+
+    /// Resolves a pending key sequence once `timeoutlen` expired. Call this
+    /// regularly, e.g. on each tick of the event loop.
+    pub fn on_tick(&mut self, state: &mut EditorState) -> Option<Custom<I>> {
+        if self.register.pending().is_empty() {
+            return None;
+        }
+        let actions = self.register.tick(state.mode);
+        let custom = Self::execute(actions, state);
+        state.pending_keys = self.register.pending().iter().map(|key| format_pending(*key)).collect();
+        custom
+    }
+
+    /// Executes the editor actions and returns the last custom action.
+    fn execute(actions: Vec<Action<I>>, state: &mut EditorState) -> Option<Custom<I>> {
+        let mut custom = None;
+        for mut action in actions {
+            match action {
+                Action::Custom(action) => custom = Some(action),
+                _ if action.at_cursors() => state.execute_at_cursors(&mut action),
+                _ => action.execute(state),
+            }
+        }
+        custom
+    }
+
+    fn on_key_inner(&mut self, key: KeyEvent, state: &mut EditorState) -> Option<Custom<I>> {
         let mode = state.mode;
-        // r.insert(RegisterKey::n(vec![Key::Char(':')]), StartCommand);
-        // r.insert(RegisterKey::c(vec![Key::Esc]), StopCommand);
-        // r.insert(RegisterKey::c(vec![Key::Enter]), TriggerCommand);
-        // r.insert(RegisterKey::c(vec![Key::Backspace]), RemoveCharFromCommand);
-        match key.into().code {
+        // Characters typed with ctrl or alt are looked up in the register.
+        let is_text = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match key.code {
             // Always insert characters in insert mode
-            KeyCode::Char(c) if mode == EditorMode::Insert => InsertChar(c).execute(state),
+            KeyCode::Char(c) if mode == EditorMode::Insert && is_text => state.execute_at_cursors(&mut InsertChar(c)),
             // Always add characters to search in search mode
-            KeyCode::Char(c) if mode == EditorMode::Search => AppendCharToSearch(c).execute(state),
+            KeyCode::Char(c) if mode == EditorMode::Search && is_text => AppendCharToSearch(c).execute(state),
 
             KeyCode::Char(':') if mode == EditorMode::Normal => {
+                self.register.abort();
                 self.command.clear();
                 state.command.clone_from(&self.command.input);
                 state.mode = EditorMode::Command;
             },
-            KeyCode::Char(c) if mode == EditorMode::Command => {
+            KeyCode::Char(c) if mode == EditorMode::Command && is_text => {
                 self.command.push_char(c);
                 state.command.clone_from(&self.command.input);
             },
+            KeyCode::Tab if mode == EditorMode::Command => {
+                self.command.complete();
+                state.command.clone_from(&self.command.input);
+            },
             KeyCode::Backspace if mode == EditorMode::Command => {
                 self.command.remove_char();
                 state.command.clone_from(&self.command.input);
@@ -220,14 +306,19 @@ where
 
             // Else lookup an action from the register
             _ => {
-                if let Some(mut action) = self.register.get(key.into(), mode) {
-                    match action {
-                        Action::Custom(action) => return Some(action),
-                        _ => action.execute(state),
-                    }
-                }
+                let actions = self.register.get(key, mode);
+                return Self::execute(actions, state);
             },
         }
         None
     }
 }
+
+/// Formats a pending key for the status line. Characters are shown as
+/// typed, other keys like `<ctrl-w>`.
+fn format_pending(key: KeyEvent) -> String {
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => c.to_string(),
+        _ => format!("<{}>", Key::from(key)),
+    }
+}
@@ -1,46 +1,304 @@
-use crossterm::event::{KeyCode, KeyEvent};
+//! Keys and their string representation
+//!
+//! A key is written as its name, optionally prefixed with modifiers, e.g.
+//! `a`, `G`, `ctrl-r`, `shift-tab`, `alt-enter` or `f5`. The modifiers are
+//! `ctrl`, `shift`, `alt`, `super`, `hyper` and `meta`.
+use std::{error::Error, fmt, str::FromStr};
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub enum Key {
-    Char(char),
-    Down,
-    Up,
-    Right,
-    Left,
-    Enter,
-    Esc,
-    Backspace,
-    None,
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MediaKeyCode, ModifierKeyCode};
+
+/// A key with its modifiers.
+///
+/// Uppercase characters always carry the shift modifier, so `G` and
+/// `shift-g` are the same key.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub const BACKSPACE: Key = Key::new(KeyCode::Backspace, KeyModifiers::NONE);
+    pub const DELETE: Key = Key::new(KeyCode::Delete, KeyModifiers::NONE);
+    pub const DOWN: Key = Key::new(KeyCode::Down, KeyModifiers::NONE);
+    pub const END: Key = Key::new(KeyCode::End, KeyModifiers::NONE);
+    pub const ENTER: Key = Key::new(KeyCode::Enter, KeyModifiers::NONE);
+    pub const ESC: Key = Key::new(KeyCode::Esc, KeyModifiers::NONE);
+    pub const HOME: Key = Key::new(KeyCode::Home, KeyModifiers::NONE);
+    pub const LEFT: Key = Key::new(KeyCode::Left, KeyModifiers::NONE);
+    pub const RIGHT: Key = Key::new(KeyCode::Right, KeyModifiers::NONE);
+    pub const TAB: Key = Key::new(KeyCode::Tab, KeyModifiers::NONE);
+    pub const UP: Key = Key::new(KeyCode::Up, KeyModifiers::NONE);
+
+    /// Creates a new key.
+    #[must_use]
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let (code, modifiers) = match code {
+            KeyCode::Char(c) if c.is_ascii_uppercase() => (code, modifiers.union(KeyModifiers::SHIFT)),
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                (KeyCode::Char(c.to_ascii_uppercase()), modifiers)
+            },
+            KeyCode::BackTab => (code, modifiers.union(KeyModifiers::SHIFT)),
+            _ => (code, modifiers),
+        };
+        Self { code, modifiers }
+    }
+
+    /// Creates a character key.
+    #[must_use]
+    pub const fn char(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    /// Creates a character key with the control modifier.
+    #[must_use]
+    pub const fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    /// Creates a character key with the alt modifier.
+    #[must_use]
+    pub const fn alt(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::ALT)
+    }
+
+    /// Returns the key code.
+    #[must_use]
+    pub fn code(&self) -> KeyCode {
+        self.code
+    }
+
+    /// Returns the modifiers.
+    #[must_use]
+    pub fn modifiers(&self) -> KeyModifiers {
+        self.modifiers
+    }
+}
+
+impl From<KeyCode> for Key {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
 }
 
 impl From<KeyEvent> for Key {
     fn from(key: KeyEvent) -> Self {
-        match key.code {
-            KeyCode::Char(c) => Key::Char(c),
-            KeyCode::Enter => Key::Enter,
-            KeyCode::Down => Key::Down,
-            KeyCode::Up => Key::Up,
-            KeyCode::Right => Key::Right,
-            KeyCode::Left => Key::Left,
-            KeyCode::Esc => Key::Esc,
-            KeyCode::Backspace => Key::Backspace,
-            _ => Key::None,
-        }
+        Self::new(key.code, key.modifiers)
     }
 }
 
 impl From<Key> for KeyEvent {
     fn from(val: Key) -> Self {
-        match val {
-            Key::Char(c) => KeyEvent::from(KeyCode::Char(c)),
-            Key::Enter => KeyEvent::from(KeyCode::Enter),
-            Key::Down => KeyEvent::from(KeyCode::Down),
-            Key::Up => KeyEvent::from(KeyCode::Up),
-            Key::Right => KeyEvent::from(KeyCode::Right),
-            Key::Left => KeyEvent::from(KeyCode::Left),
-            Key::Esc => KeyEvent::from(KeyCode::Esc),
-            Key::Backspace => KeyEvent::from(KeyCode::Backspace),
-            Key::None => KeyEvent::from(KeyCode::Null),
+        KeyEvent::new(val.code, val.modifiers)
+    }
+}
+
+const MODIFIERS: [(KeyModifiers, &str); 6] = [
+    (KeyModifiers::CONTROL, "ctrl"),
+    (KeyModifiers::SHIFT, "shift"),
+    (KeyModifiers::ALT, "alt"),
+    (KeyModifiers::SUPER, "super"),
+    (KeyModifiers::HYPER, "hyper"),
+    (KeyModifiers::META, "meta"),
+];
+
+const NAMED_KEYS: [(KeyCode, &str); 24] = [
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::Enter, "enter"),
+    (KeyCode::Left, "left"),
+    (KeyCode::Right, "right"),
+    (KeyCode::Up, "up"),
+    (KeyCode::Down, "down"),
+    (KeyCode::Home, "home"),
+    (KeyCode::End, "end"),
+    (KeyCode::PageUp, "pageup"),
+    (KeyCode::PageDown, "pagedown"),
+    (KeyCode::Tab, "tab"),
+    (KeyCode::BackTab, "backtab"),
+    (KeyCode::Delete, "delete"),
+    (KeyCode::Insert, "insert"),
+    (KeyCode::Char(' '), "space"),
+    (KeyCode::Esc, "esc"),
+    (KeyCode::Null, "null"),
+    (KeyCode::CapsLock, "capslock"),
+    (KeyCode::ScrollLock, "scrolllock"),
+    (KeyCode::NumLock, "numlock"),
+    (KeyCode::PrintScreen, "printscreen"),
+    (KeyCode::Pause, "pause"),
+    (KeyCode::Menu, "menu"),
+    (KeyCode::KeypadBegin, "keypadbegin"),
+];
+
+const MEDIA_KEYS: [(MediaKeyCode, &str); 13] = [
+    (MediaKeyCode::Play, "play"),
+    (MediaKeyCode::Pause, "pause"),
+    (MediaKeyCode::PlayPause, "playpause"),
+    (MediaKeyCode::Reverse, "reverse"),
+    (MediaKeyCode::Stop, "stop"),
+    (MediaKeyCode::FastForward, "fastforward"),
+    (MediaKeyCode::Rewind, "rewind"),
+    (MediaKeyCode::TrackNext, "tracknext"),
+    (MediaKeyCode::TrackPrevious, "trackprevious"),
+    (MediaKeyCode::Record, "record"),
+    (MediaKeyCode::LowerVolume, "lowervolume"),
+    (MediaKeyCode::RaiseVolume, "raisevolume"),
+    (MediaKeyCode::MuteVolume, "mutevolume"),
+];
+
+const MODIFIER_KEYS: [(ModifierKeyCode, &str); 14] = [
+    (ModifierKeyCode::LeftShift, "leftshift"),
+    (ModifierKeyCode::LeftControl, "leftctrl"),
+    (ModifierKeyCode::LeftAlt, "leftalt"),
+    (ModifierKeyCode::LeftSuper, "leftsuper"),
+    (ModifierKeyCode::LeftHyper, "lefthyper"),
+    (ModifierKeyCode::LeftMeta, "leftmeta"),
+    (ModifierKeyCode::RightShift, "rightshift"),
+    (ModifierKeyCode::RightControl, "rightctrl"),
+    (ModifierKeyCode::RightAlt, "rightalt"),
+    (ModifierKeyCode::RightSuper, "rightsuper"),
+    (ModifierKeyCode::RightHyper, "righthyper"),
+    (ModifierKeyCode::RightMeta, "rightmeta"),
+    (ModifierKeyCode::IsoLevel3Shift, "isolevel3shift"),
+    (ModifierKeyCode::IsoLevel5Shift, "isolevel5shift"),
+];
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The shift of uppercase characters and backtab is implied.
+        let implied_shift = match self.code {
+            KeyCode::Char(c) => c.is_ascii_uppercase(),
+            KeyCode::BackTab => true,
+            _ => false,
+        };
+        for (modifier, name) in MODIFIERS {
+            if self.modifiers.contains(modifier) && !(modifier == KeyModifiers::SHIFT && implied_shift) {
+                write!(f, "{name}-")?;
+            }
+        }
+        if let Some((_, name)) = NAMED_KEYS.iter().find(|(code, _)| *code == self.code) {
+            return f.write_str(name);
+        }
+        match self.code {
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            KeyCode::Media(media) => {
+                let name = MEDIA_KEYS.iter().find(|(key, _)| *key == media).map_or("", |(_, name)| name);
+                write!(f, "media-{name}")
+            },
+            KeyCode::Modifier(modifier) => {
+                let name = MODIFIER_KEYS.iter().find(|(key, _)| *key == modifier).map_or("", |(_, name)| name);
+                write!(f, "modifier-{name}")
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An error returned when parsing a [`Key`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError(String);
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to parse key `{}`", self.0)
+    }
+}
+
+impl Error for ParseKeyError {
+}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let error = || ParseKeyError(raw.to_string());
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = raw;
+        'outer: loop {
+            for (modifier, name) in MODIFIERS {
+                let prefix = rest.get(..=name.len()).filter(|_| rest.len() > name.len() + 1);
+                if prefix.is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{name}-"))) {
+                    modifiers |= modifier;
+                    rest = &rest[name.len() + 1..];
+                    continue 'outer;
+                }
+            }
+            break;
+        }
+
+        let mut chars = rest.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key::new(KeyCode::Char(c), modifiers));
+        }
+        let name = rest.to_ascii_lowercase();
+        let code = if let Some((code, _)) = NAMED_KEYS.iter().find(|(_, key)| *key == name) {
+            *code
+        } else if let Some(media) = name.strip_prefix("media-") {
+            let (media, _) = MEDIA_KEYS.iter().find(|(_, key)| *key == media).ok_or_else(error)?;
+            KeyCode::Media(*media)
+        } else if let Some(modifier) = name.strip_prefix("modifier-") {
+            let (modifier, _) = MODIFIER_KEYS.iter().find(|(_, key)| *key == modifier).ok_or_else(error)?;
+            KeyCode::Modifier(*modifier)
+        } else if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            KeyCode::F(n)
+        } else {
+            match name.as_str() {
+                "hyphen" | "minus" => KeyCode::Char('-'),
+                "lt" => KeyCode::Char('<'),
+                "gt" => KeyCode::Char('>'),
+                _ => return Err(error()),
+            }
+        };
+        Ok(Key::new(code, modifiers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("a".parse(), Ok(Key::char('a')));
+        assert_eq!("ctrl-r".parse(), Ok(Key::ctrl('r')));
+        assert_eq!("CTRL-Alt-x".parse(), Ok(Key::new(KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT)));
+        assert_eq!("G".parse(), Ok(Key::new(KeyCode::Char('g'), KeyModifiers::SHIFT)));
+        assert_eq!("shift-tab".parse(), Ok(Key::new(KeyCode::Tab, KeyModifiers::SHIFT)));
+        assert_eq!("backtab".parse(), Ok(Key::new(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert_eq!("Home".parse(), Ok(Key::HOME));
+        assert_eq!("f12".parse(), Ok(Key::from(KeyCode::F(12))));
+        assert_eq!("ctrl--".parse(), Ok(Key::ctrl('-')));
+        assert_eq!("lt".parse(), Ok(Key::char('<')));
+        assert_eq!("media-play".parse(), Ok(Key::from(KeyCode::Media(MediaKeyCode::Play))));
+        assert_eq!("ctrl-".parse::<Key>(), Err(ParseKeyError("ctrl-".to_string())));
+        assert!("unknown".parse::<Key>().is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Key::char('a').to_string(), "a");
+        assert_eq!(Key::char('G').to_string(), "G");
+        assert_eq!(Key::new(KeyCode::Char('g'), KeyModifiers::SHIFT).to_string(), "G");
+        assert_eq!(Key::ctrl('r').to_string(), "ctrl-r");
+        assert_eq!(Key::new(KeyCode::Tab, KeyModifiers::SHIFT).to_string(), "shift-tab");
+        assert_eq!(Key::from(KeyCode::BackTab).to_string(), "backtab");
+        assert_eq!(Key::char(' ').to_string(), "space");
+        assert_eq!(Key::from(KeyCode::F(5)).to_string(), "f5");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let codes = NAMED_KEYS
+            .iter()
+            .map(|(code, _)| *code)
+            .chain(MEDIA_KEYS.iter().map(|(media, _)| KeyCode::Media(*media)))
+            .chain(MODIFIER_KEYS.iter().map(|(modifier, _)| KeyCode::Modifier(*modifier)))
+            .chain([KeyCode::F(1), KeyCode::F(24), KeyCode::Char('x'), KeyCode::Char('X'), KeyCode::Char('-')]);
+        for code in codes {
+            for (modifiers, _) in MODIFIERS {
+                let key = Key::new(code, modifiers | KeyModifiers::CONTROL);
+                assert_eq!(key.to_string().parse(), Ok(key), "{key}");
+            }
         }
     }
 }
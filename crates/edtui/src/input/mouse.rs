@@ -0,0 +1,206 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+
+use crate::{
+    helper::{max_col, set_selection, word_at},
+    state::selection::Selection,
+    text::TextStorage,
+    EditorMode, EditorState, Index2,
+};
+
+/// The maximum time between two clicks of a double click.
+pub const DOUBLE_CLICK_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The number of rows scrolled by one step of the mouse wheel.
+pub const SCROLL_ROWS: usize = 3;
+
+/// Tracks clicks and drags of the mouse.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Mouse {
+    last_click: Option<(Instant, Index2)>,
+    drag_start: Option<Index2>,
+}
+
+impl Mouse {
+    /// Handles a mouse event. The screen position is mapped to the text
+    /// through the areas of the last render.
+    pub(crate) fn on_mouse(&mut self, event: MouseEvent, state: &mut EditorState) {
+        match event.kind {
+            MouseEventKind::ScrollDown => scroll(state, true),
+            MouseEventKind::ScrollUp => scroll(state, false),
+            _ if matches!(state.mode, EditorMode::Search | EditorMode::Command) => {},
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some(position) = text_position(state, event) else {
+                    return;
+                };
+                // The secondary cursors are no longer where the user works
+                state.remove_cursors();
+                let now = Instant::now();
+                match self.last_click.take() {
+                    Some((time, last)) if last == position && now - time <= DOUBLE_CLICK_TIMEOUT => {
+                        select_word(state, position);
+                        self.drag_start = None;
+                    },
+                    _ => {
+                        if state.mode == EditorMode::Visual {
+                            state.mode = EditorMode::Normal;
+                            state.selection = None;
+                        }
+                        state.cursor = position;
+                        self.last_click = Some((now, position));
+                        self.drag_start = Some(position);
+                    },
+                }
+            },
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let (Some(start), Some(position)) = (self.drag_start, text_position(state, event)) else {
+                    return;
+                };
+                if state.mode != EditorMode::Visual {
+                    state.mode = EditorMode::Visual;
+                    state.selection = Some(Selection::new(start, start));
+                }
+                state.cursor = position;
+                set_selection(&mut state.selection, position);
+            },
+            MouseEventKind::Up(MouseButton::Left) => self.drag_start = None,
+            _ => {},
+        }
+    }
+}
+
+/// Returns the text position under the mouse, clamped to the text.
+fn text_position(state: &EditorState, event: MouseEvent) -> Option<Index2> {
+    let mut position = state.view.screen_to_text(event.column, event.row)?;
+    position.row = position.row.min(state.lines.len().saturating_sub(1));
+    position.col = position.col.min(max_col(&state.lines, &position, state.mode));
+    Some(position)
+}
+
+/// Selects the word under a position in visual mode.
+fn select_word(state: &mut EditorState, position: Index2) {
+    let Some((start, end)) = word_at(&state.lines, position) else {
+        return;
+    };
+    state.mode = EditorMode::Visual;
+    state.selection = Some(Selection::new(start, end));
+    state.cursor = end;
+}
+
+/// Scrolls the viewport by [`SCROLL_ROWS`] and keeps the cursor visible.
+fn scroll(state: &mut EditorState, down: bool) {
+    if down {
+        state.view.scroll_down(SCROLL_ROWS, state.lines.len().saturating_sub(1));
+    } else {
+        state.view.scroll_up(SCROLL_ROWS);
+    }
+    let (first, last) = state.view.visible_rows();
+    state.cursor.row = state.cursor.row.clamp(first, last.max(first)).min(state.lines.len().saturating_sub(1));
+    if state.mode == EditorMode::Visual {
+        set_selection(&mut state.selection, state.cursor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use ratatui::layout::Rect;
+
+    use super::*;
+    use crate::{state::cursor::Cursor, Lines};
+
+    fn test_state() -> EditorState {
+        let mut state = EditorState::new(Lines::from("Hello world!\nfoo_bar baz\n\n1\n2\n3\n4\n5"), "txt");
+        // The text starts right of a gutter of width 5.
+        state.view.set_area(Rect::new(0, 0, 20, 4), Rect::new(5, 0, 15, 3));
+        state
+    }
+
+    fn event(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE }
+    }
+
+    fn click(column: u16, row: u16) -> MouseEvent {
+        event(MouseEventKind::Down(MouseButton::Left), column, row)
+    }
+
+    #[test]
+    fn test_click() {
+        let mut state = test_state();
+        let mut mouse = Mouse::default();
+        mouse.on_mouse(click(7, 1), &mut state);
+        assert_eq!(state.cursor, Index2::new(1, 2));
+
+        // Clicks past the end of the line are clamped
+        mouse.on_mouse(click(19, 0), &mut state);
+        assert_eq!(state.cursor, Index2::new(0, 11));
+
+        // Clicks into the status line are ignored
+        mouse.on_mouse(click(7, 3), &mut state);
+        assert_eq!(state.cursor, Index2::new(0, 11));
+    }
+
+    #[test]
+    fn test_click_removes_cursors() {
+        let mut state = test_state();
+        let mut mouse = Mouse::default();
+        state.cursors = vec![Cursor::new(Index2::new(1, 0), None)];
+        mouse.on_mouse(click(7, 1), &mut state);
+        assert!(state.cursors.is_empty());
+
+        // So does a double click
+        state.cursors = vec![Cursor::new(Index2::new(0, 0), None)];
+        mouse.on_mouse(click(7, 1), &mut state);
+        assert_eq!(state.mode, EditorMode::Visual);
+        assert!(state.cursors.is_empty());
+    }
+
+    #[test]
+    fn test_drag() {
+        let mut state = test_state();
+        let mut mouse = Mouse::default();
+        mouse.on_mouse(click(6, 0), &mut state);
+        mouse.on_mouse(event(MouseEventKind::Drag(MouseButton::Left), 8, 1), &mut state);
+        assert_eq!(state.mode, EditorMode::Visual);
+        assert_eq!(state.selection, Some(Selection::new(Index2::new(0, 1), Index2::new(1, 3))));
+        assert_eq!(state.cursor, Index2::new(1, 3));
+
+        mouse.on_mouse(event(MouseEventKind::Up(MouseButton::Left), 8, 1), &mut state);
+        mouse.on_mouse(event(MouseEventKind::Drag(MouseButton::Left), 9, 1), &mut state);
+        assert_eq!(state.cursor, Index2::new(1, 3));
+    }
+
+    #[test]
+    fn test_double_click() {
+        let mut state = test_state();
+        let mut mouse = Mouse::default();
+        mouse.on_mouse(click(12, 0), &mut state);
+        mouse.on_mouse(click(12, 0), &mut state);
+        assert_eq!(state.mode, EditorMode::Visual);
+        assert_eq!(state.selection, Some(Selection::new(Index2::new(0, 6), Index2::new(0, 10))));
+
+        // A click ends the visual mode
+        mouse.on_mouse(click(5, 0), &mut state);
+        assert_eq!(state.mode, EditorMode::Normal);
+        assert_eq!(state.selection, None);
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut state = test_state();
+        let mut mouse = Mouse::default();
+        mouse.on_mouse(event(MouseEventKind::ScrollDown, 0, 0), &mut state);
+        assert_eq!(state.view.visible_rows(), (3, 5));
+        assert_eq!(state.cursor.row, 3);
+
+        mouse.on_mouse(event(MouseEventKind::ScrollDown, 0, 0), &mut state);
+        mouse.on_mouse(event(MouseEventKind::ScrollDown, 0, 0), &mut state);
+        assert_eq!(state.view.visible_rows(), (7, 9));
+        assert_eq!(state.cursor.row, 7);
+
+        mouse.on_mouse(event(MouseEventKind::ScrollUp, 0, 0), &mut state);
+        assert_eq!(state.view.visible_rows(), (4, 6));
+        assert_eq!(state.cursor.row, 6);
+    }
+}
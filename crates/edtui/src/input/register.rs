@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
 use crossterm::event::KeyEvent;
 use serde::{Deserialize, Serialize};
@@ -8,13 +11,63 @@ use crate::{
     EditorMode, EditorState,
 };
 
-#[derive(Clone, Debug, Default)]
+/// The default time to wait for the next key of an ambiguous sequence.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// The keymap of the editor.
+///
+/// The key sequences of each mode are stored in a trie. A sequence that is
+/// the prefix of a longer one, like `g` of `gg`, stays pending until either
+/// the next key decides or the timeout expires, see [`Register::tick`].
+#[derive(Clone, Debug)]
 pub struct Register<I>
 where
     I: Clone + Execute + Serialize + Deserialize<'static>,
 {
     lookup: Vec<KeyEvent>,
-    register: HashMap<RegisterKey, Action<I>>,
+    pending_since: Option<Instant>,
+    timeout: Duration,
+    keymaps: HashMap<EditorMode, Node<I>>,
+}
+
+#[derive(Clone, Debug)]
+struct Node<I: Clone + Execute> {
+    action: Option<Action<I>>,
+    children: HashMap<KeyEvent, Node<I>>,
+}
+
+impl<I: Clone + Execute> Node<I> {
+    fn new() -> Self {
+        Self { action: None, children: HashMap::new() }
+    }
+
+    fn walk(&self, keys: &[KeyEvent]) -> Option<&Node<I>> {
+        keys.iter().try_fold(self, |node, key| node.children.get(key))
+    }
+
+    /// Returns the number of bound sequences below this node.
+    fn bindings(&self) -> usize {
+        self.children.values().map(|child| usize::from(child.action.is_some()) + child.bindings()).sum()
+    }
+}
+
+/// A key that continues the pending sequence, see [`Register::continuations`].
+#[derive(Clone, Debug)]
+pub struct Continuation<'a, I: Clone + Execute> {
+    pub key: KeyEvent,
+    /// The action bound to the continued sequence.
+    pub action: Option<&'a Action<I>>,
+    /// The number of longer sequences that start with the continued sequence.
+    pub followups: usize,
+}
+
+impl<I> Default for Register<I>
+where
+    I: Clone + Execute + Serialize + Deserialize<'static>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<I> Register<I>
@@ -24,40 +77,106 @@ where
     /// Constructs a new Register
     #[must_use]
     pub fn new() -> Self {
-        Self { lookup: Vec::new(), register: HashMap::new() }
+        Self { lookup: Vec::new(), pending_since: None, timeout: DEFAULT_TIMEOUT, keymaps: HashMap::new() }
     }
 
     /// Insert a new callback to the registry
     pub fn insert<T: Into<Action<I>>>(&mut self, k: RegisterKey, v: T) {
-        self.register.insert(k, v.into());
+        let root = self.keymaps.entry(k.mode).or_insert_with(Node::new);
+        let node = k.keys.into_iter().fold(root, |node, key| node.children.entry(key).or_insert_with(Node::new));
+        node.action = Some(v.into());
     }
 
-    /// Returns an action for a specific register key, if present.
-    /// Returns an action only if there is an exact match. If there
-    /// are multiple matches or an inexact match, the specified key
-    /// is appended to the lookup vector.
-    /// If there is an exact match or if none of the keys in the registry
-    /// starts with the current sequence, the lookup sequence is reset.
+    /// Sets the time to wait for the next key of an ambiguous sequence.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Returns the keys of the pending sequence.
     #[must_use]
-    pub fn get(&mut self, c: KeyEvent, mode: EditorMode) -> Option<Action<I>> {
-        let key = self.create_register_key(c, mode);
-
-        match self.register.keys().filter(|k| k.mode == key.mode && k.keys.starts_with(&key.keys)).count() {
-            0 => {
-                self.lookup.clear();
-                None
-            },
-            1 => self.register.get(&key).map(|action| {
-                self.lookup.clear();
-                action.clone()
-            }),
-            _ => None,
-        }
+    pub fn pending(&self) -> &[KeyEvent] {
+        &self.lookup
+    }
+
+    /// Returns how long the pending sequence has been waiting for its next key.
+    #[must_use]
+    pub fn pending_for(&self) -> Option<Duration> {
+        self.pending_since.map(|since| since.elapsed())
+    }
+
+    /// Returns the keys that continue the pending sequence in a mode.
+    #[must_use]
+    pub fn continuations(&self, mode: EditorMode) -> Vec<Continuation<'_, I>> {
+        let node = self.keymaps.get(&mode).and_then(|root| root.walk(&self.lookup));
+        node.into_iter()
+            .flat_map(|node| &node.children)
+            .map(|(key, child)| Continuation { key: *key, action: child.action.as_ref(), followups: child.bindings() })
+            .collect()
+    }
+
+    /// Discards the pending sequence.
+    pub fn abort(&mut self) {
+        self.lookup.clear();
+        self.pending_since = None;
     }
 
-    fn create_register_key(&mut self, c: KeyEvent, mode: EditorMode) -> RegisterKey {
+    /// Appends a key to the pending sequence and returns the actions it
+    /// resolves to.
+    ///
+    /// A sequence that is a prefix of another binding stays pending. A key
+    /// that does not continue the sequence fires the longest bound prefix,
+    /// the remaining keys are looked up again. Keys without any binding are
+    /// dropped.
+    #[must_use]
+    pub fn get(&mut self, c: KeyEvent, mode: EditorMode) -> Vec<Action<I>> {
         self.lookup.push(c);
-        RegisterKey::new(self.lookup.clone(), mode)
+        self.resolve(mode, false)
+    }
+
+    /// Resolves the pending sequence once the timeout expired, firing the
+    /// longest bound prefix.
+    #[must_use]
+    pub fn tick(&mut self, mode: EditorMode) -> Vec<Action<I>> {
+        match self.pending_since {
+            Some(since) if since.elapsed() >= self.timeout => self.resolve(mode, true),
+            _ => Vec::new(),
+        }
+    }
+
+    fn resolve(&mut self, mode: EditorMode, timed_out: bool) -> Vec<Action<I>> {
+        let mut actions = Vec::new();
+        let Some(root) = self.keymaps.get(&mode) else {
+            self.abort();
+            return actions;
+        };
+        while !self.lookup.is_empty() {
+            match root.walk(&self.lookup) {
+                Some(node) if !node.children.is_empty() && !timed_out => {
+                    self.pending_since = Some(Instant::now());
+                    return actions;
+                },
+                Some(Node { action: Some(action), .. }) => {
+                    actions.push(action.clone());
+                    self.lookup.clear();
+                },
+                _ => {
+                    let bound = (1..self.lookup.len()).rev().find_map(|len| {
+                        root.walk(&self.lookup[..len]).and_then(|node| node.action.as_ref()).map(|a| (len, a))
+                    });
+                    match bound {
+                        Some((len, action)) => {
+                            actions.push(action.clone());
+                            self.lookup.drain(..len);
+                        },
+                        None => {
+                            self.lookup.remove(0);
+                        },
+                    }
+                },
+            }
+        }
+        self.pending_since = None;
+        actions
     }
 }
 
@@ -115,3 +234,80 @@ impl RegisterKey {
         Self::new(keys, EditorMode::Command)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{
+            motion::{MoveToFirstLine, MoveToLastLine},
+            DeleteLine, RemoveChar, SwitchMode,
+        },
+        input::key::Key,
+    };
+
+    fn register() -> Register<SwitchMode> {
+        let mut r = Register::new();
+        r.insert(RegisterKey::n(vec![Key::char('g')]), MoveToLastLine());
+        r.insert(RegisterKey::n(vec![Key::char('g'), Key::char('g')]), MoveToFirstLine());
+        r.insert(RegisterKey::n(vec![Key::char('d'), Key::char('d')]), DeleteLine(1));
+        r.insert(RegisterKey::n(vec![Key::char('x')]), RemoveChar(1));
+        r
+    }
+
+    fn get(r: &mut Register<SwitchMode>, c: char) -> Vec<Action<SwitchMode>> {
+        r.get(Key::char(c).into(), EditorMode::Normal)
+    }
+
+    #[test]
+    fn test_prefix() {
+        let mut r = register();
+        assert!(get(&mut r, 'g').is_empty());
+        assert_eq!(r.pending().len(), 1);
+        assert!(matches!(get(&mut r, 'g')[..], [Action::MoveToFirstLine(_)]));
+        assert!(r.pending().is_empty());
+
+        // A key that does not continue the sequence fires the prefix.
+        assert!(get(&mut r, 'g').is_empty());
+        assert!(matches!(get(&mut r, 'x')[..], [Action::MoveToLastLine(_), Action::RemoveChar(_)]));
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let mut r = register();
+        assert!(get(&mut r, 'd').is_empty());
+        assert!(matches!(get(&mut r, 'x')[..], [Action::RemoveChar(_)]));
+        assert!(get(&mut r, 'q').is_empty());
+        assert!(r.pending().is_empty());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut r = register();
+        assert!(get(&mut r, 'g').is_empty());
+        assert!(r.tick(EditorMode::Normal).is_empty());
+        r.set_timeout(Duration::ZERO);
+        assert!(matches!(r.tick(EditorMode::Normal)[..], [Action::MoveToLastLine(_)]));
+        assert!(r.pending().is_empty());
+
+        assert!(get(&mut r, 'd').is_empty());
+        assert!(r.tick(EditorMode::Normal).is_empty());
+        assert!(r.pending().is_empty());
+    }
+
+    #[test]
+    fn test_continuations() {
+        let mut r = register();
+        r.insert(RegisterKey::n(vec![Key::char('g'), Key::char('d'), Key::char('d')]), DeleteLine(2));
+        assert!(get(&mut r, 'g').is_empty());
+        assert!(r.pending_for().is_some());
+
+        let mut continuations = r.continuations(EditorMode::Normal);
+        continuations.sort_by_key(|c| Key::from(c.key).to_string());
+        let [d, g] = &continuations[..] else { panic!("expected two continuations") };
+        assert_eq!((d.key, d.action, d.followups), (Key::char('d').into(), None, 1));
+        assert!(matches!(g.action, Some(Action::MoveToFirstLine(_))));
+        assert_eq!(g.followups, 0);
+        assert!(r.continuations(EditorMode::Insert).is_empty());
+    }
+}
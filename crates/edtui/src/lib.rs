@@ -40,6 +40,9 @@
 //! | `r`                     | Redo the last undone action                  |
 //! | `y`                     | Copy the selected text                       |
 //! | `p`                     | Paste the copied text                        |
+//! | `Ctrl-n`                | Add a cursor at the next match of the word   |
+//! | `Ctrl-Down`, `Ctrl-Up`  | Add a cursor below/above                     |
+//! | `Alt-n`                 | Select all matches of the last search        |
 //!
 //! #### Insert Mode:
 //!
@@ -51,7 +54,7 @@
 //!
 //! ## Demo
 //!
-//!![](resources/app.gif)
+//! ![](resources/app.gif)
 //!
 //! ### Roadmap
 //!
@@ -68,14 +71,17 @@ pub mod actions;
 pub mod clipboard;
 mod debug;
 mod helper;
+pub mod highlight;
 pub mod input;
 pub mod state;
+pub mod text;
 pub mod view;
 
 pub use input::Input;
 pub use state::{mode::EditorMode, EditorState};
 pub use view::{theme::EditorTheme, EditorView, StatusLine};
 
-/// A data structure that contains chars organized in rows and columns
-pub type Lines = jagged::Jagged<char>;
+/// The text storage of the editor. Contains chars organized in rows and columns.
+/// See [`text::TextStorage`] for the available operations.
+pub type Lines = text::Rope;
 pub use jagged::Index2;
@@ -1,18 +1,20 @@
 //! The editors state
 pub mod command;
+pub mod cursor;
 pub mod mode;
 mod search;
 pub mod selection;
 mod undo;
 mod view;
+pub mod window;
 
-use synoptic::{from_extension, Highlighter};
-
-use self::search::SearchState;
-use self::view::ViewState;
-use self::{mode::EditorMode, selection::Selection, undo::Stack};
-use crate::clipboard::{Clipboard, ClipboardTrait};
-use crate::{Index2, Lines};
+use self::{cursor::Cursor, mode::EditorMode, search::SearchState, selection::Selection, undo::Stack, view::ViewState};
+use crate::{
+    clipboard::{Clipboard, ClipboardTrait},
+    highlight::{Highlighter, HighlighterTrait, SynopticHighlighter},
+    text::TextStorage,
+    Index2, Lines,
+};
 
 /// Represents the state of an editor.
 pub struct EditorState {
@@ -28,6 +30,10 @@ pub struct EditorState {
     /// Represents the selection in the editor, if any.
     pub selection: Option<Selection>,
 
+    /// The secondary cursors. Edits and motions apply at each of them and
+    /// at the primary cursor, see [`EditorState::execute_at_cursors`].
+    pub cursors: Vec<Cursor>,
+
     /// Internal view state of the editor.
     pub(crate) view: ViewState,
 
@@ -40,20 +46,29 @@ pub struct EditorState {
     /// Stack for redo operations.
     pub(crate) redo: Stack,
 
+    /// Whether an edit was captured within a group of edits that undo in a
+    /// single step. `None` outside of a group.
+    pub(crate) undo_group: Option<bool>,
+
+    /// Identifies the text, see [`EditorState::revision`].
+    pub(crate) revision: usize,
+
     /// Clipboard for yank and paste operations.
     pub(crate) clip: Clipboard,
 
+    /// The syntax highlighter. Rows are highlighted lazily when rendered.
     pub highlighter: Highlighter,
 
     pub command: String,
+
+    /// The keys of a pending key sequence, shown in the status line.
+    pub pending_keys: String,
 }
 
 impl Default for EditorState {
     /// Creates a default `EditorState` with no text.
     fn default() -> Self {
-        let mut state = EditorState::new(Lines::default(), "");
-        state.highlighter = Highlighter::new(4);
-        state
+        EditorState::new(Lines::default(), "")
     }
 }
 
@@ -69,20 +84,24 @@ impl EditorState {
     /// ```
     #[must_use]
     pub fn new(lines: Lines, ext: &str) -> EditorState {
-        let mut highlighter = from_extension(ext, 4).unwrap_or(Highlighter::new(4));
-        highlighter.run(&lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>());
+        let mut highlighter = Highlighter::new(SynopticHighlighter::new(ext));
+        highlighter.reset(lines.len());
         EditorState {
             lines,
             cursor: Index2::new(0, 0),
             mode: EditorMode::Normal,
             selection: None,
+            cursors: Vec::new(),
             view: ViewState::default(),
             search: SearchState::default(),
             undo: Stack::new(),
             redo: Stack::new(),
+            undo_group: None,
+            revision: undo::next_revision(),
             clip: Clipboard::default(),
             highlighter,
             command: String::new(),
+            pending_keys: String::new(),
         }
     }
 
@@ -91,7 +110,14 @@ impl EditorState {
         self.clip = Clipboard::new(clipboard);
     }
 
+    /// Set a custom syntax highlighter.
+    pub fn set_highlighter(&mut self, highlighter: impl HighlighterTrait + 'static) {
+        self.highlighter = Highlighter::new(highlighter);
+        self.reset_highlighter();
+    }
+
+    /// Invalidates the highlighting of all rows.
     pub fn reset_highlighter(&mut self) {
-        self.highlighter.run(&self.lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>());
+        self.highlighter.reset(self.lines.len());
     }
 }
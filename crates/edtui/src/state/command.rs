@@ -6,6 +6,8 @@ pub struct CommandState<I> {
     pub(crate) start_cursor: Index2,
     pub(crate) input: String,
     pub available_commands: Vec<Command<I>>,
+    /// The completed prefix and the index of the current candidate.
+    completion: Option<(String, usize)>,
 }
 
 #[derive(Clone, Debug)]
@@ -14,18 +16,26 @@ pub struct Command<I> {
     pub name: String,
     pub aliases: Vec<String>,
     pub description: String,
+    /// The arguments offered when completing the command.
+    pub arguments: Vec<String>,
     pub action: fn(String) -> I,
 }
 
 impl<I: Default> Default for Command<I> {
     fn default() -> Self {
-        Self { name: String::new(), description: String::new(), aliases: Vec::new(), action: |_| I::default() }
+        Self {
+            name: String::new(),
+            description: String::new(),
+            aliases: Vec::new(),
+            arguments: Vec::new(),
+            action: |_| I::default(),
+        }
     }
 }
 
 impl<I> Command<I> {
     pub fn new(name: String, description: String, aliases: Vec<String>, action: fn(String) -> I) -> Self {
-        Self { name, description, aliases, action }
+        Self { name, description, aliases, arguments: Vec::new(), action }
     }
 
     pub fn name(&mut self, name: String) -> &mut Self {
@@ -42,6 +52,11 @@ impl<I> Command<I> {
         self.aliases = aliases;
         self
     }
+
+    pub fn arguments(&mut self, arguments: Vec<String>) -> &mut Self {
+        self.arguments = arguments;
+        self
+    }
 }
 
 impl<I> CommandState<I> {
@@ -53,19 +68,96 @@ impl<I> CommandState<I> {
     /// Clears both the search pattern and matched indices.
     pub(crate) fn clear(&mut self) {
         self.input.clear();
+        self.completion = None;
     }
 
     /// Appends a character to the command.
     pub(crate) fn push_char(&mut self, ch: char) {
         self.input.push(ch);
+        self.completion = None;
     }
 
     /// Removes the last character from the command.
     pub(crate) fn remove_char(&mut self) {
         self.input.pop();
+        self.completion = None;
+    }
+
+    /// Completes the command name or its argument. Repeated calls cycle
+    /// through the candidates.
+    pub(crate) fn complete(&mut self) {
+        let (prefix, index) = match self.completion.take() {
+            Some((prefix, index)) => (prefix, index + 1),
+            None => (self.input.clone(), 0),
+        };
+        let candidates = self.candidates(&prefix);
+        if !candidates.is_empty() {
+            self.input.clone_from(&candidates[index % candidates.len()]);
+            self.completion = Some((prefix, index));
+        }
+    }
+
+    /// Returns the completions of a command input. Completes the command
+    /// name, or its arguments after a space.
+    #[must_use]
+    pub fn candidates(&self, input: &str) -> Vec<String> {
+        let mut candidates: Vec<String> = match input.split_once(' ') {
+            None => {
+                self.available_commands.iter().filter(|c| c.name.starts_with(input)).map(|c| c.name.clone()).collect()
+            },
+            Some((name, arg)) => {
+                self.available_commands
+                    .iter()
+                    .filter(|c| c.name == name || c.aliases.iter().any(|alias| alias == name))
+                    .flat_map(|c| c.arguments.iter())
+                    .filter(|a| a.starts_with(arg))
+                    .map(|a| format!("{name} {a}"))
+                    .collect()
+            },
+        };
+        candidates.sort();
+        candidates.dedup();
+        candidates
     }
 
     pub fn add_command(&mut self, name: String, description: String, aliases: Vec<String>, action: fn(String) -> I) {
-        self.available_commands.push(Command { name, description, aliases, action });
+        self.available_commands.push(Command::new(name, description, aliases, action));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_state() -> CommandState<()> {
+        let mut state = CommandState::default();
+        state.add_command("colorscheme".into(), String::new(), vec!["colo".into()], |_| ());
+        state.add_command("close".into(), String::new(), Vec::new(), |_| ());
+        state.available_commands[0].arguments(vec!["light".into(), "dark".into(), "dracula".into()]);
+        state
+    }
+
+    #[test]
+    fn test_candidates() {
+        let state = command_state();
+        assert_eq!(state.candidates("c"), vec!["close", "colorscheme"]);
+        assert_eq!(state.candidates("colo d"), vec!["colo dark", "colo dracula"]);
+        assert!(state.candidates("close d").is_empty());
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut state = command_state();
+        "colorscheme d".chars().for_each(|ch| state.push_char(ch));
+        state.complete();
+        assert_eq!(state.input, "colorscheme dark");
+        state.complete();
+        assert_eq!(state.input, "colorscheme dracula");
+        state.complete();
+        assert_eq!(state.input, "colorscheme dark");
+
+        state.push_char('x');
+        state.complete();
+        assert_eq!(state.input, "colorscheme darkx");
     }
 }
@@ -0,0 +1,178 @@
+use super::selection::Selection;
+use crate::{actions::Execute, text::TextStorage, EditorState, Index2, Lines};
+
+/// A secondary cursor with its own selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub position: Index2,
+    pub selection: Option<Selection>,
+}
+
+impl Cursor {
+    #[must_use]
+    pub fn new(position: Index2, selection: Option<Selection>) -> Self {
+        Self { position, selection }
+    }
+
+    /// Returns the first position covered by the cursor or its selection.
+    fn start(&self) -> (usize, usize) {
+        let start = self.selection.as_ref().map_or(self.position, Selection::start);
+        (start.row, start.col).min((self.position.row, self.position.col))
+    }
+
+    /// Moves the cursor and its selection by `delta` characters after an
+    /// edit in front of it changed `old` into `new`.
+    fn shift(&mut self, old: &Lines, new: &Lines, delta: isize) {
+        let shift = |index: Index2| {
+            let offset = char_offset(old, index).saturating_add_signed(delta);
+            new.char_to_index(offset.min(char_len(new)))
+        };
+        self.position = shift(self.position);
+        if let Some(selection) = &mut self.selection {
+            *selection = Selection::new(shift(selection.start), shift(selection.end));
+        }
+    }
+}
+
+impl EditorState {
+    /// Adds a cursor. The current cursor becomes a secondary cursor.
+    pub fn add_cursor(&mut self, position: Index2, selection: Option<Selection>) {
+        let previous = Cursor::new(self.cursor, self.selection.take());
+        self.cursors.retain(|cursor| cursor.position != position && cursor.position != previous.position);
+        if previous.position != position {
+            self.cursors.push(previous);
+        }
+        self.cursor = position;
+        self.selection = selection;
+    }
+
+    /// Removes all secondary cursors.
+    pub fn remove_cursors(&mut self) {
+        self.cursors.clear();
+    }
+
+    /// Executes an action at every cursor.
+    ///
+    /// The cursors are visited from the last to the first one, such that an
+    /// edit only moves the cursors behind it, which are shifted by the change
+    /// of the text length. All edits undo in a single step.
+    pub fn execute_at_cursors<A: Execute>(&mut self, action: &mut A) {
+        if self.cursors.is_empty() {
+            action.execute(self);
+            return;
+        }
+        let mode = self.mode;
+        let mut cursors = std::mem::take(&mut self.cursors);
+        cursors.push(Cursor::new(self.cursor, self.selection.take()));
+        let mut order = (0..cursors.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| std::cmp::Reverse(cursors[i].start()));
+
+        self.undo_group = Some(false);
+        let mut visited: Vec<usize> = Vec::new();
+        let mut next_mode = mode;
+        for i in order {
+            let old = self.lines.clone();
+            self.mode = mode;
+            self.cursor = cursors[i].position;
+            self.selection = cursors[i].selection.take();
+            action.execute(self);
+            next_mode = self.mode;
+            cursors[i] = Cursor::new(self.cursor, self.selection.take());
+
+            let delta = char_len(&self.lines) as isize - char_len(&old) as isize;
+            if delta != 0 {
+                for &j in &visited {
+                    cursors[j].shift(&old, &self.lines, delta);
+                }
+            }
+            visited.push(i);
+        }
+        self.undo_group = None;
+        self.mode = next_mode;
+
+        let primary = cursors.pop().unwrap_or_else(|| Cursor::new(self.cursor, None));
+        self.cursor = primary.position;
+        self.selection = primary.selection;
+        for cursor in cursors {
+            if cursor.position != self.cursor && !self.cursors.iter().any(|c| c.position == cursor.position) {
+                self.cursors.push(cursor);
+            }
+        }
+    }
+}
+
+/// Returns the offset of an index in characters, clamped to the text.
+pub(super) fn char_offset(lines: &Lines, index: Index2) -> usize {
+    if lines.is_empty() {
+        return 0;
+    }
+    let row = index.row.min(lines.len() - 1);
+    let col = index.col.min(lines.len_col(row).unwrap_or_default());
+    lines.index_to_char(Index2::new(row, col))
+}
+
+/// Returns the number of characters of the text.
+pub(super) fn char_len(lines: &Lines) -> usize {
+    char_offset(lines, Index2::new(usize::MAX, usize::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{DeleteChar, DeleteSelection, InsertChar, InsertText, Undo},
+        EditorMode,
+    };
+
+    fn test_state() -> EditorState {
+        let mut state = EditorState::new(Lines::from("foo bar\nfoo baz\nfoo"), "txt");
+        state.add_cursor(Index2::new(1, 0), None);
+        state.add_cursor(Index2::new(2, 0), None);
+        state
+    }
+
+    #[test]
+    fn test_add_cursor() {
+        let mut state = test_state();
+        assert_eq!(state.cursor, Index2::new(2, 0));
+        assert_eq!(state.cursors.len(), 2);
+
+        // Adding a cursor twice keeps one of them
+        state.add_cursor(Index2::new(1, 0), None);
+        assert_eq!(state.cursors.len(), 2);
+    }
+
+    #[test]
+    fn test_insert_at_cursors() {
+        let mut state = test_state();
+        state.mode = EditorMode::Insert;
+        state.cursors.push(Cursor::new(Index2::new(0, 4), None));
+        state.execute_at_cursors(&mut InsertChar('x'));
+        assert_eq!(state.lines, Lines::from("xfoo xbar\nxfoo baz\nxfoo"));
+        assert_eq!(state.cursor, Index2::new(2, 1));
+        let mut positions = state.cursors.iter().map(|c| (c.position.row, c.position.col)).collect::<Vec<_>>();
+        positions.sort_unstable();
+        assert_eq!(positions, vec![(0, 1), (0, 6), (1, 1)]);
+
+        state.execute_at_cursors(&mut DeleteChar(1));
+        assert_eq!(state.lines, Lines::from("foo bar\nfoo baz\nfoo"));
+
+        // All edits undo in a single step
+        state.execute_at_cursors(&mut InsertText("yy".to_string()));
+        Undo.execute(&mut state);
+        assert_eq!(state.lines, Lines::from("foo bar\nfoo baz\nfoo"));
+        assert!(state.cursors.is_empty());
+    }
+
+    #[test]
+    fn test_delete_selections() {
+        let mut state = EditorState::new(Lines::from("foo bar foo"), "txt");
+        state.mode = EditorMode::Visual;
+        state.selection = Some(Selection::new(Index2::new(0, 0), Index2::new(0, 2)));
+        state.add_cursor(Index2::new(0, 10), Some(Selection::new(Index2::new(0, 8), Index2::new(0, 10))));
+        state.execute_at_cursors(&mut DeleteSelection);
+        assert_eq!(state.lines, Lines::from(" bar "));
+        assert_eq!(state.mode, EditorMode::Normal);
+        assert_eq!(state.cursors.len(), 1);
+    }
+}
@@ -1,6 +1,6 @@
 use jagged::Index2;
 
-use crate::Lines;
+use crate::{text::TextStorage, Lines};
 
 /// Represents the state of a search operation, including the search pattern,
 /// matched indices, and selected index.
@@ -33,7 +33,12 @@ impl SearchState {
     /// Triggers a search based on the current pattern in the provided text.
     pub(crate) fn trigger_search(&mut self, lines: &Lines) {
         let pattern: Vec<char> = self.pattern.chars().collect();
-        self.matches = lines.match_indices(&pattern).map(|(_, index)| index).collect();
+        self.matches = lines.match_indices(&pattern);
+    }
+
+    /// Returns the positions of the matches of the last search.
+    pub(crate) fn matches(&self) -> &[Index2] {
+        &self.matches
     }
 
     /// Appends a character to the search pattern.
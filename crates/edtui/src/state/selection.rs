@@ -1,4 +1,4 @@
-use crate::{Index2, Lines};
+use crate::{text::TextStorage, Index2};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Selection {
@@ -50,14 +50,15 @@ impl Selection {
 
     /// Extracts a selection from `Lines`.
     #[must_use]
-    pub fn extract(&self, lines: &Lines) -> Lines {
-        lines.iter().from(self.start()).to(self.end()).collect()
+    pub fn extract<T: TextStorage>(&self, lines: &T) -> T {
+        T::from(lines.slice(self.start(), self.end()).as_str())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Lines;
     fn test_data() -> Lines {
         Lines::from(
             "Hello\n\
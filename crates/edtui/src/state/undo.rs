@@ -1,10 +1,22 @@
 //! This module contains a rather brute-force implementation of undo and redo functionality
 //! for a simple text editor. It stores the entire editor state at each action.
 //!
-//! While this approach works for basic undo/redo needs, it may not be efficient for more
-//! complex usage. In the long run, this should be replaced with an action-based mechanism.
+//! Since [`Lines`] is a rope, capturing a state only clones a reference counted tree
+//! and shares all unchanged chunks with the current text. In the long run, this should
+//! still be replaced with an action-based mechanism.
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::{EditorState, Index2, Lines};
 
+/// The last revision given to a text, revisions are unique among all editor
+/// states.
+static REVISION: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a new revision.
+pub(crate) fn next_revision() -> usize {
+    REVISION.fetch_add(1, Ordering::Relaxed) + 1
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Stack {
     inner: Vec<UndoState>,
@@ -40,31 +52,83 @@ impl Stack {
 pub(crate) struct UndoState {
     lines: Lines,
     cursor: Index2,
+    revision: usize,
 }
 
 impl EditorState {
     pub(crate) fn capture(&mut self) {
-        let editor_state = UndoState { lines: self.lines.clone(), cursor: self.cursor };
+        match self.undo_group {
+            Some(true) => return,
+            Some(false) => self.undo_group = Some(true),
+            None => {},
+        }
+        let editor_state = UndoState { lines: self.lines.clone(), cursor: self.cursor, revision: self.revision };
         self.undo.push(editor_state);
+        self.touch();
+    }
+
+    /// Gives the text a new revision after an edit that is not captured.
+    pub(crate) fn touch(&mut self) {
+        self.revision = next_revision();
+    }
+
+    /// Returns the revision of the text. Every edit creates a new revision,
+    /// undo and redo return to the revision of the restored text. A buffer
+    /// is modified if its revision differs from the one it was saved at.
+    #[must_use]
+    pub fn revision(&self) -> usize {
+        self.revision
     }
 
     pub fn undo(&mut self) {
         if let Some(prev) = self.undo.pop() {
-            let current = UndoState { lines: self.lines.clone(), cursor: self.cursor };
+            self.cursors.clear();
+            let current = UndoState { lines: self.lines.clone(), cursor: self.cursor, revision: self.revision };
+            self.highlighter.replace(&current.lines, &prev.lines);
             self.lines = prev.lines;
-            self.highlighter.run(&self.lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>());
             self.cursor = prev.cursor;
+            self.revision = prev.revision;
             self.redo.push(current);
         }
     }
 
     pub fn redo(&mut self) {
         if let Some(prev) = self.redo.pop() {
-            let current = UndoState { lines: self.lines.clone(), cursor: self.cursor };
+            self.cursors.clear();
+            let current = UndoState { lines: self.lines.clone(), cursor: self.cursor, revision: self.revision };
+            self.highlighter.replace(&current.lines, &prev.lines);
             self.lines = prev.lines;
-            self.highlighter.run(&self.lines.iter_row().map(|e| e.iter().collect()).collect::<Vec<String>>());
             self.cursor = prev.cursor;
+            self.revision = prev.revision;
             self.undo.push(current);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{Execute, InsertChar, InsertText},
+        EditorMode,
+    };
+
+    #[test]
+    fn test_revision() {
+        let mut state = EditorState::new(Lines::from("foo"), "txt");
+        let saved = state.revision();
+        InsertText("bar".to_string()).execute(&mut state);
+        let edited = state.revision();
+        assert_ne!(edited, saved);
+
+        state.undo();
+        assert_eq!(state.revision(), saved);
+        state.redo();
+        assert_eq!(state.revision(), edited);
+
+        // Edits in insert mode are not captured, but change the revision
+        state.mode = EditorMode::Insert;
+        InsertChar('!').execute(&mut state);
+        assert_ne!(state.revision(), edited);
+    }
+}
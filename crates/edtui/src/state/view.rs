@@ -1,3 +1,5 @@
+use ratatui::layout::Rect;
+
 use crate::Index2;
 
 /// Represents the (x, y) offset of the editor's viewport.
@@ -8,12 +10,64 @@ pub struct ViewState {
     x: usize,
     /// The y-coordinate offset of the viewport.
     y: usize,
+    /// The screen area of the editor, including the gutter.
+    area: Rect,
+    /// The screen area of the text. Set when the editor is rendered.
+    text: Rect,
 }
 
 impl ViewState {
     /// Instantiates a new [`ViewState`] with specified x and y coordinates.
     pub(crate) fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
+        Self { x, y, ..Self::default() }
+    }
+
+    /// Returns the x and y offset of the viewport.
+    pub(crate) fn offset(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// Stores the screen areas of the last render.
+    pub(crate) fn set_area(&mut self, area: Rect, text: Rect) {
+        self.area = area;
+        self.text = text;
+    }
+
+    /// Maps a screen position to a position in the text. Clicks into the
+    /// gutter map to the first column. Returns `None` outside of the rows
+    /// of the text.
+    pub(crate) fn screen_to_text(&self, column: u16, row: u16) -> Option<Index2> {
+        if row < self.text.top()
+            || row >= self.text.bottom()
+            || column < self.area.left()
+            || column >= self.area.right()
+        {
+            return None;
+        }
+        let col = usize::from(column.saturating_sub(self.text.left())) + self.x;
+        Some(Index2::new(usize::from(row - self.text.top()) + self.y, col))
+    }
+
+    /// Scrolls the viewport down by `n` rows, at most to `last_row`.
+    pub(crate) fn scroll_down(&mut self, n: usize, last_row: usize) {
+        self.y = (self.y + n).min(last_row);
+    }
+
+    /// Scrolls the viewport up by `n` rows.
+    pub(crate) fn scroll_up(&mut self, n: usize) {
+        self.y = self.y.saturating_sub(n);
+    }
+
+    /// Moves the offset to at most `last_row` and `last_col`, e.g. after
+    /// the text shrank.
+    pub(crate) fn clamp_offset(&mut self, last_row: usize, last_col: usize) {
+        self.x = self.x.min(last_col);
+        self.y = self.y.min(last_row);
+    }
+
+    /// Returns the first and the last visible row.
+    pub(crate) fn visible_rows(&self) -> (usize, usize) {
+        (self.y, self.y + usize::from(self.text.height).saturating_sub(1))
     }
 
     /// Updates the view's offset and returns the new offset.
@@ -110,4 +164,17 @@ mod tests {
             expected: (1, 0)
         }
     );
+
+    #[test]
+    fn test_screen_to_text() {
+        let mut view = ViewState::new(2, 10);
+        view.set_area(Rect::new(0, 1, 20, 6), Rect::new(5, 1, 15, 5));
+        assert_eq!(view.screen_to_text(5, 1), Some(Index2::new(10, 2)));
+        assert_eq!(view.screen_to_text(8, 3), Some(Index2::new(12, 5)));
+        // The gutter maps to the first visible column
+        assert_eq!(view.screen_to_text(1, 2), Some(Index2::new(11, 2)));
+        // The status line is not part of the text
+        assert_eq!(view.screen_to_text(5, 6), None);
+        assert_eq!(view.screen_to_text(20, 1), None);
+    }
 }
@@ -0,0 +1,280 @@
+use super::{
+    cursor::{char_len, char_offset, Cursor},
+    selection::Selection,
+    view::ViewState,
+};
+use crate::{
+    helper::{clamp_column, max_col},
+    text::TextStorage,
+    EditorMode, EditorState, Index2, Lines,
+};
+
+/// The part of an [`EditorState`] that belongs to a single window: the
+/// mode, the cursors, the selections and the viewport.
+///
+/// Windows that show the same text share one [`EditorState`]. Each window
+/// keeps its own [`WindowState`] and loads it into the shared state before
+/// it handles input or renders, see [`EditorState::load_window`].
+#[derive(Debug, Clone, Default)]
+pub struct WindowState {
+    mode: EditorMode,
+    cursor: Index2,
+    selection: Option<Selection>,
+    cursors: Vec<Cursor>,
+    view: ViewState,
+}
+
+impl WindowState {
+    /// Instantiates a window state with a cursor and the offset of the
+    /// viewport, e.g. to restore a window.
+    #[must_use]
+    pub fn new(cursor: Index2, offset: Index2) -> Self {
+        Self { cursor, view: ViewState::new(offset.col, offset.row), ..Self::default() }
+    }
+
+    /// Returns the offset of the viewport, i.e. its first row and column.
+    #[must_use]
+    pub fn offset(&self) -> Index2 {
+        let (x, y) = self.view.offset();
+        Index2::new(y, x)
+    }
+
+    /// Returns the cursor of the window.
+    #[must_use]
+    pub fn cursor(&self) -> Index2 {
+        self.cursor
+    }
+
+    /// Returns the mode of the window.
+    #[must_use]
+    pub fn mode(&self) -> EditorMode {
+        self.mode
+    }
+}
+
+/// An edit of a text, found by comparing the text before and after it: the
+/// chars from `start` to `old_end` were replaced by the chars from `start`
+/// to `new_end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextEdit {
+    start: usize,
+    old_end: usize,
+    new_end: usize,
+}
+
+impl TextEdit {
+    /// Returns the edit between the common start and the common end of
+    /// `old` and `new`, or `None` if they are equal.
+    #[must_use]
+    pub fn between(old: &Lines, new: &Lines) -> Option<Self> {
+        let (old, new) = (old.inner(), new.inner());
+        let (old_len, new_len) = (old.len_bytes(), new.len_bytes());
+        let start = old.bytes().zip(new.bytes()).take_while(|(a, b)| a == b).count();
+        if start == old_len && start == new_len {
+            return None;
+        }
+        let end = old
+            .bytes_at(old_len)
+            .reversed()
+            .zip(new.bytes_at(new_len).reversed())
+            .take(old_len.min(new_len) - start)
+            .take_while(|(a, b)| a == b)
+            .count();
+        // A char that differs in some of its bytes belongs to the edit
+        let char_end = |rope: &ropey::Rope, byte: usize| {
+            let char_idx = rope.byte_to_char(byte);
+            char_idx + usize::from(rope.char_to_byte(char_idx) != byte)
+        };
+        Some(Self {
+            start: old.byte_to_char(start),
+            old_end: char_end(old, old_len - end),
+            new_end: char_end(new, new_len - end),
+        })
+    }
+
+    /// Maps a char offset before the edit to the offset after it. Offsets
+    /// at an insertion move behind the inserted text, offsets in the
+    /// replaced chars move to the start of the edit.
+    fn map(&self, offset: usize) -> usize {
+        if offset >= self.old_end {
+            offset - self.old_end + self.new_end
+        } else if offset <= self.start {
+            offset
+        } else {
+            self.start
+        }
+    }
+}
+
+impl WindowState {
+    /// Moves the cursors, the selections and the viewport of the window
+    /// along with an edit made in another window, which changed `old` into
+    /// `new`.
+    pub fn shift(&mut self, edit: &TextEdit, old: &Lines, new: &Lines) {
+        let shift = |index: Index2| new.char_to_index(edit.map(char_offset(old, index)).min(char_len(new)));
+        let shift_selection = |selection: &mut Selection| {
+            *selection = Selection::new(shift(selection.start), shift(selection.end));
+        };
+        self.cursor = shift(self.cursor);
+        self.selection.iter_mut().for_each(shift_selection);
+        for cursor in &mut self.cursors {
+            cursor.position = shift(cursor.position);
+            cursor.selection.iter_mut().for_each(shift_selection);
+        }
+        let (_, top) = self.view.offset();
+        let shifted = shift(Index2::new(top, 0)).row;
+        if shifted > top {
+            self.view.scroll_down(shifted - top, shifted);
+        } else {
+            self.view.scroll_up(top - shifted);
+        }
+    }
+}
+
+impl EditorState {
+    /// Takes the window state out of the editor state.
+    #[must_use]
+    pub fn take_window(&mut self) -> WindowState {
+        WindowState {
+            mode: std::mem::take(&mut self.mode),
+            cursor: std::mem::take(&mut self.cursor),
+            selection: self.selection.take(),
+            cursors: std::mem::take(&mut self.cursors),
+            view: std::mem::take(&mut self.view),
+        }
+    }
+
+    /// Loads a window state into the editor state. Cursors and selections
+    /// outside of the text, e.g. after another window removed lines or
+    /// shortened them, are moved into the text or dropped.
+    pub fn load_window(&mut self, window: WindowState) {
+        self.mode = window.mode;
+        let rows = self.lines.len();
+        let in_text = |index: &Index2| index.row < rows.max(1);
+        let (lines, mode) = (&self.lines, self.mode);
+        let clamp = |index: Index2| Index2::new(index.row, index.col.min(max_col(lines, &index, mode)));
+        let clamp_selection = |selection: Option<Selection>| {
+            selection
+                .filter(|selection| in_text(&selection.start) && in_text(&selection.end))
+                .map(|selection| Selection::new(clamp(selection.start), clamp(selection.end)))
+        };
+        self.selection = clamp_selection(window.selection);
+        self.cursors = window
+            .cursors
+            .into_iter()
+            .filter(|cursor| in_text(&cursor.position))
+            .map(|cursor| Cursor::new(clamp(cursor.position), clamp_selection(cursor.selection)))
+            .collect();
+        self.cursor = window.cursor;
+        self.cursor.row = self.cursor.row.min(rows.saturating_sub(1));
+        clamp_column(self);
+        self.view = window.view;
+        self.view.clamp_offset(rows.saturating_sub(1), self.cursor.col);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{Execute, InsertChar},
+        Lines,
+    };
+
+    #[test]
+    fn test_windows() {
+        let mut state = EditorState::new(Lines::from("first\nsecond\nthird"), "txt");
+        state.cursor = Index2::new(2, 1);
+        let first = state.take_window();
+        assert_eq!(first.cursor(), Index2::new(2, 1));
+
+        // A second window edits the shared text
+        state.load_window(WindowState::default());
+        state.mode = EditorMode::Insert;
+        InsertChar('!').execute(&mut state);
+        let second = state.take_window();
+        assert_eq!(second.cursor(), Index2::new(0, 1));
+        assert_eq!(second.mode(), EditorMode::Insert);
+
+        // Each window keeps its own mode
+        state.load_window(first);
+        assert_eq!(state.cursor, Index2::new(2, 1));
+        assert_eq!(state.mode, EditorMode::Normal);
+        assert_eq!(state.lines, Lines::from("!first\nsecond\nthird"));
+        let first = state.take_window();
+        assert_eq!(first.mode(), EditorMode::Normal);
+        state.load_window(second);
+        assert_eq!(state.mode, EditorMode::Insert);
+    }
+
+    #[test]
+    fn test_load_window_outside_of_text() {
+        let mut state = EditorState::new(Lines::from("first\nsecond\nthird"), "txt");
+        state.cursor = Index2::new(2, 3);
+        state.selection = Some(Selection::new(Index2::new(1, 0), Index2::new(2, 3)));
+        let window = state.take_window();
+
+        state.lines = Lines::from("first");
+        state.load_window(window);
+        assert_eq!(state.cursor, Index2::new(0, 3));
+        assert_eq!(state.selection, None);
+
+        // Columns past the end of shortened lines are moved to their end
+        state.cursor = Index2::new(0, 4);
+        state.selection = Some(Selection::new(Index2::new(0, 1), Index2::new(0, 4)));
+        state.cursors = vec![Cursor::new(Index2::new(0, 3), None)];
+        let mut window = state.take_window();
+        window.view = ViewState::new(4, 2);
+        state.lines = Lines::from("fi");
+        state.load_window(window);
+        assert_eq!(state.cursor, Index2::new(0, 1));
+        assert_eq!(state.selection, Some(Selection::new(Index2::new(0, 1), Index2::new(0, 1))));
+        assert_eq!(state.cursors, vec![Cursor::new(Index2::new(0, 1), None)]);
+        assert_eq!(state.view.offset(), (1, 0));
+    }
+
+    #[test]
+    fn test_text_edit() {
+        let edit = |old: &str, new: &str| TextEdit::between(&Lines::from(old), &Lines::from(new));
+        assert_eq!(edit("same", "same"), None);
+        assert_eq!(edit("ab", "axb"), Some(TextEdit { start: 1, old_end: 1, new_end: 2 }));
+        assert_eq!(edit("abc", "ac"), Some(TextEdit { start: 1, old_end: 2, new_end: 1 }));
+        assert_eq!(edit("aa", "aaa"), Some(TextEdit { start: 2, old_end: 2, new_end: 3 }));
+        // Chars that share some of their bytes
+        assert_eq!(edit("é", "è"), Some(TextEdit { start: 0, old_end: 1, new_end: 1 }));
+    }
+
+    #[test]
+    fn test_shift_window() {
+        let mut old = Lines::from("first\nsecond\nthird");
+        let mut window = WindowState::new(Index2::new(2, 3), Index2::new(1, 0));
+        window.selection = Some(Selection::new(Index2::new(1, 1), Index2::new(2, 3)));
+        window.cursors = vec![Cursor::new(Index2::new(0, 2), None)];
+
+        // Another window inserts a line above and text in front of the cursor
+        for text in ["zero\nfirst\nsecond\nthird", "zero\nfirst\nsecond\nthe third"] {
+            let new = Lines::from(text);
+            let edit = TextEdit::between(&old, &new).unwrap();
+            window.shift(&edit, &old, &new);
+            old = new;
+        }
+        assert_eq!(window.cursor(), Index2::new(3, 7));
+        assert_eq!(window.selection, Some(Selection::new(Index2::new(2, 1), Index2::new(3, 7))));
+        assert_eq!(window.cursors, vec![Cursor::new(Index2::new(1, 2), None)]);
+        assert_eq!(window.offset(), Index2::new(2, 0));
+
+        // Removing the lines of the cursor moves it to the start of the edit
+        let new = Lines::from("zero\nfirst");
+        let edit = TextEdit::between(&old, &new).unwrap();
+        window.shift(&edit, &old, &new);
+        assert_eq!(window.cursor(), Index2::new(1, 5));
+        assert_eq!(window.cursors, vec![Cursor::new(Index2::new(1, 2), None)]);
+    }
+
+    #[test]
+    fn test_new_window() {
+        let window = WindowState::new(Index2::new(2, 1), Index2::new(1, 0));
+        assert_eq!(window.cursor(), Index2::new(2, 1));
+        assert_eq!(window.offset(), Index2::new(1, 0));
+    }
+}
@@ -0,0 +1,201 @@
+//! The editors text storage
+//!
+//! ## Storage backends
+//!
+//! All editing operations go through the [`TextStorage`] trait, which describes
+//! a text organized in rows and columns of chars. Two backends are provided:
+//!
+//! - [`Rope`]: The default storage of the editor. Edits as well as byte, char and
+//!   line index conversions are `O(log n)`, and cloning is `O(1)` which keeps undo
+//!   captures cheap for large files.
+//! - [`Jagged<char>`](::jagged::Jagged): A vector of char vectors. Simple, but every
+//!   char takes 4 bytes and cloning copies the whole text.
+//!
+//! The benchmarks in `benches/text.rs` compare both backends.
+mod iter;
+mod jagged;
+mod rope;
+
+use std::io;
+
+pub use self::{iter::TextIter, rope::Rope};
+use crate::Index2;
+
+/// A text organized in rows and columns of chars.
+///
+/// Rows never contain line breaks. Columns are counted in chars, not bytes.
+pub trait TextStorage: Clone + Default + PartialEq + for<'a> From<&'a str> {
+    /// Returns the number of rows.
+    ///
+    /// A storage may report zero rows for an empty text.
+    fn len(&self) -> usize;
+
+    /// Returns the number of columns of a given row.
+    /// Returns None if the row is out of bounds.
+    fn len_col(&self, row: usize) -> Option<usize>;
+
+    /// Returns the char at a given position.
+    /// Returns None if the position is out of bounds.
+    fn get(&self, index: Index2) -> Option<char>;
+
+    /// Returns the content of a row without its line break.
+    /// Returns None if the row is out of bounds.
+    fn line(&self, row: usize) -> Option<String>;
+
+    /// Inserts a char at a given position.
+    ///
+    /// The char must not be a line break, use [`TextStorage::split_line`] instead.
+    fn insert_char(&mut self, index: Index2, ch: char);
+
+    /// Inserts a string, which may contain line breaks, at a given position.
+    fn insert_str(&mut self, index: Index2, text: &str);
+
+    /// Removes and returns the char at a given position.
+    /// Returns None if the position is out of bounds.
+    fn remove_char(&mut self, index: Index2) -> Option<char>;
+
+    /// Inserts a new row at a given row index, shifting all rows after it.
+    fn insert_line(&mut self, row: usize, line: &str);
+
+    /// Removes a row. Removing the only row leaves an empty text.
+    fn remove_line(&mut self, row: usize);
+
+    /// Appends a row to the back of the text.
+    fn push_line(&mut self, line: &str);
+
+    /// Splits a row in two at a given position.
+    fn split_line(&mut self, at: Index2);
+
+    /// Joins a row with the row following it.
+    fn join_lines(&mut self, row: usize);
+
+    /// Returns the text between two positions, both inclusive.
+    fn slice(&self, start: Index2, end: Index2) -> String;
+
+    /// Converts a position into a char index.
+    fn index_to_char(&self, index: Index2) -> usize;
+
+    /// Converts a char index into a position.
+    fn char_to_index(&self, char_idx: usize) -> Index2;
+
+    /// Converts a char index into a byte index.
+    fn char_to_byte(&self, char_idx: usize) -> usize;
+
+    /// Converts a byte index into a char index.
+    fn byte_to_char(&self, byte_idx: usize) -> usize;
+
+    /// Returns `true` if the text contains no chars.
+    fn is_empty(&self) -> bool {
+        self.len() == 0 || self.len() == 1 && self.len_col(0) == Some(0)
+    }
+
+    /// Returns an iterator that yields the chars along with their position.
+    /// Empty rows yield `None`, see [`TextIter`].
+    fn iter(&self) -> TextIter<'_, Self> {
+        TextIter::new(self)
+    }
+
+    /// Returns the content of all rows.
+    fn rows(&self) -> Vec<String> {
+        (0..self.len()).filter_map(|row| self.line(row)).collect()
+    }
+
+    /// Returns the positions of all disjoint matches of a pattern.
+    /// Matches never span multiple rows.
+    fn match_indices(&self, pattern: &[char]) -> Vec<Index2> {
+        let mut matches = Vec::new();
+        if pattern.is_empty() {
+            return matches;
+        }
+        for row in 0..self.len() {
+            let Some(line) = self.line(row) else { continue };
+            let line: Vec<char> = line.chars().collect();
+            let mut col = 0;
+            while col + pattern.len() <= line.len() {
+                if line[col..col + pattern.len()] == *pattern {
+                    matches.push(Index2::new(row, col));
+                    col += pattern.len();
+                } else {
+                    col += 1;
+                }
+            }
+        }
+        matches
+    }
+
+    /// Writes the text into a writer. Rows are separated by `\n`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the writer fails.
+    fn write_to<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        for row in 0..self.len() {
+            if row > 0 {
+                writer.write_all(b"\n")?;
+            }
+            writer.write_all(self.line(row).unwrap_or_default().as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_storage<T: TextStorage + std::fmt::Debug>() {
+        let mut text = T::from("Hello\n\nWorld");
+        assert_eq!(text.len(), 3);
+        assert_eq!(text.len_col(0), Some(5));
+        assert_eq!(text.len_col(1), Some(0));
+        assert_eq!(text.len_col(3), None);
+        assert_eq!(text.get(Index2::new(2, 1)), Some('o'));
+        assert_eq!(text.get(Index2::new(1, 0)), None);
+
+        text.insert_char(Index2::new(0, 5), '!');
+        text.split_line(Index2::new(2, 2));
+        assert_eq!(text, T::from("Hello!\n\nWo\nrld"));
+
+        text.join_lines(2);
+        assert_eq!(text.remove_char(Index2::new(0, 5)), Some('!'));
+        assert_eq!(text, T::from("Hello\n\nWorld"));
+
+        text.insert_str(Index2::new(1, 0), "a\nb");
+        assert_eq!(text, T::from("Hello\na\nb\nWorld"));
+        assert_eq!(text.slice(Index2::new(0, 3), Index2::new(2, 0)), "lo\na\nb");
+
+        text.remove_line(1);
+        text.insert_line(0, "first");
+        text.push_line("last");
+        assert_eq!(text, T::from("first\nHello\nb\nWorld\nlast"));
+
+        assert_eq!(text.index_to_char(Index2::new(1, 2)), 8);
+        assert_eq!(text.char_to_index(8), Index2::new(1, 2));
+        assert_eq!(text.match_indices(&['l', 'l']), vec![Index2::new(1, 2)]);
+
+        for _ in 0..5 {
+            text.remove_line(0);
+        }
+        assert!(text.is_empty());
+    }
+
+    #[test]
+    fn test_rope() {
+        check_storage::<Rope>();
+    }
+
+    #[test]
+    fn test_jagged() {
+        check_storage::<::jagged::Jagged<char>>();
+    }
+
+    #[test]
+    fn test_byte_conversion() {
+        let text = Rope::from("añb\nc");
+        assert_eq!(text.char_to_byte(2), 3);
+        assert_eq!(text.byte_to_char(3), 2);
+        let text = ::jagged::Jagged::<char>::from("añb\nc");
+        assert_eq!(text.char_to_byte(4), 5);
+        assert_eq!(text.byte_to_char(5), 4);
+    }
+}
@@ -0,0 +1,132 @@
+#![allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+use super::TextStorage;
+use crate::Index2;
+
+/// An iterator over the chars of a [`TextStorage`] along with their position.
+///
+/// Yields `None` as value for empty rows. Can be started from and stopped at
+/// a given position, and iterated in both directions.
+pub struct TextIter<'a, T: ?Sized> {
+    data: &'a T,
+    row: isize,
+    col: usize,
+    end: Option<Index2>,
+    stop: bool,
+}
+
+impl<'a, T: TextStorage> TextIter<'a, T> {
+    /// Instantiates a new [`TextIter`] that starts at the first position.
+    #[must_use]
+    pub fn new(data: &'a T) -> Self {
+        Self { data, row: 0, col: 0, end: None, stop: false }
+    }
+
+    /// A [`TextIter`] that starts from a given position.
+    #[must_use]
+    pub fn from(self, index: Index2) -> Self {
+        Self { row: index.row as isize, col: index.col, end: None, ..self }
+    }
+
+    /// A [`TextIter`] that ends at a given position.
+    #[must_use]
+    pub fn to(self, index: Index2) -> Self {
+        Self { end: Some(index), ..self }
+    }
+
+    fn out_of_bounds(&self, index: Index2) -> bool {
+        let Some(len_col) = self.data.len_col(index.row) else {
+            return true;
+        };
+        index.col != 0 && index.col >= len_col
+    }
+
+    fn next_index(&self, index: Index2) -> Option<Index2> {
+        let len_col = self.data.len_col(index.row)?;
+        let is_last_row = index.row + 1 >= self.data.len();
+        let is_last_col = index.col >= len_col.saturating_sub(1);
+        match (is_last_row, is_last_col) {
+            (true, true) => None,
+            (false, true) => Some(Index2::new(index.row + 1, 0)),
+            _ => Some(Index2::new(index.row, index.col + 1)),
+        }
+    }
+
+    fn prev_index(&self, index: Index2) -> Option<Index2> {
+        self.data.len_col(index.row)?;
+        match (index.row == 0, index.col == 0) {
+            (true, true) => None,
+            (false, true) => {
+                let row = index.row - 1;
+                Some(Index2::new(row, self.data.len_col(row).unwrap_or_default().saturating_sub(1)))
+            },
+            _ => Some(Index2::new(index.row, index.col - 1)),
+        }
+    }
+}
+
+impl<'a, T: TextStorage> Iterator for TextIter<'a, T> {
+    type Item = (Option<char>, Index2);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = Index2::new(self.row as usize, self.col);
+        if self.stop || self.row < 0 || self.out_of_bounds(current) {
+            return None;
+        }
+        if let Some(index) = self.next_index(current) {
+            self.row = index.row as isize;
+            self.col = index.col;
+        } else {
+            self.stop = true;
+        }
+        if Some(current) == self.end {
+            self.stop = true;
+        }
+        Some((self.data.get(current), current))
+    }
+}
+
+impl<'a, T: TextStorage> DoubleEndedIterator for TextIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.stop || self.row < 0 {
+            return None;
+        }
+        let current = Index2::new(self.row as usize, self.col);
+        if let Some(index) = self.prev_index(current) {
+            self.row = index.row as isize;
+            self.col = index.col;
+        } else {
+            self.stop = true;
+        }
+        if Some(current) == self.end {
+            self.stop = true;
+        }
+        Some((self.data.get(current), current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::Rope;
+
+    fn collect(iter: impl Iterator<Item = (Option<char>, Index2)>) -> String {
+        let mut result = String::new();
+        let mut row = None;
+        for (value, index) in iter {
+            if row.is_some() && row != Some(index.row) {
+                result.push('\n');
+            }
+            row = Some(index.row);
+            result.extend(value);
+        }
+        result
+    }
+
+    #[test]
+    fn test_iter() {
+        let text = Rope::from("h world!\n\n123.");
+        assert_eq!(collect(text.iter()), "h world!\n\n123.");
+        assert_eq!(collect(text.iter().from(Index2::new(0, 3)).to(Index2::new(2, 1))), "orld!\n\n12");
+        assert_eq!(collect(text.iter().from(Index2::new(2, 3)).rev()), ".321\n\n!dlrow h");
+    }
+}
@@ -0,0 +1,131 @@
+use jagged::{index::RowIndex, Jagged};
+
+use super::TextStorage;
+use crate::Index2;
+
+impl TextStorage for Jagged<char> {
+    fn len(&self) -> usize {
+        Jagged::len(self)
+    }
+
+    fn len_col(&self, row: usize) -> Option<usize> {
+        Jagged::len_col(self, row)
+    }
+
+    fn get(&self, index: Index2) -> Option<char> {
+        Jagged::get(self, index).copied()
+    }
+
+    fn line(&self, row: usize) -> Option<String> {
+        Jagged::get(self, RowIndex::new(row)).map(|line| line.iter().collect())
+    }
+
+    fn insert_char(&mut self, index: Index2, ch: char) {
+        if Jagged::is_empty(self) {
+            self.push(Vec::new());
+        }
+        self.insert(index, ch);
+    }
+
+    fn insert_str(&mut self, index: Index2, text: &str) {
+        if Jagged::is_empty(self) {
+            self.push(Vec::new());
+        }
+        let mut rest = self.split_off(index);
+        let mut inserted = Jagged::from(text);
+        if text.ends_with('\n') {
+            // `From<&str>` adds an empty row for a trailing line break.
+            let _ = inserted.remove(RowIndex::new(inserted.len() - 1));
+        }
+        if index.col == 0 {
+            // Splitting at column zero does not leave an empty row to merge into.
+            self.push(Vec::new());
+        }
+        self.merge(&mut inserted);
+        if text.ends_with('\n') {
+            self.push(Vec::new());
+        }
+        self.merge(&mut rest);
+    }
+
+    fn remove_char(&mut self, index: Index2) -> Option<char> {
+        Jagged::get(self, index)?;
+        Some(self.remove(index))
+    }
+
+    fn insert_line(&mut self, row: usize, line: &str) {
+        self.insert(RowIndex::new(row.min(Jagged::len(self))), line.chars().collect::<Vec<_>>());
+    }
+
+    fn remove_line(&mut self, row: usize) {
+        if row < Jagged::len(self) {
+            let _ = self.remove(RowIndex::new(row));
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        self.push(line.chars().collect::<Vec<_>>());
+    }
+
+    fn split_line(&mut self, at: Index2) {
+        if at.col == 0 {
+            self.insert(RowIndex::new(at.row), Vec::new());
+        } else {
+            let mut rest = self.split_off(at);
+            self.append(&mut rest);
+        }
+    }
+
+    fn join_lines(&mut self, row: usize) {
+        if row + 1 < Jagged::len(self) {
+            let mut rest = self.split_off(Index2::new(row + 1, 0));
+            self.merge(&mut rest);
+        }
+    }
+
+    fn slice(&self, start: Index2, end: Index2) -> String {
+        let mut result = String::new();
+        let mut row = start.row;
+        for (value, index) in self.iter().from(start).to(end) {
+            if index.row != row {
+                result.push('\n');
+                row = index.row;
+            }
+            result.extend(value);
+        }
+        result
+    }
+
+    fn index_to_char(&self, index: Index2) -> usize {
+        self.iter_row().take(index.row).map(|line| line.len() + 1).sum::<usize>() + index.col
+    }
+
+    fn char_to_index(&self, char_idx: usize) -> Index2 {
+        let mut start = 0;
+        for (row, line) in self.iter_row().enumerate() {
+            if char_idx <= start + line.len() {
+                return Index2::new(row, char_idx - start);
+            }
+            start += line.len() + 1;
+        }
+        let row = Jagged::len(self).saturating_sub(1);
+        Index2::new(row, Jagged::len_col(self, row).unwrap_or_default())
+    }
+
+    fn char_to_byte(&self, char_idx: usize) -> usize {
+        let line_break = Some('\n');
+        self.flatten(&line_break).iter().take(char_idx).map(|ch| ch.len_utf8()).sum()
+    }
+
+    fn byte_to_char(&self, byte_idx: usize) -> usize {
+        let line_break = Some('\n');
+        let mut bytes = 0;
+        for (i, ch) in self.flatten(&line_break).iter().enumerate() {
+            if bytes >= byte_idx {
+                return i;
+            }
+            bytes += ch.len_utf8();
+        }
+        self.flatten(&line_break).len()
+    }
+}
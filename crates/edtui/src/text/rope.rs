@@ -0,0 +1,200 @@
+use std::{fmt, io};
+
+use super::TextStorage;
+use crate::Index2;
+
+/// A rope based text storage.
+///
+/// Wraps a [`ropey::Rope`]. Only `\n` is treated as a line break, a text
+/// always has at least one (possibly empty) row.
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct Rope(ropey::Rope);
+
+impl Rope {
+    /// Returns the number of chars.
+    #[must_use]
+    pub fn len_chars(&self) -> usize {
+        self.0.len_chars()
+    }
+
+    /// Returns the number of bytes.
+    #[must_use]
+    pub fn len_bytes(&self) -> usize {
+        self.0.len_bytes()
+    }
+
+    /// Returns the char index of the start of a row.
+    #[must_use]
+    pub fn line_to_char(&self, row: usize) -> usize {
+        self.0.line_to_char(row.min(self.0.len_lines()))
+    }
+
+    /// Returns the row of a char index.
+    #[must_use]
+    pub fn char_to_line(&self, char_idx: usize) -> usize {
+        self.0.char_to_line(char_idx.min(self.0.len_chars()))
+    }
+
+    /// Returns the underlying [`ropey::Rope`].
+    #[must_use]
+    pub fn inner(&self) -> &ropey::Rope {
+        &self.0
+    }
+
+    /// Returns the char index of a position, clamped to the end of its row.
+    fn char_idx(&self, index: Index2) -> usize {
+        let len_col = self.len_col(index.row).unwrap_or_default();
+        self.line_to_char(index.row) + index.col.min(len_col)
+    }
+}
+
+impl TextStorage for Rope {
+    fn len(&self) -> usize {
+        self.0.len_lines()
+    }
+
+    fn len_col(&self, row: usize) -> Option<usize> {
+        let line = self.0.get_line(row)?;
+        let len = line.len_chars();
+        if len > 0 && line.char(len - 1) == '\n' {
+            Some(len - 1)
+        } else {
+            Some(len)
+        }
+    }
+
+    fn get(&self, index: Index2) -> Option<char> {
+        if index.col >= self.len_col(index.row)? {
+            return None;
+        }
+        self.0.get_char(self.0.line_to_char(index.row) + index.col)
+    }
+
+    fn line(&self, row: usize) -> Option<String> {
+        let len_col = self.len_col(row)?;
+        Some(self.0.line(row).slice(..len_col).to_string())
+    }
+
+    fn insert_char(&mut self, index: Index2, ch: char) {
+        let char_idx = self.char_idx(index);
+        self.0.insert_char(char_idx, ch);
+    }
+
+    fn insert_str(&mut self, index: Index2, text: &str) {
+        let char_idx = self.char_idx(index);
+        self.0.insert(char_idx, text);
+    }
+
+    fn remove_char(&mut self, index: Index2) -> Option<char> {
+        let ch = self.get(index)?;
+        let char_idx = self.0.line_to_char(index.row) + index.col;
+        self.0.remove(char_idx..=char_idx);
+        Some(ch)
+    }
+
+    fn insert_line(&mut self, row: usize, line: &str) {
+        if row >= self.len() {
+            self.push_line(line);
+            return;
+        }
+        let char_idx = self.0.line_to_char(row);
+        self.0.insert_char(char_idx, '\n');
+        self.0.insert(char_idx, line);
+    }
+
+    fn remove_line(&mut self, row: usize) {
+        let len = self.len();
+        if row >= len {
+            return;
+        }
+        let start = self.0.line_to_char(row);
+        if row + 1 < len {
+            self.0.remove(start..self.0.line_to_char(row + 1));
+        } else {
+            // The last row has no trailing line break, remove the preceding one instead.
+            self.0.remove(start.saturating_sub(1)..);
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        let end = self.0.len_chars();
+        self.0.insert_char(end, '\n');
+        self.0.insert(end + 1, line);
+    }
+
+    fn split_line(&mut self, at: Index2) {
+        let char_idx = self.char_idx(at);
+        self.0.insert_char(char_idx, '\n');
+    }
+
+    fn join_lines(&mut self, row: usize) {
+        if row + 1 >= self.len() {
+            return;
+        }
+        let char_idx = self.0.line_to_char(row + 1) - 1;
+        self.0.remove(char_idx..=char_idx);
+    }
+
+    fn slice(&self, start: Index2, end: Index2) -> String {
+        let start = self.char_idx(start);
+        let inclusive = usize::from(self.get(end).is_some());
+        let end = (self.char_idx(end) + inclusive).max(start);
+        self.0.slice(start..end).to_string()
+    }
+
+    fn index_to_char(&self, index: Index2) -> usize {
+        self.line_to_char(index.row) + index.col
+    }
+
+    fn char_to_index(&self, char_idx: usize) -> Index2 {
+        let row = self.char_to_line(char_idx);
+        Index2::new(row, char_idx.saturating_sub(self.0.line_to_char(row)))
+    }
+
+    fn char_to_byte(&self, char_idx: usize) -> usize {
+        self.0.char_to_byte(char_idx.min(self.0.len_chars()))
+    }
+
+    fn byte_to_char(&self, byte_idx: usize) -> usize {
+        self.0.byte_to_char(byte_idx.min(self.0.len_bytes()))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.len_chars() == 0
+    }
+
+    fn write_to<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        self.0.write_to(writer)
+    }
+}
+
+impl From<&str> for Rope {
+    /// Instantiate a [`Rope`] from a string. `\r\n` line breaks are
+    /// converted to `\n`.
+    fn from(value: &str) -> Self {
+        if value.contains("\r\n") {
+            Self(ropey::Rope::from_str(&value.replace("\r\n", "\n")))
+        } else {
+            Self(ropey::Rope::from_str(value))
+        }
+    }
+}
+
+impl From<Rope> for String {
+    /// Construct a string from a [`Rope`].
+    fn from(value: Rope) -> String {
+        value.0.to_string()
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Rope").field(&self.0.to_string()).finish()
+    }
+}
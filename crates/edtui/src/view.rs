@@ -2,26 +2,48 @@
 pub mod explorer;
 pub mod status_line;
 pub mod theme;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use ratatui::prelude::*;
 pub use status_line::StatusLine;
 use synoptic::{trim, TokOpt};
 
 use self::theme::EditorTheme;
-use crate::{helper::max_col, state::EditorState, EditorMode, Index2};
+use crate::{helper::max_col, state::EditorState, text::TextStorage, EditorMode, Index2};
 
-#[derive(Debug, Clone, Default)]
+/// A message in the status line, shown for a duration after it was created.
+#[derive(Debug, Clone)]
 pub struct EditorMessage {
     message: String,
     duration: Duration,
+    created: Instant,
+    style: Style,
 }
 
 impl EditorMessage {
     /// Creates a new instance of [`EditorMessage`].
     #[must_use]
     pub fn new(message: String, duration: Duration) -> Self {
-        Self { message, duration }
+        Self { message, duration, created: Instant::now(), style: Style::default() }
+    }
+
+    /// Sets the style of the message, e.g. to tell errors apart.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns the text of the message.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.message
+    }
+
+    /// Whether the message was shown for its duration.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.created.elapsed() >= self.duration
     }
 }
 
@@ -67,8 +89,9 @@ impl<'a, 'b> EditorView<'a, 'b> {
     pub fn get_state_mut(&'a mut self) -> &'a mut EditorState {
         self.state
     }
-    fn highlight_colour(&self, name: &str) -> Color {
-        self.theme.highlighting.get(name).unwrap_or(Color::Reset)
+
+    fn highlight_style(&self, name: &str) -> Style {
+        self.theme.highlighting.get(name).unwrap_or(Style::default().fg(Color::Reset))
     }
 }
 
@@ -100,6 +123,8 @@ impl Widget for EditorView<'_, '_> {
         ])
         .areas(main);
 
+        self.state.view.set_area(area, main);
+
         let width = main.width as usize;
         let height = main.height as usize;
 
@@ -113,9 +138,18 @@ impl Widget for EditorView<'_, '_> {
         let size = (width, height);
         let (x_off, y_off) = self.state.view.update_offset(size, cursor);
 
-        // Rendering the text and the selection.
+        // Highlight the visible rows. Only rows that changed since the
+        // last render are tokenized again.
         let lines = &self.state.lines;
-        for (i, line) in lines.iter_row().skip(y_off).take(height).enumerate() {
+        let rows = y_off..lines.len().min(y_off + height);
+        self.state.highlighter.update(lines, rows.clone());
+
+        // Rendering the text and the selections of all cursors.
+        let selections =
+            self.state.selection.iter().chain(self.state.cursors.iter().filter_map(|c| c.selection.as_ref()));
+        let selections = selections.collect::<Vec<_>>();
+        let selected = |position: Index2| selections.iter().any(|selection| selection.within(&position));
+        for (i, line) in rows.filter_map(|row| lines.line(row)).enumerate() {
             let y = (main.top() as usize) as u16 + i as u16;
             // Render the line number.
             if let Some(line_numbers_style) = self.theme.line_numbers_style {
@@ -131,23 +165,22 @@ impl Widget for EditorView<'_, '_> {
                 }
             }
 
-            let tokens = self.state.highlighter.line(y_off + i, &line.iter().collect());
-            let tokens = trim(&tokens, x_off);
+            let tokens = match self.state.highlighter.line(y_off + i) {
+                Some(tokens) => trim(tokens, x_off),
+                None => trim(&[TokOpt::None(line)], x_off),
+            };
             let mut j = 0;
             for token in tokens {
                 match token {
                     TokOpt::Some(text, kind) => {
-                        let color = self.highlight_colour(&kind);
+                        let style = self.highlight_style(&kind);
                         for c in text.chars() {
                             let x = (main.left() as usize) as u16 + j as u16;
-                            if let Some(selection) = &self.state.selection {
-                                let position = Index2::new(y_off + i, x_off + j);
-                                if selection.within(&position) {
-                                    buf.get_mut(x, y).set_style(self.theme.selection_style);
-                                }
+                            if selected(Index2::new(y_off + i, x_off + j)) {
+                                buf.get_mut(x, y).set_style(self.theme.selection_style);
                             }
                             if x < main.right() && y < main.bottom() {
-                                buf.get_mut(x, y).set_symbol(&c.to_string()).set_style(Style::default().fg(color));
+                                buf.get_mut(x, y).set_symbol(&c.to_string()).set_style(style);
                                 j += 1;
                             } else {
                                 break;
@@ -157,11 +190,8 @@ impl Widget for EditorView<'_, '_> {
                     TokOpt::None(text) => {
                         for c in text.chars() {
                             let x = (main.left() as usize) as u16 + j as u16;
-                            if let Some(selection) = &self.state.selection {
-                                let position = Index2::new(y_off + i, x_off + j);
-                                if selection.within(&position) {
-                                    buf.get_mut(x, y).set_style(self.theme.selection_style);
-                                }
+                            if selected(Index2::new(y_off + i, x_off + j)) {
+                                buf.get_mut(x, y).set_style(self.theme.selection_style);
                             }
                             if x < main.right() && y < main.bottom() {
                                 buf.get_mut(x, y).set_symbol(&c.to_string());
@@ -184,15 +214,32 @@ impl Widget for EditorView<'_, '_> {
             cursor_cell.set_symbol(&symbol.to_string());
         }
 
+        // Render the secondary cursors that are inside the viewport.
+        for cursor in &self.state.cursors {
+            let max_col = max_col(&self.state.lines, &cursor.position, self.state.mode);
+            let (row, col) = (cursor.position.row, cursor.position.col.min(max_col));
+            if row < y_off || row >= y_off + height || col < x_off || col >= x_off + width {
+                continue;
+            }
+            let x = main.left() + (col - x_off) as u16;
+            let y = main.top() + (row - y_off) as u16;
+            buf.get_mut(x, y).set_style(self.theme.cursor_style);
+        }
+
         // Render the status line.
         if let Some(s) = self.theme.status_line {
+            let message = self.message.filter(|message| {
+                !message.is_expired() && !matches!(self.state.mode, EditorMode::Search | EditorMode::Command)
+            });
             s.mode(self.state.mode.name())
+                .message(message.map(|message| (message.message, message.style)))
                 .search(if self.state.mode == EditorMode::Search {
                     Some(self.state.search.pattern.clone())
                 } else {
                     None
                 })
                 .command(if self.state.mode == EditorMode::Command { Some(self.state.command.clone()) } else { None })
+                .pending(Some(self.state.pending_keys.clone()).filter(|keys| !keys.is_empty()))
                 .render(status, buf);
         }
     }
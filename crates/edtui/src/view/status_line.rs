@@ -19,6 +19,10 @@ pub struct StatusLine {
     command: Option<String>,
     // Custom text to display on the opposite side of the mode
     text: Option<String>,
+    /// The keys of a pending key sequence, shown left of the text.
+    pending: Option<String>,
+    /// A message and its style, shown in place of the search or command.
+    message: Option<(String, Style)>,
 }
 
 impl Default for StatusLine {
@@ -34,6 +38,8 @@ impl Default for StatusLine {
             align_left: true,
             command: None,
             text: None,
+            pending: None,
+            message: None,
         }
     }
 }
@@ -95,6 +101,22 @@ impl StatusLine {
         self
     }
 
+    /// Overwrite the pending keys for the status line.
+    ///
+    /// This method is used internally to show the keys of a pending sequence.
+    #[must_use]
+    pub fn pending<S: Into<String>>(mut self, pending: Option<S>) -> Self {
+        self.pending = pending.map(Into::into);
+        self
+    }
+
+    /// Overwrite the message for the status line.
+    #[must_use]
+    pub fn message(mut self, message: Option<(String, Style)>) -> Self {
+        self.message = message;
+        self
+    }
+
     /// Overwrite the text content for the status line.
     #[must_use]
     pub fn text<S: Into<String>>(mut self, text: Option<S>) -> Self {
@@ -124,20 +146,42 @@ impl Widget for StatusLine {
         let command_paragraph =
             Paragraph::new(Line::from(Span::from(command_text))).alignment(Alignment::Left).style(self.style_line);
 
+        let message_paragraph = self.message.map(|(message, style)| {
+            Paragraph::new(Line::from(Span::styled(format!(" {message}"), style))).style(self.style_line)
+        });
+
+        let pending = self.pending.unwrap_or_default();
+        let pending_width = pending.chars().count() as u16;
+        let pending_paragraph =
+            Paragraph::new(Line::from(Span::from(pending))).alignment(Alignment::Right).style(self.style_line);
+
         let text_paragraph = self.text.map_or(String::new(), |s| s);
+        let text_width = text_paragraph.chars().count() as u16 + 1;
         let text_paragraph =
             Paragraph::new(Line::from(Span::from(text_paragraph))).alignment(Alignment::Right).style(self.style_line);
         // Determine the alignment position
+        let content = if self.align_left { right } else { left };
         if self.align_left {
             mode_paragraph.render(left, buf);
             search_paragraph.render(right, buf);
             command_paragraph.render(right, buf);
+            message_paragraph.render(right, buf);
             text_paragraph.render(right, buf);
         } else {
             search_paragraph.render(left, buf);
             command_paragraph.render(left, buf);
+            message_paragraph.render(left, buf);
             mode_paragraph.render(right, buf);
             text_paragraph.render(left, buf);
         };
+        if pending_width > 0 {
+            let [_, pending_area, _] = Layout::horizontal([
+                Constraint::Min(0),
+                Constraint::Length(pending_width),
+                Constraint::Length(text_width),
+            ])
+            .areas(content);
+            pending_paragraph.render(pending_area, buf);
+        }
     }
 }
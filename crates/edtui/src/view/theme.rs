@@ -25,39 +25,55 @@ pub struct EditorTheme<'a> {
     pub line_numbers_style: Option<Style>,
     /// The file explorer
     pub explorer: Option<Explorer>,
-    /// Highlighting token to color mapping
+    /// Highlighting token to style mapping
     pub highlighting: Highlighting,
 }
 
-pub struct Highlighting(HashMap<String, Color>);
+/// Maps the highlighting tokens, e.g. "keyword" or "comment", to their styles.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Highlighting(HashMap<String, Style>);
+
 impl Highlighting {
-    pub fn get(&self, name: &str) -> Option<Color> {
-        self.0.get(name).cloned()
+    /// Returns the style of a token.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<Style> {
+        self.0.get(name).copied()
+    }
+
+    /// Sets the style of a token.
+    pub fn set(&mut self, name: impl Into<String>, style: Style) {
+        self.0.insert(name.into(), style);
+    }
+
+    /// Returns the names of all styled tokens.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
     }
 }
+
 impl Default for Highlighting {
     fn default() -> Self {
         Self(HashMap::from([
-            ("string".to_string(), Color::Rgb(54, 161, 102)),
-            ("comment".to_string(), Color::Rgb(108, 107, 90)),
-            ("digit".to_string(), Color::Rgb(157, 108, 124)),
-            ("keyword".to_string(), Color::Rgb(91, 157, 72)),
-            ("attribute".to_string(), Color::Rgb(95, 145, 130)),
-            ("character".to_string(), Color::Rgb(125, 151, 38)),
-            ("type".to_string(), Color::Rgb(165, 152, 13)),
-            ("function".to_string(), Color::Rgb(174, 115, 19)),
-            ("header".to_string(), Color::Rgb(174, 115, 19)),
-            ("macro".to_string(), Color::Rgb(157, 108, 124)),
-            ("namespace".to_string(), Color::Rgb(125, 151, 38)),
-            ("struct".to_string(), Color::Rgb(125, 151, 38)),
-            ("operator".to_string(), Color::Rgb(95, 145, 130)),
-            ("boolean".to_string(), Color::Rgb(54, 161, 102)),
-            ("reference".to_string(), Color::Rgb(91, 157, 72)),
-            ("tag".to_string(), Color::Rgb(95, 145, 130)),
-            ("heading".to_string(), Color::Rgb(174, 115, 19)),
-            ("link".to_string(), Color::Rgb(157, 108, 124)),
-            ("key".to_string(), Color::Rgb(157, 108, 124)),
-            ("table".to_string(), Color::Rgb(157, 108, 124)),
+            ("string".to_string(), Style::default().fg(Color::Rgb(54, 161, 102))),
+            ("comment".to_string(), Style::default().fg(Color::Rgb(108, 107, 90))),
+            ("digit".to_string(), Style::default().fg(Color::Rgb(157, 108, 124))),
+            ("keyword".to_string(), Style::default().fg(Color::Rgb(91, 157, 72))),
+            ("attribute".to_string(), Style::default().fg(Color::Rgb(95, 145, 130))),
+            ("character".to_string(), Style::default().fg(Color::Rgb(125, 151, 38))),
+            ("type".to_string(), Style::default().fg(Color::Rgb(165, 152, 13))),
+            ("function".to_string(), Style::default().fg(Color::Rgb(174, 115, 19))),
+            ("header".to_string(), Style::default().fg(Color::Rgb(174, 115, 19))),
+            ("macro".to_string(), Style::default().fg(Color::Rgb(157, 108, 124))),
+            ("namespace".to_string(), Style::default().fg(Color::Rgb(125, 151, 38))),
+            ("struct".to_string(), Style::default().fg(Color::Rgb(125, 151, 38))),
+            ("operator".to_string(), Style::default().fg(Color::Rgb(95, 145, 130))),
+            ("boolean".to_string(), Style::default().fg(Color::Rgb(54, 161, 102))),
+            ("reference".to_string(), Style::default().fg(Color::Rgb(91, 157, 72))),
+            ("tag".to_string(), Style::default().fg(Color::Rgb(95, 145, 130))),
+            ("heading".to_string(), Style::default().fg(Color::Rgb(174, 115, 19))),
+            ("link".to_string(), Style::default().fg(Color::Rgb(157, 108, 124))),
+            ("key".to_string(), Style::default().fg(Color::Rgb(157, 108, 124))),
+            ("table".to_string(), Style::default().fg(Color::Rgb(157, 108, 124))),
         ]))
     }
 }
@@ -174,6 +190,14 @@ impl<'a> EditorTheme<'a> {
         self.line_numbers_style = Some(style);
         self
     }
+
+    /// This method allows you to customize the styles of the syntax
+    /// highlighting tokens. See [`Highlighting`].
+    #[must_use]
+    pub fn highlighting(mut self, highlighting: Highlighting) -> Self {
+        self.highlighting = highlighting;
+        self
+    }
 }
 
 // Tailwind slate c100
@@ -16,4 +16,6 @@ pub enum Action {
     Resume,
     Refresh,
     Error(String),
+    /// Files open in the editor changed on disk.
+    FilesChanged,
 }
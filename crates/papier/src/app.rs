@@ -22,13 +22,28 @@ pub struct App {
     pub should_quit: bool,
     pub should_suspend: bool,
     pub last_tick_key_events: Vec<KeyEvent>,
+    /// Errors of the start-up, shown once the app runs.
+    pub errors: Vec<String>,
 }
 
 impl App {
-    pub fn new(tick_rate: f64, frame_rate: f64, files: Vec<PathBuf>) -> Result<Self> {
-        let editor = Editor::new(files);
+    pub fn new(
+        tick_rate: f64,
+        frame_rate: f64,
+        files: Vec<PathBuf>,
+        theme: Option<String>,
+        session: Option<String>,
+    ) -> Result<Self> {
+        let editor = Editor::new(files).with_session(session);
         // let fps = FpsCounter::default();
-        let config = Config::new()?;
+        let mut errors = Vec::new();
+        let mut config = Config::new().unwrap_or_else(|err| {
+            errors.push(format!("Invalid configuration: {err}"));
+            Config::builtin()
+        });
+        if theme.is_some() {
+            config.theme = theme;
+        }
         Ok(Self {
             tick_rate,
             frame_rate,
@@ -37,6 +52,7 @@ impl App {
             should_suspend: false,
             config,
             last_tick_key_events: Vec::new(),
+            errors,
         })
     }
 
@@ -59,6 +75,10 @@ impl App {
             component.init(tui.size()?)?;
         }
 
+        for error in self.errors.drain(..) {
+            action_tx.send(Action::Error(error))?;
+        }
+
         loop {
             if let Some(e) = tui.next().await {
                 match e {
@@ -69,9 +89,13 @@ impl App {
                     _ => {},
                 }
                 for component in self.components.iter_mut() {
-                    if let Some(action) = component.handle_events(Some(e.clone()))? {
-                        log::debug!("Sending action: {action:?}");
-                        action_tx.send(action)?;
+                    match component.handle_events(Some(e.clone())) {
+                        Ok(Some(action)) => {
+                            log::debug!("Sending action: {action:?}");
+                            action_tx.send(action)?;
+                        },
+                        Ok(None) => {},
+                        Err(err) => action_tx.send(Action::Error(err.to_string()))?,
                     }
                 }
             }
@@ -112,9 +136,13 @@ impl App {
                     _ => {},
                 }
                 for component in self.components.iter_mut() {
-                    if let Some(action) = component.update(action.clone())? {
-                        action_tx.send(action)?
-                    };
+                    match component.update(action.clone()) {
+                        Ok(Some(action)) => action_tx.send(action)?,
+                        Ok(None) => {},
+                        // An error while handling an error is not reported again.
+                        Err(err) if matches!(action, Action::Error(_)) => log::error!("{err}"),
+                        Err(err) => action_tx.send(Action::Error(err.to_string()))?,
+                    }
                 }
             }
             if self.should_suspend {
@@ -25,6 +25,12 @@ pub struct Cli {
     )]
     pub frame_rate: f64,
 
+    #[arg(long, value_name = "NAME", help = "Color scheme, overrides the configured theme")]
+    pub theme: Option<String>,
+
+    #[arg(long, value_name = "NAME", help = "Session to restore, see :mksession")]
+    pub session: Option<String>,
+
     #[arg(value_name = "PATH", help = "Paths to the files to open")]
     pub files: Vec<PathBuf>,
 }
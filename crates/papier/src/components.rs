@@ -13,8 +13,12 @@ use crate::{
     tui::{Event, Frame},
 };
 
+pub mod dashboard;
 pub mod editor;
 pub mod fps;
+pub mod hints;
+pub mod picker;
+pub mod tab_line;
 
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 /// Implementors of this trait can be registered with the main application loop and will be able to receive events,
@@ -71,6 +75,9 @@ pub trait Component {
         let r = match event {
             Some(Event::Key(key_event)) => self.handle_key_events(key_event)?,
             Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event)?,
+            Some(Event::Paste(text)) => self.handle_paste_events(text)?,
+            Some(Event::FocusGained) => self.handle_focus_events(true)?,
+            Some(Event::FocusLost) => self.handle_focus_events(false)?,
             _ => None,
         };
         Ok(r)
@@ -101,6 +108,33 @@ pub trait Component {
     fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
         Ok(None)
     }
+    /// Handle pasted text and produce actions if necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of a bracketed paste.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    #[allow(unused_variables)]
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        Ok(None)
+    }
+    /// Handle the terminal gaining or losing the focus and produce actions if
+    /// necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `focused` - Whether the terminal gained the focus.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    #[allow(unused_variables)]
+    fn handle_focus_events(&mut self, focused: bool) -> Result<Option<Action>> {
+        Ok(None)
+    }
     /// Update the state of the component based on a received action. (REQUIRED)
     ///
     /// # Arguments
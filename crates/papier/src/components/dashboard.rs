@@ -0,0 +1,246 @@
+//! The start screen shown when the editor starts without files. It lists the
+//! recent files, the recent sessions and quick actions.
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{layout::Position, prelude::*, widgets::*};
+
+/// The number of recent files shown, each with a digit as key.
+const RECENT_FILES: usize = 9;
+
+/// The number of recent sessions shown.
+const RECENT_SESSIONS: usize = 5;
+
+/// The width of the entries.
+const WIDTH: u16 = 60;
+
+/// What an entry of the dashboard opens or does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DashboardItem {
+    File(PathBuf),
+    Session(String),
+    NewFile,
+    Explorer,
+    /// Finds a recent file with the fuzzy picker.
+    RecentFiles,
+    EditConfig,
+}
+
+/// An entry of the dashboard, selectable with its key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    key: Option<char>,
+    label: String,
+    item: DashboardItem,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Dashboard {
+    /// The sections, a heading and its entries.
+    sections: Vec<(&'static str, Vec<Entry>)>,
+    /// The index of the selected entry, counting through all sections.
+    selected: usize,
+    /// The screen areas of the entries of the last render.
+    areas: Vec<Rect>,
+}
+
+impl Dashboard {
+    /// Lists the most recent of `files` and `sessions`. Files are shown
+    /// relative to `cwd` if they are in it.
+    #[must_use]
+    pub fn new(files: &[PathBuf], sessions: &[String], cwd: &Path) -> Self {
+        let files = files.iter().take(RECENT_FILES).zip('1'..='9').map(|(path, key)| {
+            let label = path.strip_prefix(cwd).unwrap_or(path).display().to_string();
+            Entry { key: Some(key), label, item: DashboardItem::File(path.clone()) }
+        });
+        let sessions = sessions
+            .iter()
+            .take(RECENT_SESSIONS)
+            .map(|name| Entry { key: None, label: name.clone(), item: DashboardItem::Session(name.clone()) });
+        let actions = [
+            ('n', "New file", DashboardItem::NewFile),
+            ('e', "Open the file explorer", DashboardItem::Explorer),
+            ('f', "Find a recent file", DashboardItem::RecentFiles),
+            ('c', "Edit the configuration", DashboardItem::EditConfig),
+        ]
+        .map(|(key, label, item)| Entry { key: Some(key), label: label.to_string(), item });
+        let sections = [
+            ("Recent files", files.collect::<Vec<_>>()),
+            ("Sessions", sessions.collect()),
+            ("Actions", actions.to_vec()),
+        ];
+        Self { sections: sections.into_iter().filter(|(_, entries)| !entries.is_empty()).collect(), ..Self::default() }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.sections.iter().flat_map(|(_, entries)| entries)
+    }
+
+    /// Returns the selected item.
+    pub fn selected(&self) -> Option<&DashboardItem> {
+        self.entries().nth(self.selected).map(|entry| &entry.item)
+    }
+
+    /// Handles a key: `j` and `k` move the selection, enter picks the
+    /// selected item and the key of an entry picks it.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<DashboardItem> {
+        let count = self.entries().count();
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => self.selected = (self.selected + 1) % count,
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Enter | KeyCode::Char('l') => return self.selected().cloned(),
+            KeyCode::Char(c) => {
+                let entry = self.entries().position(|entry| entry.key == Some(c))?;
+                self.selected = entry;
+                return self.selected().cloned();
+            },
+            _ => {},
+        }
+        None
+    }
+
+    /// Selects the entry at a screen position and returns its item.
+    pub fn click(&mut self, position: Position) -> Option<DashboardItem> {
+        self.selected = self.areas.iter().position(|area| area.contains(position))?;
+        self.selected().cloned()
+    }
+}
+
+/// Renders a [`Dashboard`] in the middle of the area.
+pub struct DashboardView<'a> {
+    dashboard: &'a mut Dashboard,
+    style: Style,
+    title_style: Style,
+    key_style: Style,
+    selected_style: Style,
+}
+
+impl<'a> DashboardView<'a> {
+    #[must_use]
+    pub fn new(dashboard: &'a mut Dashboard) -> Self {
+        Self {
+            dashboard,
+            style: Style::default(),
+            title_style: Style::default(),
+            key_style: Style::default(),
+            selected_style: Style::default(),
+        }
+    }
+
+    /// Sets the style of the dashboard.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the style of the title and the headings.
+    #[must_use]
+    pub fn title_style(mut self, style: Style) -> Self {
+        self.title_style = style;
+        self
+    }
+
+    /// Sets the style of the keys of the entries.
+    #[must_use]
+    pub fn key_style(mut self, style: Style) -> Self {
+        self.key_style = style;
+        self
+    }
+
+    /// Sets the style of the selected entry.
+    #[must_use]
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+}
+
+impl Widget for DashboardView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        buf.set_style(area, self.style);
+        let dashboard = self.dashboard;
+        let entries = dashboard.entries().count() as u16;
+        let height = 2 + entries + 2 * dashboard.sections.len() as u16 + 1;
+        let width = WIDTH.min(area.width);
+        let mut y = area.y + area.height.saturating_sub(height) / 2;
+        let x = area.x + (area.width - width) / 2;
+        let mut line = |line: Line, y: &mut u16| {
+            let row = Rect { x, y: *y, width, height: 1 };
+            *y += 1;
+            if row.bottom() > area.bottom() {
+                return None;
+            }
+            line.render(row, buf);
+            Some(row)
+        };
+
+        line(Line::styled("papier", self.title_style).centered(), &mut y);
+        y += 1;
+        dashboard.areas.clear();
+        let mut index = 0;
+        for (heading, entries) in &dashboard.sections {
+            line(Line::styled(*heading, self.title_style), &mut y);
+            for entry in entries {
+                let key = entry.key.map_or_else(|| " ".to_string(), String::from);
+                let mut spans = Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(key, self.key_style),
+                    Span::raw("  "),
+                    entry.label.clone().into(),
+                ]);
+                if index == dashboard.selected {
+                    spans = spans.style(self.selected_style);
+                }
+                dashboard.areas.extend(line(spans, &mut y));
+                index += 1;
+            }
+            y += 1;
+        }
+        line(Line::raw("j/k to move, enter to open, : for commands").centered(), &mut y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_dashboard() {
+        let files = [PathBuf::from("/home/me/notes.txt"), PathBuf::from("/etc/hosts")];
+        let mut dashboard = Dashboard::new(&files, &["work".to_string()], Path::new("/home/me"));
+        assert_eq!(dashboard.selected(), Some(&DashboardItem::File(files[0].clone())));
+
+        assert_eq!(dashboard.handle_key(KeyEvent::from(KeyCode::Char('k'))), None);
+        assert_eq!(dashboard.selected(), Some(&DashboardItem::EditConfig));
+        dashboard.handle_key(KeyEvent::from(KeyCode::Char('j')));
+        dashboard.handle_key(KeyEvent::from(KeyCode::Char('j')));
+        assert_eq!(dashboard.handle_key(KeyEvent::from(KeyCode::Char('j'))), None);
+        assert_eq!(dashboard.handle_key(KeyEvent::from(KeyCode::Enter)), Some(DashboardItem::Session("work".into())));
+        assert_eq!(
+            dashboard.handle_key(KeyEvent::from(KeyCode::Char('2'))),
+            Some(DashboardItem::File(files[1].clone()))
+        );
+        assert_eq!(dashboard.handle_key(KeyEvent::from(KeyCode::Char('x'))), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let files = [PathBuf::from("/home/me/notes.txt")];
+        let mut dashboard = Dashboard::new(&files, &[], Path::new("/home/me"));
+        let area = Rect::new(0, 0, 40, 14);
+        let mut buf = Buffer::empty(area);
+        DashboardView::new(&mut dashboard).render(area, &mut buf);
+        let rows = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf.get(x, y).symbol()).collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows[4].trim_end(), "  1  notes.txt");
+
+        // Clicking an entry picks it
+        let item = dashboard.click(Position { x: 5, y: 8 });
+        assert_eq!(item, Some(DashboardItem::Explorer));
+    }
+}
@@ -3,89 +3,1076 @@ use std::{
     fmt::{Debug, Display},
     fs,
     io::{self, BufRead, Write},
-    path::PathBuf,
-    time::Duration,
+    mem,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
 use color_eyre::eyre::{eyre, Result};
 use config::File;
-use crossterm::event::{Event, KeyCode, KeyEvent};
+use crossterm::event::{Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use edtui::{
-    actions::Execute, state::command::Command, view::EditorMessage, EditorMode, EditorState, EditorTheme, EditorView,
-    Index2, Input, Lines, StatusLine,
+    actions::{Execute, ReplaceText},
+    state::{
+        command::Command,
+        window::{TextEdit, WindowState},
+    },
+    text::TextStorage,
+    view::EditorMessage,
+    EditorMode, EditorState, EditorView, Index2, Input, Lines,
 };
+use encoding_rs::Encoding;
 use log::{debug, trace};
-use ratatui::{prelude::*, style::palette::tailwind::PURPLE, widgets::*};
+use ratatui::{layout::Position, prelude::*, style::palette::tailwind::PURPLE, widgets::*};
 use ratatui_explorer::{FileExplorer, Input as ExplorerInput, Theme as FileTheme};
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
 use tui_logger::{TuiLoggerLevelOutput, TuiLoggerSmartWidget, TuiWidgetEvent, TuiWidgetState};
 
-use super::{Component, Frame};
+pub use self::layout::WindowDirection;
+use self::{
+    file::{Decoding, FileOptions, FileStamp},
+    layout::WindowId,
+    messages::{History, Messages},
+    recent::RecentFiles,
+    recovery::Recovery,
+    session::{Session, SessionBuffer, SessionTabPage, SessionWindow, Sessions},
+    watcher::FileWatcher,
+};
+use super::{
+    dashboard::{Dashboard, DashboardItem, DashboardView},
+    hints::{KeyHints, HINT_DELAY},
+    picker::{Picker, PickerEvent, PickerView},
+    tab_line::{unique_labels, Tab, TabArea, TabLine},
+    Component, Frame,
+};
 use crate::{
     action::Action,
-    config::{Config, KeyBindings},
+    config::{Config, Highlighters, KeyBindings},
+    theme::{self, Theme},
     PapierAction,
 };
 
-pub struct Theme<'a> {
-    pub editor: EditorTheme<'a>,
-}
+pub mod file;
+mod layout;
+mod messages;
+mod recent;
+pub mod recovery;
+mod session;
+mod watcher;
+
+/// The message when closing a modified buffer.
+const NOT_SAVED: &str = "No write since last change (add ! to override)";
 
-#[derive(Default)]
 pub struct Editor {
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
+    theme: Theme,
     buffers: Vec<Buffer>,
-    current_buffer: Option<usize>,
+    windows: HashMap<WindowId, Window>,
+    tab_pages: Vec<TabPage>,
+    /// The index of the current tab page.
+    tab_page: usize,
+    next_window: WindowId,
+    /// The screen area of the windows of the last render.
+    area: Rect,
+    /// The screen areas of the tabs in the tab line.
+    tabs: Vec<TabArea>,
+    /// The number of modified buffers while asking whether to quit anyway.
+    quit_prompt: Option<usize>,
+    messages: Messages,
+    /// Whether the message history is shown, see `:messages`.
+    show_messages: bool,
+    recovery: Recovery,
+    /// The buffers with a recovery file to ask about, the first is asked.
+    recovery_prompts: Vec<usize>,
+    /// When the last key was pressed, recovery files are written when idle.
+    last_input: Instant,
+    /// The modified buffers whose file changed on disk, the first is asked
+    /// whether to reload it.
+    reload_prompts: Vec<usize>,
+    /// When the files were last checked for changes on disk.
+    last_check: Instant,
+    /// Reports changes to the files, which are polled without it.
+    watcher: Option<FileWatcher>,
+    /// When the buffers were last saved after being idle, once per idle time.
+    last_autosave: Instant,
+    sessions: Sessions,
+    /// The session to restore when the editor starts.
+    startup_session: Option<String>,
+    recent: RecentFiles,
+    /// The start screen, shown over the windows.
+    dashboard: Option<Dashboard>,
+    /// The picker of the recent files, shown over the windows.
+    picker: Option<Picker>,
+}
+
+/// A view into a buffer. Windows on the same buffer share its text, each
+/// keeps its own cursor and viewport. The state of the focused window is
+/// loaded into its buffer, the other windows keep theirs here.
+struct Window {
+    buffer: usize,
+    state: WindowState,
+}
+
+/// A tab page, a layout of windows independent of the other tab pages.
+struct TabPage {
+    layout: layout::Layout,
+    /// The focused window.
+    focus: WindowId,
+}
+
+impl TabPage {
+    fn new(window: WindowId) -> Self {
+        Self { layout: layout::Layout::Window(window), focus: window }
+    }
 }
 
 impl Editor {
     pub fn new(files: Vec<PathBuf>) -> Self {
+        Self::with_data_dir(files, &crate::utils::get_data_dir())
+    }
+
+    /// Instantiates an editor that keeps the recovery files, the sessions
+    /// and the recent files in `data_dir`.
+    fn with_data_dir(files: Vec<PathBuf>, data_dir: &Path) -> Self {
         let config = Config::default();
         let mut buffers = Vec::new();
+        let mut messages = Messages::default();
+        let recovery = Recovery::new(data_dir.join("recovery"));
+        let sessions = Sessions::new(data_dir.join("sessions"));
+        let mut recent = RecentFiles::load(data_dir.join("recent.json"));
         if !files.is_empty() {
             if files.iter().any(|f| f.is_dir()) {
                 log::error!("Directories are not supported");
                 std::process::exit(1);
             }
             for file in files {
-                let buffer = Buffer::new(Some(file.clone()), config.keybindings.clone(), None, None).unwrap();
-                buffers.push(buffer);
+                match Buffer::new(Some(file.clone()), config.keybindings.clone(), None, None, config.decoding(None)) {
+                    Ok(buffer) => {
+                        buffer.warn_read_only(&mut messages);
+                        recent.add(&file);
+                        buffers.push(buffer);
+                    },
+                    Err(err) => messages.error(format!("Cannot open {}: {err}", file.display())),
+                }
+            }
+        }
+        let dashboard = buffers.is_empty().then(|| Dashboard::new(recent.files(), &sessions.list(), &cwd()));
+        if buffers.is_empty() {
+            buffers.push(Buffer::new(None, config.keybindings.clone(), None, None, Decoding::default()).unwrap());
+        }
+        let recovery_prompts = (0..buffers.len()).filter(|&i| buffers[i].has_recovery(&recovery)).collect();
+        Self {
+            command_tx: None,
+            config,
+            theme: Theme::default(),
+            buffers,
+            windows: HashMap::from([(0, Window { buffer: 0, state: WindowState::default() })]),
+            tab_pages: vec![TabPage::new(0)],
+            tab_page: 0,
+            next_window: 1,
+            area: Rect::default(),
+            tabs: Vec::new(),
+            quit_prompt: None,
+            messages,
+            show_messages: false,
+            recovery_prompts,
+            recovery,
+            last_input: Instant::now(),
+            reload_prompts: Vec::new(),
+            last_check: Instant::now(),
+            watcher: None,
+            last_autosave: Instant::now(),
+            sessions,
+            startup_session: None,
+            recent,
+            dashboard,
+            picker: None,
+        }
+    }
+
+    /// Restores a session when the editor starts.
+    #[must_use]
+    pub fn with_session(mut self, name: Option<String>) -> Self {
+        self.startup_session = name;
+        self
+    }
+
+    /// Returns the focused window of the current tab page.
+    fn focus(&self) -> WindowId {
+        self.tab_pages[self.tab_page].focus
+    }
+
+    /// Returns the index of the buffer in the focused window.
+    fn buffer_index(&self) -> usize {
+        self.windows[&self.focus()].buffer
+    }
+
+    /// Moves the windows that are not focused along with the edits made to
+    /// their buffer since the last call, so their cursors stay on the same
+    /// text.
+    fn sync_windows(&mut self) {
+        let focus = self.focus();
+        let focused = self.buffer_index();
+        for (index, buffer) in self.buffers.iter_mut().enumerate() {
+            let revision = buffer.state.revision();
+            if buffer.synced.0 == revision {
+                continue;
+            }
+            let (_, old) = mem::replace(&mut buffer.synced, (revision, buffer.state.lines.clone()));
+            let mut windows = self
+                .windows
+                .iter_mut()
+                .filter(|(id, window)| window.buffer == index && **id != focus)
+                .map(|(_, window)| &mut window.state)
+                .peekable();
+            if windows.peek().is_none() && index == focused {
+                continue;
+            }
+            let Some(edit) = TextEdit::between(&old, &buffer.state.lines) else {
+                continue;
+            };
+            for state in windows.chain([&mut buffer.last_window]) {
+                state.shift(&edit, &old, &buffer.state.lines);
+            }
+        }
+    }
+
+    /// Moves the state of the focused window out of its buffer into the
+    /// window, before the focus moves.
+    fn unload_focus(&mut self) {
+        self.sync_windows();
+        let focus = self.focus();
+        let window = self.windows.get_mut(&focus).unwrap();
+        window.state = self.buffers[window.buffer].state.take_window();
+    }
+
+    /// Loads the state of the focused window into its buffer.
+    fn load_focus(&mut self) {
+        let focus = self.focus();
+        let window = self.windows.get_mut(&focus).unwrap();
+        self.buffers[window.buffer].state.load_window(mem::take(&mut window.state));
+    }
+
+    /// Returns a new window on a buffer.
+    fn new_window(&mut self, buffer: usize, state: WindowState) -> WindowId {
+        let id = self.next_window;
+        self.next_window += 1;
+        self.windows.insert(id, Window { buffer, state });
+        id
+    }
+
+    /// Shows a buffer in the focused window, at the cursor where the buffer
+    /// was last left.
+    fn show_buffer(&mut self, index: usize) {
+        let focus = self.focus();
+        let window = self.windows.get_mut(&focus).unwrap();
+        if window.buffer == index {
+            return;
+        }
+        self.sync_windows();
+        let window = self.windows.get_mut(&focus).unwrap();
+        let previous = mem::replace(&mut window.buffer, index);
+        let buffer = &mut self.buffers[previous];
+        buffer.last_window = buffer.state.take_window();
+        let buffer = &mut self.buffers[index];
+        buffer.state.load_window(buffer.last_window.clone());
+        if self.config.autosave.buffer_switch {
+            self.autosave([previous]);
+        }
+    }
+
+    /// Saves the modified buffers among `indices` that have a file and are
+    /// not read-only. Failures are reported as messages.
+    fn autosave(&mut self, indices: impl IntoIterator<Item = usize>) {
+        for index in indices {
+            let buffer = &mut self.buffers[index];
+            if buffer.path.is_none() || buffer.read_only.is_some() || !buffer.modified() {
+                continue;
+            }
+            debug!("Autosaving {}", buffer.display_path());
+            if let Err(err) = buffer.save(&self.config, &self.recovery, false) {
+                self.messages.error(format!("Autosave failed: {err}"));
+            }
+        }
+    }
+
+    /// Saves the modified buffers that have a file and are not read-only.
+    /// Failures are reported as messages and do not stop the other buffers
+    /// from being saved. Returns whether all of them were saved.
+    fn save_modified(&mut self) -> bool {
+        let mut saved = true;
+        for buffer in &mut self.buffers {
+            if buffer.path.is_none() || buffer.read_only.is_some() || !buffer.modified() {
+                continue;
             }
+            if let Err(err) = buffer.save(&self.config, &self.recovery, false) {
+                self.messages.error(err.to_string());
+                saved = false;
+            }
+        }
+        saved
+    }
+
+    /// Moves the focus to another window of the current tab page.
+    fn focus_window(&mut self, id: WindowId) {
+        if id == self.focus() || !self.tab_pages[self.tab_page].layout.windows().contains(&id) {
+            return;
+        }
+        self.unload_focus();
+        self.tab_pages[self.tab_page].focus = id;
+        self.load_focus();
+    }
+
+    /// Splits the focused window and focuses the new window. It shows the
+    /// file at `path`, or the same buffer if `path` is empty.
+    fn split(&mut self, direction: Direction, path: String) -> Result<()> {
+        self.unload_focus();
+        let focused = &self.windows[&self.focus()];
+        let id = self.new_window(focused.buffer, focused.state.clone());
+        let tab_page = &mut self.tab_pages[self.tab_page];
+        tab_page.layout.split(tab_page.focus, id, direction);
+        tab_page.focus = id;
+        self.load_focus();
+        if !path.is_empty() {
+            self.handle_custom(PapierAction::Open(path))?;
+        }
+        Ok(())
+    }
+
+    /// Closes the focused window, the window that takes its space gets the
+    /// focus. The last window of a tab page cannot be closed.
+    fn close_window(&mut self) {
+        let tab_page = &mut self.tab_pages[self.tab_page];
+        let focus = tab_page.focus;
+        let Some(next) = tab_page.layout.close(focus) else {
+            self.messages.error("Cannot close the last window");
+            return;
+        };
+        tab_page.focus = next;
+        self.sync_windows();
+        self.remove_window(focus);
+        self.load_focus();
+    }
+
+    /// Removes a window that is not focused anymore. The buffer keeps the
+    /// window state if the window was loaded into it.
+    fn remove_window(&mut self, id: WindowId) {
+        let window = self.windows.remove(&id).unwrap();
+        let buffer = &mut self.buffers[window.buffer];
+        buffer.last_window = buffer.state.take_window();
+    }
+
+    /// Opens a tab page after the current one, showing the file at `path`,
+    /// or the current buffer if `path` is empty.
+    fn new_tab_page(&mut self, path: String) -> Result<()> {
+        self.unload_focus();
+        let focused = &self.windows[&self.focus()];
+        let id = self.new_window(focused.buffer, focused.state.clone());
+        self.tab_page += 1;
+        self.tab_pages.insert(self.tab_page, TabPage::new(id));
+        self.load_focus();
+        if !path.is_empty() {
+            self.handle_custom(PapierAction::Open(path))?;
+        }
+        Ok(())
+    }
+
+    /// Closes the current tab page and its windows, the next tab page gets
+    /// the focus. The last tab page cannot be closed.
+    fn close_tab_page(&mut self) {
+        if self.tab_pages.len() == 1 {
+            self.messages.error("Cannot close the last tab page");
+            return;
+        }
+        self.sync_windows();
+        let tab_page = self.tab_pages.remove(self.tab_page);
+        self.remove_window(tab_page.focus);
+        for id in tab_page.layout.windows() {
+            self.windows.remove(&id);
+        }
+        self.tab_page = self.tab_page.min(self.tab_pages.len() - 1);
+        self.load_focus();
+    }
+
+    /// Switches to another tab page.
+    fn switch_tab_page(&mut self, index: usize) {
+        if index == self.tab_page || index >= self.tab_pages.len() {
+            return;
+        }
+        self.unload_focus();
+        self.tab_page = index;
+        self.load_focus();
+    }
+
+    /// Returns the tabs of the tab line and the index of the active one.
+    /// The tab line shows the tab pages if there is more than one, else the
+    /// buffers.
+    fn tabs(&self) -> (Vec<Tab>, usize) {
+        let paths = self.buffers.iter().filter_map(|buffer| buffer.path.as_deref()).collect::<Vec<_>>();
+        let mut labels = unique_labels(&paths).into_iter();
+        let names = self
+            .buffers
+            .iter()
+            .map(|buffer| {
+                match (&buffer.name, &buffer.path) {
+                    (_, Some(_)) => labels.next().unwrap_or_default(),
+                    (Some(name), None) => name.clone(),
+                    (None, None) => "Untitled".to_string(),
+                }
+            })
+            .collect::<Vec<_>>();
+        if self.tab_pages.len() == 1 {
+            let tabs = self.buffers.iter().zip(names).map(|(buffer, name)| Tab::new(name, buffer.modified()));
+            return (tabs.collect(), self.buffer_index());
+        }
+        let tabs = self.tab_pages.iter().map(|tab_page| {
+            let windows = tab_page.layout.windows();
+            let name = names[self.windows[&tab_page.focus].buffer].clone();
+            let modified = windows.iter().any(|id| self.buffers[self.windows[id].buffer].modified());
+            match windows.len() {
+                1 => Tab::new(name, modified),
+                n => Tab::new(format!("{name} ({n})"), modified),
+            }
+        });
+        (tabs.collect(), self.tab_page)
+    }
+
+    /// Closes the focused window, tab page or buffer, or quits the app if
+    /// there is nothing else to close. Without `force`, a modified buffer is
+    /// not closed and quitting asks for confirmation if buffers are modified.
+    fn quit(&mut self, force: bool) -> Option<Action> {
+        if self.tab_pages[self.tab_page].layout.windows().len() > 1 {
+            debug!(target: "key_events", "Quitting window");
+            self.close_window();
+        } else if self.tab_pages.len() > 1 {
+            debug!(target: "key_events", "Quitting tab page");
+            self.close_tab_page();
+        } else if !force && self.current_buffer().unwrap().modified() {
+            self.messages.error(NOT_SAVED);
+        } else if self.buffers.len() > 1 {
+            debug!(target: "key_events", "Quitting buffer");
+            self.remove_buffer(self.buffer_index());
         } else {
-            let welcome_buffer = Buffer::new(None, config.keybindings.clone(), None, Some("Welcome".into())).unwrap();
-            buffers.push(welcome_buffer);
-            let test_buffer = Buffer::new(None, config.keybindings.clone(), None, Some("Test".into())).unwrap();
-            buffers.push(test_buffer);
-            let test_buffer = Buffer::new(None, config.keybindings.clone(), None, Some("Test".into())).unwrap();
-            buffers.push(test_buffer);
-            let test_buffer = Buffer::new(None, config.keybindings.clone(), None, Some("Test".into())).unwrap();
-            buffers.push(test_buffer);
-            let test_buffer = Buffer::new(None, config.keybindings.clone(), None, Some("Test".into())).unwrap();
-            buffers.push(test_buffer);
-            let test_buffer = Buffer::new(None, config.keybindings.clone(), None, Some("Test".into())).unwrap();
-            buffers.push(test_buffer);
-            let test_buffer = Buffer::new(None, config.keybindings.clone(), None, Some("Test".into())).unwrap();
-            buffers.push(test_buffer);
-            let test_buffer = Buffer::new(None, config.keybindings.clone(), None, Some("Test".into())).unwrap();
-            buffers.push(test_buffer);
+            return self.quit_all(force);
+        }
+        None
+    }
+
+    /// Quits the app. Without `force`, asks for confirmation first if
+    /// buffers are modified.
+    fn quit_all(&mut self, force: bool) -> Option<Action> {
+        let modified = self.buffers.iter().filter(|buffer| buffer.modified()).count();
+        if force || modified == 0 {
+            debug!(target: "key_events", "Quitting app");
+            return Some(Action::Quit);
+        }
+        self.quit_prompt = Some(modified);
+        None
+    }
+
+    /// Removes a buffer, the windows on it show the previous buffer.
+    fn remove_buffer(&mut self, index: usize) {
+        self.unload_focus();
+        let buffer = self.buffers.remove(index);
+        if let Some(path) = &buffer.path {
+            self.recovery.remove(path);
+        }
+        for prompts in [&mut self.recovery_prompts, &mut self.reload_prompts] {
+            prompts.retain(|&prompt| prompt != index);
+            prompts.iter_mut().filter(|prompt| **prompt > index).for_each(|prompt| *prompt -= 1);
+        }
+        let previous = (index + self.buffers.len() - 1) % self.buffers.len();
+        for window in self.windows.values_mut() {
+            if window.buffer == index {
+                window.buffer = previous;
+                window.state = self.buffers[previous].last_window.clone();
+            } else if window.buffer > index {
+                window.buffer -= 1;
+            }
+        }
+        self.load_focus();
+    }
+
+    /// Themes the editor with a color scheme. The configured `[styles]`
+    /// take precedence over the color scheme.
+    fn set_colorscheme(&mut self, name: &str) -> Result<(), String> {
+        let mut styles = self.config.styles.clone();
+        styles.merge_defaults(&theme::load_colorscheme(&theme::colorscheme_dir(), name)?);
+        self.theme = Theme::new(styles);
+        for buffer in &mut self.buffers {
+            buffer.set_theme(&self.theme);
+        }
+        Ok(())
+    }
+
+    /// Sets an option of the current buffer, e.g. `ff=dos`. Without a value
+    /// the option is shown.
+    fn set_option(&mut self, option: &str) -> Result<(), String> {
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (option.trim(), None),
         };
-        Self { command_tx: None, config, buffers, current_buffer: Some(0) }
+        let buffer = self.current_buffer().unwrap();
+        match (name, value) {
+            ("ff" | "fileformat", Some(value)) => buffer.options.format = value.parse()?,
+            ("ff" | "fileformat", None) => {
+                let message = format!("fileformat={}", buffer.options.format);
+                self.messages.info(message);
+            },
+            ("fenc" | "fileencoding", Some(value)) => buffer.options.encoding = file::encoding_for_label(value)?,
+            ("fenc" | "fileencoding", None) => {
+                let message = format!("fileencoding={}", buffer.options.encoding_name());
+                self.messages.info(message);
+            },
+            _ => return Err(format!("Unknown option: {name}")),
+        }
+        Ok(())
+    }
+
+    /// Opens a buffer with the configured input, highlighter and theme. The
+    /// file is decoded with `encoding`, or a detected encoding if `None`.
+    fn new_buffer(&mut self, path: PathBuf, encoding: Option<&'static Encoding>) -> io::Result<Buffer> {
+        let decoding = self.config.decoding(encoding);
+        let mut buffer = Buffer::new(Some(path.clone()), self.config.keybindings.clone(), None, None, decoding)
+            .map_err(|err| io::Error::new(err.kind(), format!("Cannot open {}: {err}", path.display())))?;
+        buffer.set_highlighter(&self.config.highlighter);
+        buffer.set_theme(&self.theme);
+        buffer.input.register.set_timeout(self.config.timeout());
+        buffer.warn_read_only(&mut self.messages);
+        self.recent.add(&path);
+        Ok(buffer)
+    }
+
+    /// Opens a file in the focused window. An unnamed and unmodified buffer
+    /// in the window, e.g. the one behind the dashboard, is closed.
+    fn open_file(&mut self, path: PathBuf) -> io::Result<()> {
+        let index = self.buffer_index();
+        let current = &self.buffers[index];
+        let scratch = current.path.is_none() && current.name.is_none() && !current.modified();
+        let buffer = self.new_buffer(path, None)?;
+        self.open_buffer(buffer);
+        if scratch {
+            self.remove_buffer(index);
+        }
+        Ok(())
+    }
+
+    /// Returns a dashboard of the recent files and sessions.
+    fn new_dashboard(&self) -> Dashboard {
+        Dashboard::new(self.recent.files(), &self.sessions.list(), &cwd())
+    }
+
+    /// Opens the fuzzy picker of the recent files.
+    fn open_recent_picker(&mut self) {
+        let files = self.recent.files().iter().map(|path| path.display().to_string()).collect();
+        self.picker = Some(Picker::new("Recent files", files));
+    }
+
+    /// Opens or does the item picked on the dashboard. The dashboard is
+    /// closed unless the item fails or opens the picker.
+    fn pick_dashboard(&mut self, item: DashboardItem) -> io::Result<()> {
+        match item {
+            DashboardItem::File(path) => self.open_file(path)?,
+            DashboardItem::Session(name) => self.load_session(&name)?,
+            DashboardItem::NewFile => {},
+            DashboardItem::Explorer => self.current_buffer().unwrap().explorer_state.open = true,
+            DashboardItem::RecentFiles => {
+                self.open_recent_picker();
+                return Ok(());
+            },
+            DashboardItem::EditConfig => self.open_file(crate::utils::get_config_dir().join("config.toml"))?,
+        }
+        self.dashboard = None;
+        Ok(())
+    }
+
+    /// Answers the picker of the recent files, opening the picked file.
+    fn answer_picker(&mut self, key: KeyEvent) -> io::Result<()> {
+        let Some(picker) = &mut self.picker else {
+            return Ok(());
+        };
+        match picker.handle_key(key) {
+            Some(PickerEvent::Pick(index)) => {
+                self.picker = None;
+                let path = self.recent.files()[index].clone();
+                self.open_file(path)?;
+                self.dashboard = None;
+            },
+            Some(PickerEvent::Cancel) => self.picker = None,
+            None => {},
+        }
+        Ok(())
+    }
+
+    /// Adds a buffer and shows it in the focused window. If the file has a
+    /// recovery file, asks whether to recover it.
+    fn open_buffer(&mut self, buffer: Buffer) {
+        if buffer.has_recovery(&self.recovery) {
+            self.recovery_prompts.push(self.buffers.len());
+        }
+        self.buffers.push(buffer);
+        self.show_buffer(self.buffers.len() - 1);
+    }
+
+    /// Answers the recovery prompt of the first buffer in
+    /// `recovery_prompts`: (r)ecover the changes, (d)iscard the recovery
+    /// file, or show the di(f)f from the file to the recovery file in a
+    /// split. Escape keeps the recovery file for later.
+    fn answer_recovery(&mut self, key: KeyCode) -> Result<()> {
+        let index = self.recovery_prompts[0];
+        let path = self.buffers[index].path.clone().unwrap();
+        match key {
+            KeyCode::Char('r') => {
+                self.show_buffer(index);
+                self.recover()?;
+            },
+            KeyCode::Char('d') => {
+                self.recovery.remove(&path);
+                let message = format!("Discarded the recovery file of {}", self.buffers[index].display_path());
+                self.messages.info(message);
+            },
+            KeyCode::Char('f') => {
+                let text =
+                    recovery::line_diff(&self.buffers[index].state.lines.to_string(), &self.recovery.read(&path)?);
+                let name = format!("{} (recovery diff)", self.buffers[index].name.as_deref().unwrap_or_default());
+                let mut buffer =
+                    Buffer::new(None, self.config.keybindings.clone(), None, Some(name), Decoding::default())?;
+                buffer.state = EditorState::new(Lines::from(text.as_str()), "diff");
+                buffer.saved_revision = buffer.state.revision();
+                buffer.set_highlighter(&self.config.highlighter);
+                buffer.set_theme(&self.theme);
+                self.show_buffer(index);
+                self.split(Direction::Horizontal, String::new())?;
+                self.buffers.push(buffer);
+                self.show_buffer(self.buffers.len() - 1);
+                self.messages.info("Use :recover in the file to recover the changes");
+            },
+            KeyCode::Esc => {},
+            _ => return Ok(()),
+        }
+        self.recovery_prompts.remove(0);
+        Ok(())
+    }
+
+    /// Replaces the text of the current buffer with its recovery file. The
+    /// buffer stays modified until it is saved.
+    fn recover(&mut self) -> io::Result<()> {
+        let index = self.buffer_index();
+        let buffer = &mut self.buffers[index];
+        let Some(path) = buffer.path.clone().filter(|path| buffer.has_recovery(&self.recovery)) else {
+            let message = format!("No recovery file for {}", buffer.display_path());
+            return Err(io::Error::new(io::ErrorKind::NotFound, message));
+        };
+        let text = self.recovery.read(&path)?;
+        ReplaceText(text).execute(&mut buffer.state);
+        buffer.recovery_revision = None;
+        let message = format!("Recovered {}, write it to keep the changes", buffer.display_path());
+        self.messages.info(message);
+        Ok(())
+    }
+
+    /// Writes the recovery files of the modified buffers once no key was
+    /// pressed for `updatetime`, and removes those of unmodified buffers.
+    /// The modified buffers are kept for the panic hook.
+    fn update_recovery(&mut self) {
+        let idle = self.last_input.elapsed() >= self.config.updatetime();
+        let mut snapshot = Vec::new();
+        for buffer in &mut self.buffers {
+            let Some(path) = &buffer.path else {
+                continue;
+            };
+            let revision = buffer.state.revision();
+            let modified = buffer.modified();
+            if modified {
+                snapshot.push((path.clone(), buffer.state.lines.clone()));
+            }
+            match buffer.recovery_revision {
+                Some(_) if !modified => {
+                    self.recovery.remove(path);
+                    buffer.recovery_revision = None;
+                },
+                Some(written) if written == revision => {},
+                _ if modified && idle => {
+                    if let Err(err) = self.recovery.write(path, &buffer.state.lines) {
+                        self.messages.error(format!("Cannot write the recovery file: {err}"));
+                    }
+                    buffer.recovery_revision = Some(revision);
+                },
+                _ => {},
+            }
+        }
+        self.recovery.snapshot(snapshot);
+    }
+
+    /// Checks whether the files of the buffers changed on disk. Unmodified
+    /// buffers are reloaded, for modified buffers the user is asked.
+    fn check_files(&mut self) {
+        self.last_check = Instant::now();
+        for (index, buffer) in self.buffers.iter_mut().enumerate() {
+            let (Some(path), Some(stamp)) = (&buffer.path, buffer.stamp.as_mut()) else {
+                continue;
+            };
+            match stamp.changed(path) {
+                Ok(None) => {},
+                Ok(Some(_)) if buffer.modified() => {
+                    if !self.reload_prompts.contains(&index) {
+                        self.reload_prompts.push(index);
+                    }
+                },
+                Ok(Some(_)) => {
+                    match buffer.reload() {
+                        Ok(()) => self.messages.info(format!("{} changed on disk, reloaded", buffer.display_path())),
+                        Err(err) => self.messages.error(format!("Cannot reload {}: {err}", buffer.display_path())),
+                    }
+                },
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    self.messages.warn(format!("{} was deleted", buffer.display_path()));
+                    buffer.stamp = None;
+                },
+                Err(err) => debug!("Cannot check {}: {err}", path.display()),
+            }
+        }
+    }
+
+    /// Answers the reload prompt of the first buffer in `reload_prompts`:
+    /// (r)eload the file, which can be undone, or (k)eep the changes.
+    fn answer_reload(&mut self, key: KeyCode) -> io::Result<()> {
+        let buffer = &mut self.buffers[self.reload_prompts[0]];
+        match key {
+            KeyCode::Char('r') => buffer.reload()?,
+            KeyCode::Char('k') | KeyCode::Esc => {
+                if let (Some(path), Some(stamp)) = (&buffer.path, &mut buffer.stamp) {
+                    if let Ok(Some(changed)) = stamp.changed(path) {
+                        *stamp = changed;
+                    }
+                }
+            },
+            _ => return Ok(()),
+        }
+        self.reload_prompts.remove(0);
+        Ok(())
+    }
+
+    /// Edits a file, e.g. `++enc=latin1 notes.txt`. Without a file the
+    /// current file is read again, which fails if it is modified.
+    fn edit(&mut self, args: &str) -> io::Result<()> {
+        let mut encoding = None;
+        let mut path = None;
+        for arg in args.split_whitespace() {
+            match arg.strip_prefix("++enc=") {
+                Some(label) => {
+                    let label = file::encoding_for_label(label).map_err(io::Error::other)?;
+                    encoding = Some(label);
+                },
+                None => path = Some(PathBuf::from(arg)),
+            }
+        }
+
+        let index = self.buffer_index();
+        let current = self.buffers[index].path.clone();
+        match path {
+            Some(path) if Some(&path) != current.as_ref() => {
+                let buffer = self.new_buffer(path, encoding)?;
+                self.open_buffer(buffer);
+            },
+            _ => {
+                let Some(path) = current else {
+                    return Err(io::Error::new(io::ErrorKind::NotFound, "No file name"));
+                };
+                if self.buffers[index].modified() {
+                    return Err(io::Error::other(NOT_SAVED));
+                }
+                let buffer = self.new_buffer(path, encoding)?;
+                self.unload_focus();
+                let previous = mem::replace(&mut self.buffers[index], buffer);
+                self.buffers[index].last_window = previous.last_window;
+                self.load_focus();
+            },
+        }
+        Ok(())
+    }
+
+    /// Returns the open buffers with a file, the windows and the tab pages
+    /// as a session. Windows on buffers without a file are kept empty.
+    fn session(&mut self) -> Session {
+        self.unload_focus();
+        let focused = self.buffer_index();
+        let mut indices = Vec::new();
+        let mut buffers = Vec::new();
+        for (index, buffer) in self.buffers.iter().enumerate() {
+            let Some(path) = &buffer.path else {
+                indices.push(None);
+                continue;
+            };
+            let window = if index == focused { &self.windows[&self.focus()].state } else { &buffer.last_window };
+            indices.push(Some(buffers.len()));
+            buffers.push(SessionBuffer {
+                path: std::path::absolute(path).unwrap_or_else(|_| path.clone()),
+                view: window.into(),
+                explorer: buffer.explorer_state.open,
+                explorer_dir: buffer.explorer.cwd().clone(),
+            });
+        }
+        let windows = self
+            .windows
+            .iter()
+            .map(|(&id, window)| (id, SessionWindow { buffer: indices[window.buffer], view: (&window.state).into() }))
+            .collect();
+        let tab_pages = self
+            .tab_pages
+            .iter()
+            .map(|tab_page| SessionTabPage { layout: tab_page.layout.clone(), focus: tab_page.focus })
+            .collect();
+        self.load_focus();
+        Session { buffers, windows, tab_pages, tab_page: self.tab_page }
+    }
+
+    /// Replaces the buffers and windows with those of a session. Files that
+    /// cannot be opened are reported, their windows show the first buffer.
+    fn restore_session(&mut self, session: Session) -> io::Result<()> {
+        if self.buffers.iter().any(|buffer| buffer.modified()) {
+            return Err(io::Error::other(NOT_SAVED));
+        }
+        let mut buffers = Vec::new();
+        let mut indices = Vec::new();
+        for saved in session.buffers {
+            match self.new_buffer(saved.path, None) {
+                Ok(mut buffer) => {
+                    buffer.last_window = saved.view.into();
+                    buffer.explorer_state.open = saved.explorer;
+                    if let Err(err) = buffer.explorer.set_cwd(&saved.explorer_dir) {
+                        debug!("Cannot show {} in the explorer: {err}", saved.explorer_dir.display());
+                    }
+                    indices.push(Some(buffers.len()));
+                    buffers.push(buffer);
+                },
+                Err(err) => {
+                    self.messages.error(err.to_string());
+                    indices.push(None);
+                },
+            }
+        }
+        if buffers.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No file of the session could be opened"));
+        }
+        self.recovery_prompts = (0..buffers.len()).filter(|&i| buffers[i].has_recovery(&self.recovery)).collect();
+        self.reload_prompts.clear();
+        self.buffers = buffers;
+        self.windows = session
+            .windows
+            .into_iter()
+            .map(|(id, window)| {
+                let buffer = window.buffer.and_then(|index| indices[index]).unwrap_or_default();
+                (id, Window { buffer, state: window.view.into() })
+            })
+            .collect();
+        self.next_window = self.windows.keys().max().map_or(0, |id| id + 1);
+        self.tab_pages = session
+            .tab_pages
+            .into_iter()
+            .map(|tab_page| TabPage { layout: tab_page.layout, focus: tab_page.focus })
+            .collect();
+        self.tab_page = session.tab_page;
+        self.load_focus();
+        self.dashboard = None;
+        Ok(())
+    }
+
+    /// Saves the session, of the working directory if `name` is empty.
+    fn save_session(&mut self, name: &str) -> io::Result<()> {
+        let name = session_name(name)?;
+        self.sync_windows();
+        let session = self.session();
+        if session.buffers.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No file to save in the session"));
+        }
+        self.sessions.save(&name, &session)?;
+        self.messages.info(format!("Session {name} saved"));
+        Ok(())
+    }
+
+    /// Loads the session, of the working directory if `name` is empty.
+    fn load_session(&mut self, name: &str) -> io::Result<()> {
+        let name = session_name(name)?;
+        self.sync_windows();
+        let session = self.sessions.load(&name)?;
+        self.restore_session(session)?;
+        self.messages.info(format!("Session {name} loaded"));
+        Ok(())
+    }
+
+    /// Runs `:session save [name]` or `:session load [name]`.
+    fn session_command(&mut self, args: &str) -> io::Result<()> {
+        match args.split_whitespace().collect::<Vec<_>>()[..] {
+            ["save"] => self.save_session(""),
+            ["save", name] => self.save_session(name),
+            ["load"] => self.load_session(""),
+            ["load", name] => self.load_session(name),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Usage: session save|load [name]")),
+        }
+    }
+
+    /// Runs a custom action. Errors are shown as messages rather than
+    /// stopping the app.
+    fn run_custom(&mut self, action: PapierAction) -> Option<Action> {
+        self.handle_custom(action).unwrap_or_else(|err| {
+            self.messages.error(err.to_string());
+            None
+        })
+    }
+
+    fn handle_custom(&mut self, action: PapierAction) -> Result<Option<Action>> {
+        match action {
+            PapierAction::Quit => return Ok(self.quit(false)),
+            PapierAction::ForceQuit => return Ok(self.quit(true)),
+            PapierAction::WriteQuit => {
+                let index = self.buffer_index();
+                self.buffers[index].save(&self.config, &self.recovery, false)?;
+                return Ok(self.quit(false));
+            },
+            PapierAction::Exit => {
+                let index = self.buffer_index();
+                let buffer = &mut self.buffers[index];
+                if buffer.modified() {
+                    buffer.save(&self.config, &self.recovery, false)?;
+                }
+                return Ok(self.quit(false));
+            },
+            PapierAction::Save | PapierAction::ForceSave => {
+                debug!(target: "key_events", "Saving buffer");
+                let index = self.buffer_index();
+                let buffer = &mut self.buffers[index];
+                buffer.save(&self.config, &self.recovery, matches!(action, PapierAction::ForceSave))?;
+                let message = format!("{} written", buffer.display_path());
+                self.messages.info(message);
+            },
+            PapierAction::SaveAll => {
+                debug!(target: "key_events", "Saving all buffers ({} buffers)", self.buffers.len());
+                if self.save_modified() {
+                    self.messages.info("All files written");
+                }
+            },
+            PapierAction::SaveAs(i) => {
+                let path = PathBuf::from(i);
+                debug!(target: "key_events", "Saving buffer as: {:?}", path);
+                let index = self.buffer_index();
+                let buffer = &mut self.buffers[index];
+                buffer.save_as(path, &self.config, &self.recovery)?;
+                let message = format!("{} written", buffer.display_path());
+                self.messages.info(message);
+            },
+            PapierAction::Messages => self.show_messages = true,
+            PapierAction::Edit(args) => self.edit(&args)?,
+            PapierAction::Recover => self.recover()?,
+            PapierAction::CheckTime => self.check_files(),
+            PapierAction::MkSession(name) => self.save_session(&name)?,
+            PapierAction::Session(args) => self.session_command(&args)?,
+            PapierAction::RecentFiles => self.open_recent_picker(),
+            PapierAction::Dashboard => self.dashboard = Some(self.new_dashboard()),
+            PapierAction::Set(option) => {
+                if let Err(err) = self.set_option(&option) {
+                    self.messages.error(err);
+                }
+            },
+            PapierAction::NextBuffer => {
+                let index = self.buffer_index();
+                self.show_buffer((index + 1) % self.buffers.len());
+            },
+            PapierAction::PreviousBuffer => {
+                let index = self.buffer_index();
+                self.show_buffer((index + self.buffers.len() - 1) % self.buffers.len());
+            },
+            PapierAction::Open(i) => {
+                let path = PathBuf::from(i);
+                debug!(target: "key_events", "Opening file: {:?}", path);
+                self.open_file(path)?;
+            },
+            PapierAction::ColorScheme(name) => {
+                debug!(target: "key_events", "Changing color scheme: {name}");
+                if let Err(err) = self.set_colorscheme(&name) {
+                    self.messages.error(err);
+                }
+            },
+            PapierAction::QuitAll => return Ok(self.quit_all(false)),
+            PapierAction::ForceQuitAll => return Ok(self.quit_all(true)),
+            PapierAction::WriteQuitAll => {
+                if self.save_modified() {
+                    return Ok(self.quit_all(false));
+                }
+            },
+            PapierAction::ToggleExplorer => {
+                let explorer_state = &mut self.current_buffer().unwrap().explorer_state;
+                debug!(target: "key_events", "Toggling explorer: {}", explorer_state.open);
+                explorer_state.open = !explorer_state.open;
+            },
+            PapierAction::ToggleLogger => {
+                let logger = &mut self.current_buffer().unwrap().logger;
+                logger.open = !logger.open;
+            },
+            PapierAction::Split(path) => self.split(Direction::Vertical, path)?,
+            PapierAction::VSplit(path) => self.split(Direction::Horizontal, path)?,
+            PapierAction::FocusWindow(direction) => {
+                let tab_page = &self.tab_pages[self.tab_page];
+                if let Some(id) = tab_page.layout.neighbor(self.area, tab_page.focus, direction) {
+                    self.focus_window(id);
+                }
+            },
+            PapierAction::ResizeHeight(delta) => {
+                let tab_page = &mut self.tab_pages[self.tab_page];
+                tab_page.layout.resize(tab_page.focus, Direction::Vertical, delta);
+            },
+            PapierAction::ResizeWidth(delta) => {
+                let tab_page = &mut self.tab_pages[self.tab_page];
+                tab_page.layout.resize(tab_page.focus, Direction::Horizontal, delta);
+            },
+            PapierAction::CloseWindow => self.close_window(),
+            PapierAction::OnlyWindow => {
+                let tab_page = &mut self.tab_pages[self.tab_page];
+                for id in tab_page.layout.windows() {
+                    if id != tab_page.focus {
+                        self.windows.remove(&id);
+                    }
+                }
+                tab_page.layout = layout::Layout::Window(tab_page.focus);
+            },
+            PapierAction::TabNew(path) => self.new_tab_page(path)?,
+            PapierAction::TabClose => self.close_tab_page(),
+            PapierAction::NextTabPage => self.switch_tab_page((self.tab_page + 1) % self.tab_pages.len()),
+            PapierAction::PreviousTabPage => {
+                self.switch_tab_page((self.tab_page + self.tab_pages.len() - 1) % self.tab_pages.len());
+            },
+        }
+        Ok(None)
     }
 
     pub fn current_buffer(&mut self) -> Option<&mut Buffer> {
-        self.current_buffer.as_ref().and_then(|b| self.buffers.get_mut(*b))
+        let index = self.buffer_index();
+        self.buffers.get_mut(index)
     }
 }
 
 pub struct Buffer {
     name: Option<String>,
     path: Option<PathBuf>,
-    modified: bool,
+    /// The revision of the text when it was last saved.
+    saved_revision: usize,
+    options: FileOptions,
+    saved_options: FileOptions,
+    /// Why the buffer cannot be written, e.g. `binary`.
+    read_only: Option<String>,
+    /// The revision written to the recovery file, if one was written.
+    recovery_revision: Option<usize>,
+    /// The file on disk when it was last read or written.
+    stamp: Option<FileStamp>,
     state: EditorState,
+    /// The window state of the last window that left the buffer.
+    last_window: WindowState,
+    /// The revision and the text the windows of the buffer were last moved
+    /// along with, see [`Editor::sync_windows`].
+    synced: (usize, Lines),
     input: Input<PapierAction>,
-    message: Option<String>,
     explorer: FileExplorer,
     explorer_state: FileExplorerState,
     logger: LoggerState,
@@ -107,6 +1094,18 @@ impl Buffer {
             Command::new("save".to_string(), "Save the current file".to_string(), vec!["w".to_string()], |_| {
                 PapierAction::Save
             }),
+            Command::new(
+                "save!".to_string(),
+                "Save the current file, even if it changed on disk".to_string(),
+                vec!["w!".to_string()],
+                |_| PapierAction::ForceSave,
+            ),
+            Command::new(
+                "checktime".to_string(),
+                "Check whether the files changed on disk".to_string(),
+                vec!["checkt".to_string()],
+                |_| PapierAction::CheckTime,
+            ),
             Command::new("save_all".to_string(), "Save all open files".to_string(), vec!["wa".to_string()], |_| {
                 PapierAction::SaveAll
             }),
@@ -129,10 +1128,123 @@ impl Buffer {
                 |_| PapierAction::PreviousBuffer,
             ),
             Command::new("open".to_string(), "Open a file".to_string(), vec!["o".to_string()], PapierAction::Open),
+            Command::new(
+                "recover".to_string(),
+                "Recover the changes of the recovery file".to_string(),
+                vec!["rec".to_string()],
+                |_| PapierAction::Recover,
+            ),
+            Command::new("oldfiles".to_string(), "Find a recent file".to_string(), vec!["ol".to_string()], |_| {
+                PapierAction::RecentFiles
+            }),
+            Command::new(
+                "dashboard".to_string(),
+                "Show the start screen".to_string(),
+                vec!["dash".to_string()],
+                |_| PapierAction::Dashboard,
+            ),
+            Command::new(
+                "mksession".to_string(),
+                "Save the session, of the working directory if no name is given".to_string(),
+                vec!["mks".to_string()],
+                PapierAction::MkSession,
+            ),
+            Command::new(
+                "session".to_string(),
+                "Save or load a session, e.g. save work or load work".to_string(),
+                vec!["ses".to_string()],
+                PapierAction::Session,
+            ),
+            Command::new(
+                "edit".to_string(),
+                "Edit a file, e.g. ++enc=latin1 to read it with an encoding".to_string(),
+                vec!["e".to_string()],
+                PapierAction::Edit,
+            ),
             Command::new("quit_all".to_string(), "Quit the app".to_string(), vec!["qa".to_string()], |_| {
                 PapierAction::QuitAll
             }),
+            Command::new("quit!".to_string(), "Quit, discarding changes".to_string(), vec!["q!".to_string()], |_| {
+                PapierAction::ForceQuit
+            }),
+            Command::new(
+                "quit_all!".to_string(),
+                "Quit the app, discarding all changes".to_string(),
+                vec!["qa!".to_string()],
+                |_| PapierAction::ForceQuitAll,
+            ),
+            Command::new("write_quit".to_string(), "Save and quit".to_string(), vec!["wq".to_string()], |_| {
+                PapierAction::WriteQuit
+            }),
+            Command::new("exit".to_string(), "Save if modified and quit".to_string(), vec!["x".to_string()], |_| {
+                PapierAction::Exit
+            }),
+            Command::new(
+                "write_quit_all".to_string(),
+                "Save all open files and quit the app".to_string(),
+                vec!["wqa".to_string()],
+                |_| PapierAction::WriteQuitAll,
+            ),
+            Command::new(
+                "split".to_string(),
+                "Split the window horizontally".to_string(),
+                vec!["sp".to_string()],
+                PapierAction::Split,
+            ),
+            Command::new(
+                "vsplit".to_string(),
+                "Split the window vertically".to_string(),
+                vec!["vs".to_string()],
+                PapierAction::VSplit,
+            ),
+            Command::new("close".to_string(), "Close the window".to_string(), vec!["clo".to_string()], |_| {
+                PapierAction::CloseWindow
+            }),
+            Command::new("only".to_string(), "Close all other windows".to_string(), vec!["on".to_string()], |_| {
+                PapierAction::OnlyWindow
+            }),
+            Command::new(
+                "tabnew".to_string(),
+                "Open a tab page".to_string(),
+                vec!["tabe".to_string()],
+                PapierAction::TabNew,
+            ),
+            Command::new("tabclose".to_string(), "Close the tab page".to_string(), vec!["tabc".to_string()], |_| {
+                PapierAction::TabClose
+            }),
+            Command::new(
+                "tabnext".to_string(),
+                "Switch to the next tab page".to_string(),
+                vec!["tabn".to_string()],
+                |_| PapierAction::NextTabPage,
+            ),
+            Command::new(
+                "tabprevious".to_string(),
+                "Switch to the previous tab page".to_string(),
+                vec!["tabp".to_string()],
+                |_| PapierAction::PreviousTabPage,
+            ),
+            Command::new(
+                "set".to_string(),
+                "Set an option, e.g. ff=dos or fenc=latin1".to_string(),
+                vec!["se".to_string()],
+                PapierAction::Set,
+            ),
+            Command::new(
+                "messages".to_string(),
+                "Show the message history".to_string(),
+                vec!["mes".to_string()],
+                |_| PapierAction::Messages,
+            ),
         ]);
+        let mut colorscheme = Command::new(
+            "colorscheme".to_string(),
+            "Change the color scheme".to_string(),
+            vec!["colo".to_string()],
+            PapierAction::ColorScheme,
+        );
+        colorscheme.arguments(theme::colorscheme_names(&theme::colorscheme_dir()));
+        input.command.available_commands.push(colorscheme);
     }
 
     fn new(
@@ -140,54 +1252,44 @@ impl Buffer {
         keybindings: KeyBindings,
         tx: Option<UnboundedSender<Action>>,
         name: Option<String>,
+        decoding: Decoding,
     ) -> io::Result<Self> {
+        let mut options = FileOptions::default();
+        let mut read_only = None;
+        let mut stamp = None;
         let state = match path {
             Some(ref path) => {
                 let lines = if !path.exists() {
                     log::debug!("File does not exist: {}", path.to_string_lossy());
                     "".to_string()
                 } else {
-                    let file = fs::File::open(path)?;
-                    let reader = io::BufReader::new(file);
-                    let lines = reader.lines();
-                    lines.map_while(Result::ok).collect::<Vec<String>>().join("\n")
+                    let (decoded, file_stamp) = file::read(path, decoding)?;
+                    stamp = Some(file_stamp);
+                    options = decoded.options;
+                    read_only = decoded.read_only;
+                    decoded.text
                 };
                 EditorState::new(
                     Lines::from(lines.as_str()),
-                    path.to_string_lossy().split('.').last().unwrap_or_default(),
-                )
-            },
-            None => {
-                EditorState::new(
-                    Lines::from(
-                        "papier is a light-weight vim inspired TUI editor using the RataTUI ecosystem.
-
-Navigate right (l), left (h), up (k) and down (j), using vim motions.
-    
-Traverse words forward (w) and backward (b).
-        
-Select text (v), including selection between \"brackets\" (ciw).
-        
-Copy and paste text: 
-        
-Built-in search using the '/' command.
-        
-This editor is under active development.
-Don't hesitate to open issues or submit pull requests to contribute!
-",
-                    ),
-                    "txt",
+                    path.to_string_lossy().split('.').next_back().unwrap_or_default(),
                 )
             },
+            None => EditorState::new(Lines::from(""), "txt"),
         };
         let mut input: Input<_> = keybindings.into();
         Self::init_commands(&mut input);
         Ok(Self {
             path: path.clone(),
-            modified: false,
+            saved_revision: state.revision(),
+            options,
+            saved_options: options,
+            read_only,
+            recovery_revision: None,
+            stamp,
+            synced: (state.revision(), state.lines.clone()),
             state,
+            last_window: WindowState::default(),
             input,
-            message: None,
             name: name.or_else(|| path.map(|p| p.file_name().unwrap().to_string_lossy().to_string())),
             explorer: FileExplorer::with_theme(FileTheme::default().add_default_title())?,
             explorer_state: FileExplorerState { open: false },
@@ -195,33 +1297,89 @@ Don't hesitate to open issues or submit pull requests to contribute!
         })
     }
 
-    fn save(&mut self) -> io::Result<()> {
-        if let Some(path) = &self.path {
-            let mut f = io::BufWriter::new(fs::File::create(path)?);
-            for (maybe_char, Index2 { col, row }) in self.state.lines.iter() {
-                if let Some(c) = maybe_char {
-                    write!(f, "{}", c)?;
-                }
-                if row < self.state.lines.len() - 1 {
-                    writeln!(f)?;
-                }
-            }
-            self.modified = false;
-        }
+    /// Themes the widgets of the buffer.
+    fn set_theme(&mut self, theme: &Theme) {
+        self.explorer.set_theme(theme.explorer());
+    }
+
+    /// Sets the syntax highlighter configured for the file type of the buffer.
+    fn set_highlighter(&mut self, highlighters: &Highlighters) {
+        let ext = self.path.as_ref().and_then(|p| p.extension()).map_or("txt".into(), |ext| ext.to_string_lossy());
+        highlighters.apply(&mut self.state, &ext);
+    }
+
+    /// Whether the text or the file options changed since the buffer was
+    /// last saved.
+    fn modified(&self) -> bool {
+        self.state.revision() != self.saved_revision || self.options != self.saved_options
+    }
+
+    /// Reads the file of the buffer again, in its encoding. The new text
+    /// replaces the old one with an edit that can be undone.
+    fn reload(&mut self) -> io::Result<()> {
+        let path = self.path.clone().unwrap();
+        let decoding = Decoding { encoding: Some(self.options.encoding), ..Decoding::default() };
+        let (decoded, stamp) = file::read(&path, decoding)?;
+        ReplaceText(decoded.text).execute(&mut self.state);
+        self.options = decoded.options;
+        self.saved_options = decoded.options;
+        self.saved_revision = self.state.revision();
+        self.read_only = decoded.read_only;
+        self.stamp = Some(stamp);
         Ok(())
     }
 
-    fn save_as(&mut self, path: PathBuf) -> io::Result<()> {
-        let mut f = io::BufWriter::new(fs::File::create(&path)?);
-        for (maybe_char, Index2 { col, row }) in self.state.lines.iter() {
-            if let Some(c) = maybe_char {
-                write!(f, "{}", c)?;
-            }
-            if row < self.state.lines.len() - 1 {
-                writeln!(f)?;
+    /// Whether the file of the buffer has a recovery file newer than it.
+    fn has_recovery(&self, recovery: &Recovery) -> bool {
+        self.path.as_ref().is_some_and(|path| recovery.exists(path))
+    }
+
+    /// Warns that the buffer is read-only, if it is.
+    fn warn_read_only(&self, messages: &mut Messages) {
+        if let Some(reason) = &self.read_only {
+            messages.warn(format!("{} is {reason}, opened read-only", self.display_path()));
+        }
+    }
+
+    /// Returns the path of the buffer for messages.
+    fn display_path(&self) -> String {
+        self.path.as_ref().map_or_else(|| "[No Name]".to_string(), |path| format!("\"{}\"", path.display()))
+    }
+
+    /// Writes the buffer to its file. Without `force`, fails if the file
+    /// changed on disk since it was read.
+    fn save(&mut self, config: &Config, recovery: &Recovery, force: bool) -> io::Result<()> {
+        let Some(path) = self.path.clone() else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No file name"));
+        };
+        if let (false, Some(stamp)) = (force, self.stamp.as_mut()) {
+            if let Ok(Some(_)) = stamp.changed(&path) {
+                let message = format!("{} changed on disk since it was read (add ! to override)", self.display_path());
+                return Err(io::Error::other(message));
             }
         }
-        self.modified = false;
+        self.save_as(path, config, recovery)
+    }
+
+    /// Writes the buffer atomically to `path`, with the line endings of the
+    /// file it was read from. The recovery file is removed.
+    fn save_as(&mut self, path: PathBuf, config: &Config, recovery: &Recovery) -> io::Result<()> {
+        if let Some(reason) = &self.read_only {
+            let message = format!("{} is read-only ({reason})", self.display_path());
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, message));
+        }
+        let mut options = self.options;
+        options.eol |= config.fixeol() && !self.state.lines.is_empty();
+        let stamp = file::write(&path, config.backup, &self.state.lines, options)
+            .map_err(|err| io::Error::new(err.kind(), format!("Cannot write {}: {err}", path.display())))?;
+        self.stamp = Some(stamp);
+        if let Some(previous) = &self.path {
+            recovery.remove(previous);
+        }
+        self.recovery_revision = None;
+        self.options = options;
+        self.saved_options = options;
+        self.saved_revision = self.state.revision();
         self.path = Some(path);
         Ok(())
     }
@@ -229,6 +1387,7 @@ Don't hesitate to open issues or submit pull requests to contribute!
 
 impl Component for Editor {
     fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        self.watcher = Some(FileWatcher::new(tx.clone()));
         self.command_tx = Some(tx);
         Ok(())
     }
@@ -238,13 +1397,78 @@ impl Component for Editor {
         self.buffers.iter_mut().for_each(|b| {
             let mut input: Input<_> = self.config.keybindings.clone().into();
             input.command.available_commands.clone_from(&b.input.command.available_commands);
+            input.register.set_timeout(self.config.timeout());
             b.input = input;
+            b.set_highlighter(&self.config.highlighter);
         });
+        let colorscheme = self.config.theme.clone().unwrap_or_else(|| "default".to_string());
+        if let Err(err) = self.set_colorscheme(&colorscheme) {
+            self.messages.error(err);
+            self.set_colorscheme("default").ok();
+        }
+        Ok(())
+    }
+
+    fn init(&mut self, area: Rect) -> Result<()> {
+        let restore = self.config.restore_session && self.buffers.iter().all(|buffer| buffer.path.is_none());
+        let name = self.startup_session.take().or_else(|| {
+            let name = restore.then(|| session_name("").ok()).flatten();
+            name.filter(|name| self.sessions.exists(name))
+        });
+        if let Some(name) = name {
+            if let Err(err) = self.load_session(&name) {
+                self.messages.error(err.to_string());
+            }
+        }
         Ok(())
     }
 
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Option<Action>> {
         trace!(target: "key_events", "Handling key event: {:?}", key);
+        if self.quit_prompt.take().is_some() {
+            return Ok((key.code == KeyCode::Char('y')).then_some(Action::Quit));
+        }
+        if mem::take(&mut self.show_messages) {
+            return Ok(None);
+        }
+        self.last_input = Instant::now();
+        if !self.recovery_prompts.is_empty() {
+            if let Err(err) = self.answer_recovery(key.code) {
+                self.messages.error(err.to_string());
+            }
+            return Ok(None);
+        }
+        if !self.reload_prompts.is_empty() {
+            if let Err(err) = self.answer_reload(key.code) {
+                self.messages.error(err.to_string());
+            }
+            return Ok(None);
+        }
+        if self.picker.is_some() {
+            if let Err(err) = self.answer_picker(key) {
+                self.messages.error(err.to_string());
+            }
+            return Ok(None);
+        }
+        if let Some(dashboard) = &mut self.dashboard {
+            match key.code {
+                // The command line opens over the empty buffer
+                KeyCode::Char(':') => self.dashboard = None,
+                KeyCode::Esc => {
+                    self.dashboard = None;
+                    return Ok(None);
+                },
+                KeyCode::Char('q') => return Ok(self.quit_all(false)),
+                _ => {
+                    if let Some(item) = dashboard.handle_key(key) {
+                        if let Err(err) = self.pick_dashboard(item) {
+                            self.messages.error(err.to_string());
+                        }
+                    }
+                    return Ok(None);
+                },
+            }
+        }
         let current_buffer = self.current_buffer().unwrap();
         let input = &mut current_buffer.input;
         let state = &mut current_buffer.state;
@@ -277,95 +1501,148 @@ impl Component for Editor {
                 explorer_state.open = false;
                 return Ok(None);
             }
-            explorer.handle(&Event::Key(key))?;
+            if let Err(err) = explorer.handle(&Event::Key(key)) {
+                self.messages.error(format!("Cannot read the directory: {err}"));
+                return Ok(None);
+            }
 
             if !explorer.current().is_dir() && (key.code == KeyCode::Enter || key.code == KeyCode::Char('l')) {
                 explorer_state.open = false;
-                let path = explorer.current().path().to_path_buf();
-                let buffer = Buffer::new(Some(path), self.config.keybindings.clone(), None, None)?;
-                self.buffers.push(buffer);
-                self.current_buffer = Some(self.buffers.len() - 1);
-                return Ok(None);
+                let path = explorer.current().path().to_string_lossy().to_string();
+                return Ok(self.run_custom(PapierAction::Open(path)));
             }
             return Ok(None);
         }
 
-        let maybe_custom = input.on_key(key, state);
-
-        if let Some(custom) = maybe_custom {
-            match custom.0 {
-                PapierAction::Quit => {
-                    // If there is still a buffer open, close it, else quit the app
-                    if self.buffers.len() > 1 {
-                        debug!(target: "key_events", "Quitting buffer");
-                        let index = self.current_buffer.unwrap();
-                        self.buffers.remove(index);
-                        // check if the previous buffer exists
-                        if !self.buffers.is_empty() {
-                            self.current_buffer = Some((index + self.buffers.len() - 1) % self.buffers.len());
-                            self.current_buffer().unwrap().state.reset_highlighter();
-                        }
-                    } else {
-                        debug!(target: "key_events", "Quitting app from PapierAction::Quit");
-                        return Ok(Some(Action::Quit));
+        let command = (state.mode == EditorMode::Command && key.code == KeyCode::Enter).then(|| state.command.clone());
+        if let Some(custom) = input.on_key(key, state) {
+            return Ok(self.run_custom(custom.0));
+        }
+        if let Some(command) = command.filter(|command| !command.is_empty()) {
+            let name = command.split(' ').next().unwrap_or_default();
+            let commands = &input.command.available_commands;
+            if !commands.iter().any(|c| c.name == name || c.aliases.iter().any(|alias| alias == name)) {
+                self.messages.error(format!("Not an editor command: {command}"));
+            }
+        }
+        Ok(None)
+    }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        trace!(target: "key_events", "Handling mouse event: {:?}", mouse);
+        if self.picker.is_some() {
+            return Ok(None);
+        }
+        if let Some(dashboard) = &mut self.dashboard {
+            let position = Position { x: mouse.column, y: mouse.row };
+            if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                if let Some(item) = dashboard.click(position) {
+                    if let Err(err) = self.pick_dashboard(item) {
+                        self.messages.error(err.to_string());
                     }
-                },
-                PapierAction::Save => {
-                    debug!(target: "key_events", "Saving buffer");
-                    current_buffer.save()?;
-                },
-                PapierAction::SaveAll => {
-                    debug!(target: "key_events", "Saving all buffers ({} buffers)", self.buffers.len());
-                    for buffer in self.buffers.iter_mut() {
-                        buffer.save()?;
+                }
+            }
+            return Ok(None);
+        }
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+            let position = Position { x: mouse.column, y: mouse.row };
+            if let Some(tab) = self.tabs.iter().find(|tab| tab.area.contains(position)).copied() {
+                let close = tab.close.is_some_and(|close| close.contains(position));
+                if self.tab_pages.len() > 1 {
+                    self.switch_tab_page(tab.index);
+                    if close {
+                        self.close_tab_page();
                     }
-                },
-                PapierAction::SaveAs(i) => {
-                    let path = PathBuf::from(i);
-                    debug!(target: "key_events", "Saving buffer as: {:?}", path);
-                    current_buffer.save_as(path)?;
-                },
-                PapierAction::NextBuffer => {
-                    let index = self.current_buffer.unwrap();
-                    let next = (index + 1) % self.buffers.len();
-                    self.current_buffer = Some(next);
-                    self.current_buffer().unwrap().state.reset_highlighter();
-                },
-                PapierAction::PreviousBuffer => {
-                    let index = self.current_buffer.unwrap();
-                    let next = (index + self.buffers.len() - 1) % self.buffers.len();
-                    self.current_buffer = Some(next);
-                    self.current_buffer().unwrap().state.reset_highlighter();
-                },
-                PapierAction::Open(i) => {
-                    let path = PathBuf::from(i);
-                    debug!(target: "key_events", "Opening file: {:?}", path);
-                    let buffer = Buffer::new(Some(path), self.config.keybindings.clone(), None, None)?;
-                    self.buffers.push(buffer);
-                    self.current_buffer = Some(self.buffers.len() - 1);
-                },
-                PapierAction::QuitAll => {
-                    debug!(target: "key_events", "Quitting app from PapierAction::QuitAll");
-                    return Ok(Some(Action::Quit));
-                },
-                PapierAction::ToggleExplorer => {
-                    debug!(target: "key_events", "Toggling explorer: {}", explorer_state.open);
-                    current_buffer.explorer_state.open = !current_buffer.explorer_state.open;
-                },
-                PapierAction::ToggleLogger => {
-                    current_buffer.logger.open = !current_buffer.logger.open;
-                },
+                } else if close && self.buffers[tab.index].modified() {
+                    self.messages.error(NOT_SAVED);
+                } else if close && self.buffers.len() > 1 {
+                    self.remove_buffer(tab.index);
+                } else {
+                    self.show_buffer(tab.index);
+                }
+                return Ok(None);
             }
-        };
+            let areas = self.tab_pages[self.tab_page].layout.areas(self.area);
+            if let Some((id, _)) = areas.iter().find(|(_, area)| area.contains(position)) {
+                self.focus_window(*id);
+            }
+        }
+        let current_buffer = self.current_buffer().unwrap();
+        if !current_buffer.logger.open {
+            current_buffer.input.on_mouse(mouse, &mut current_buffer.state);
+        }
+        Ok(None)
+    }
+
+    fn handle_paste_events(&mut self, text: String) -> Result<Option<Action>> {
+        trace!(target: "key_events", "Handling paste of {} bytes", text.len());
+        self.last_input = Instant::now();
+        if self.dashboard.is_some() || self.picker.is_some() {
+            return Ok(None);
+        }
+        let current_buffer = self.current_buffer().unwrap();
+        if !current_buffer.logger.open && !current_buffer.explorer_state.open {
+            current_buffer.input.on_paste(&text, &mut current_buffer.state);
+        }
+        Ok(None)
+    }
+
+    fn handle_focus_events(&mut self, focused: bool) -> Result<Option<Action>> {
+        if focused {
+            self.check_files();
+        } else if self.config.autosave.focus_lost {
+            self.autosave(0..self.buffers.len());
+        }
+        Ok(None)
+    }
 
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Tick => {
+                let idle = self.config.autosave.idle.map(Duration::from_secs);
+                if idle.is_some_and(|idle| self.last_input.elapsed() >= idle) && self.last_autosave < self.last_input {
+                    self.last_autosave = Instant::now();
+                    self.autosave(0..self.buffers.len());
+                }
+                self.update_recovery();
+                if let Some(watcher) = &mut self.watcher {
+                    watcher.watch(self.buffers.iter().filter_map(|buffer| buffer.path.as_deref()));
+                }
+                let polling = self.watcher.as_ref().is_none_or(FileWatcher::polling);
+                if polling && self.last_check.elapsed() >= self.config.check_interval() {
+                    self.check_files();
+                }
+                let buffer = self.current_buffer().unwrap();
+                if let Some(custom) = buffer.input.on_tick(&mut buffer.state) {
+                    return Ok(self.run_custom(custom.0));
+                }
+            },
+            Action::Error(message) => self.messages.error(message),
+            Action::FilesChanged => self.check_files(),
+            Action::Quit => {
+                if self.config.restore_session && self.buffers.iter().any(|buffer| buffer.path.is_some()) {
+                    if let Err(err) = self.save_session("") {
+                        log::error!("Cannot save the session: {err}");
+                    }
+                }
+                if let Err(err) = self.recent.save() {
+                    log::error!("Cannot save the recent files: {err}");
+                }
+                for path in self.buffers.iter().filter_map(|buffer| buffer.path.as_ref()) {
+                    self.recovery.remove(path);
+                }
+            },
+            _ => {},
+        }
         Ok(None)
     }
 
     fn draw(&mut self, f: &mut Frame<'_>, area: Rect) -> Result<()> {
-        let buffer_index = self.current_buffer.unwrap();
+        self.sync_windows();
+        let buffer_index = self.buffer_index();
         let buffer_count = self.buffers.len();
-        let current_buffer = self.current_buffer().unwrap();
-        let state = &mut current_buffer.state;
+        let current_buffer = &mut self.buffers[buffer_index];
+        let mode = current_buffer.state.mode;
 
         // let area = area.inner(&Margin { horizontal: 1, vertical: 1 });
         let [explorer, editor] = Layout::horizontal([
@@ -379,13 +1656,14 @@ impl Component for Editor {
             f.render_widget(&current_buffer.explorer.widget(), explorer);
         }
 
+        self.tabs.clear();
         if current_buffer.logger.open {
             let logger = TuiLoggerSmartWidget::default()
-                .style_error(Style::default().fg(Color::Red))
-                .style_debug(Style::default().fg(Color::Green))
-                .style_warn(Style::default().fg(Color::Yellow))
-                .style_trace(Style::default().fg(Color::Magenta))
-                .style_info(Style::default().fg(Color::Cyan))
+                .style_error(self.theme.logger(log::Level::Error))
+                .style_debug(self.theme.logger(log::Level::Debug))
+                .style_warn(self.theme.logger(log::Level::Warn))
+                .style_trace(self.theme.logger(log::Level::Trace))
+                .style_info(self.theme.logger(log::Level::Info))
                 .output_separator(':')
                 .output_timestamp(Some("%H:%M:%S".to_string()))
                 .output_level(Some(TuiLoggerLevelOutput::Abbreviated))
@@ -398,60 +1676,465 @@ impl Component for Editor {
             return Ok(());
         }
 
-        let theme = EditorTheme::default()
-            .base(EditorTheme::default().base.bold().bg(Color::Reset))
-            .selection_style(Style::default().bg(Color::LightMagenta).fg(Color::Reset))
-            .status_line(
-                StatusLine::default()
-                    .style_mode(match state.mode {
-                        edtui::EditorMode::Insert => Style::default().bg(Color::LightYellow).fg(LIGHT_GRAY),
-                        edtui::EditorMode::Normal => Style::default().bg(Color::Reset).fg(LIGHT_GRAY),
-                        edtui::EditorMode::Visual => Style::default().bg(Color::LightMagenta).fg(LIGHT_GRAY),
-                        edtui::EditorMode::Search => Style::default().bg(Color::LightBlue).fg(LIGHT_GRAY),
-                        EditorMode::Command => Style::default().bg(Color::Gray).fg(LIGHT_GRAY),
-                    })
-                    .style_line(match state.mode {
-                        edtui::EditorMode::Insert => Style::default().bg(Color::Yellow),
-                        edtui::EditorMode::Normal => Style::default().bg(Color::Reset),
-                        edtui::EditorMode::Visual => Style::default().bg(Color::Magenta),
-                        edtui::EditorMode::Search => Style::default().bg(Color::Blue),
-                        EditorMode::Command => Style::default().bg(Color::DarkGray),
-                    })
+        self.area = bottom;
+        let buf = f.buffer_mut();
+        let message = self
+            .messages
+            .current()
+            .map(|(severity, message)| message.clone().style(self.theme.style(mode, severity.style_key())));
+        let tab_page = &self.tab_pages[self.tab_page];
+        for (id, area) in tab_page.layout.areas(bottom) {
+            let focused = id == tab_page.focus;
+            let window = self.windows.get_mut(&id).unwrap();
+            let index = window.buffer;
+            with_window(&mut self.buffers, window, focused, |buffer| {
+                let state = &mut buffer.state;
+                let status_line = self
+                    .theme
+                    .status_line(state.mode)
                     .text(Some(format!(
-                        "{}/{} {}:{}",
-                        buffer_index + 1,
+                        "{}/{}{} {} {} {}:{}",
+                        index + 1,
                         buffer_count,
+                        if buffer.read_only.is_some() { " [RO]" } else { "" },
+                        buffer.options.encoding_name(),
+                        buffer.options.format,
                         state.cursor.row,
                         state.cursor.col
                     )))
-                    .align_left(true),
-            )
-            .cursor_style(match state.mode {
-                edtui::EditorMode::Insert => Style::default().underlined(),
-                _ => Style::default().bg(Color::White).fg(Color::Black),
-            })
-            .line_numbers_style(Style::default().fg(Color::DarkGray).bg(Color::Reset));
-
-        let editor = EditorView::new(state).theme(theme).message(
-            current_buffer.message.as_ref().map(|m| EditorMessage::new(m.to_string(), Duration::from_secs(3))),
-        );
+                    .align_left(true);
+                let mut theme = self.theme.editor(state.mode).status_line(status_line);
+                if !focused {
+                    theme = theme.hide_cursor();
+                }
+                let message = message.clone().filter(|_| focused);
+                EditorView::new(state).theme(theme).message(message).render(area, buf);
+                if !focused {
+                    return;
+                }
+                if let Some(hints) = KeyHints::new(&buffer.input, mode, HINT_DELAY) {
+                    hints
+                        .style(self.theme.style(mode, "hints"))
+                        .key_style(self.theme.style(mode, "hints_key"))
+                        .render(area, buf);
+                }
+            });
+        }
+        let (tabs, active) = self.tabs();
+        let tab_line = TabLine::new(tabs, active)
+            .style(self.theme.tab(mode, false))
+            .active_style(self.theme.tab(mode, true))
+            .modified_style(self.theme.style(mode, "tab_modified"))
+            .overflow_style(self.theme.style(mode, "tab_overflow"))
+            .close_style(self.theme.style(mode, "tab_close"))
+            .close_button(self.config.tab_close_button);
+        self.tabs = tab_line.areas(top);
+        tab_line.render(top, buf);
 
-        let buf = f.buffer_mut();
-        editor.render(bottom, buf);
-        let n = self.buffers.len();
-        let top_areas = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints((0..n).map(|_| Constraint::Ratio(1, n as u32)).collect::<Vec<_>>())
-            .split(top);
-
-        for (i, area) in top_areas.iter().enumerate() {
-            let buffer = &self.buffers[i];
-            let style = if i == buffer_index { Style::default().bg(Color::DarkGray) } else { Style::default() };
-            let text = buffer.name.clone().unwrap_or_else(|| "Untitled".to_string());
-            Paragraph::new(format!(" {}: {}", i + 1, text)).style(style).wrap(Wrap { trim: false }).render(*area, buf);
+        if let Some(dashboard) = &mut self.dashboard {
+            DashboardView::new(dashboard)
+                .style(self.theme.style(mode, "base"))
+                .title_style(self.theme.style(mode, "dashboard_title"))
+                .key_style(self.theme.style(mode, "dashboard_key"))
+                .selected_style(self.theme.style(mode, "dashboard_selected"))
+                .render(bottom, buf);
+        }
+        if let Some(picker) = &self.picker {
+            PickerView::new(picker)
+                .style(self.theme.style(mode, "hints"))
+                .selected_style(self.theme.style(mode, "picker_selected"))
+                .match_style(self.theme.style(mode, "picker_match"))
+                .render(bottom, buf);
+        }
+        if self.show_messages {
+            History::new(&self.messages, |severity| self.theme.style(mode, severity.style_key()))
+                .style(self.theme.style(mode, "hints"))
+                .render(bottom, buf);
+        }
+        if let Some(prompt) = self.prompt() {
+            let area = Rect { y: bottom.bottom().saturating_sub(1), height: bottom.height.min(1), ..bottom };
+            Clear.render(area, buf);
+            Paragraph::new(prompt).style(self.theme.style(mode, "status_line")).render(area, buf);
         }
         Ok(())
     }
 }
 
-const LIGHT_GRAY: Color = Color::Rgb(248, 250, 252);
+impl Editor {
+    /// Returns the question the next key answers, if any.
+    fn prompt(&self) -> Option<String> {
+        if let Some(modified) = self.quit_prompt {
+            let plural = if modified == 1 { "" } else { "s" };
+            return Some(format!(" {modified} buffer{plural} modified, quit anyway? (y/n)"));
+        }
+        if let Some(&index) = self.recovery_prompts.first() {
+            return Some(format!(
+                " {} has unsaved changes in a recovery file: (r)ecover, (d)iscard, di(f)f, (Esc) later",
+                self.buffers[index].display_path()
+            ));
+        }
+        let index = *self.reload_prompts.first()?;
+        Some(format!(
+            " {} changed on disk: (r)eload and lose the changes, (k)eep the changes",
+            self.buffers[index].display_path()
+        ))
+    }
+}
+
+/// Returns the working directory, or an empty path if it is unknown.
+fn cwd() -> PathBuf {
+    std::env::current_dir().unwrap_or_default()
+}
+
+/// Returns the name of a session, of the working directory if `name` is
+/// empty.
+fn session_name(name: &str) -> io::Result<String> {
+    match name {
+        "" => Ok(session::dir_name(&std::env::current_dir()?)),
+        name => Ok(name.to_string()),
+    }
+}
+
+/// Runs `f` on the buffer of a window with the state of the window loaded.
+fn with_window<R>(buffers: &mut [Buffer], window: &mut Window, focused: bool, f: impl FnOnce(&mut Buffer) -> R) -> R {
+    let buffer = &mut buffers[window.buffer];
+    if focused {
+        return f(buffer);
+    }
+    let focused_state = buffer.state.take_window();
+    buffer.state.load_window(mem::take(&mut window.state));
+    let result = f(buffer);
+    window.state = buffer.state.take_window();
+    buffer.state.load_window(focused_state);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Returns an editor on `files` that keeps its data in a temporary
+    /// directory.
+    fn new_editor(dir: &tempfile::TempDir, files: Vec<PathBuf>) -> Editor {
+        Editor::with_data_dir(files, &dir.path().join("data"))
+    }
+
+    /// Returns an editor on two files of a temporary directory.
+    fn editor_with_files(dir: &tempfile::TempDir) -> Editor {
+        let paths = ["a.txt", "b.txt"].map(|name| dir.path().join(name));
+        for path in &paths {
+            fs::write(path, "text\n").unwrap();
+        }
+        new_editor(dir, paths.to_vec())
+    }
+
+    #[test]
+    fn test_tab_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut editor = editor_with_files(&dir);
+        editor.handle_custom(PapierAction::NextBuffer).unwrap();
+        editor.handle_custom(PapierAction::TabNew(String::new())).unwrap();
+        editor.handle_custom(PapierAction::VSplit(String::new())).unwrap();
+        let tabs = vec![Tab::new("b.txt".to_string(), false), Tab::new("b.txt (2)".to_string(), false)];
+        assert_eq!(editor.tabs(), (tabs, 1));
+
+        // Each tab page keeps its own layout
+        editor.handle_custom(PapierAction::PreviousTabPage).unwrap();
+        assert_eq!(editor.tab_pages[editor.tab_page].layout.windows().len(), 1);
+        editor.handle_custom(PapierAction::PreviousBuffer).unwrap();
+        editor.handle_custom(PapierAction::NextTabPage).unwrap();
+        assert_eq!(editor.buffer_index(), 1);
+
+        editor.handle_custom(PapierAction::TabClose).unwrap();
+        assert_eq!(editor.tab_pages.len(), 1);
+        assert_eq!(editor.windows.len(), 1);
+        assert_eq!(editor.buffer_index(), 0);
+        assert_eq!(editor.tabs().1, 0);
+    }
+
+    #[test]
+    fn test_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut editor = editor_with_files(&dir);
+        editor.handle_custom(PapierAction::VSplit(String::new())).unwrap();
+        let (first, second) = (0, editor.focus());
+
+        // A window left in visual mode keeps it to itself
+        editor.handle_key_events(KeyEvent::from(KeyCode::Char('v'))).unwrap();
+        editor.focus_window(first);
+        assert_eq!(editor.current_buffer().unwrap().state.mode, EditorMode::Normal);
+        assert_eq!(editor.windows[&second].state.mode(), EditorMode::Visual);
+
+        // A line inserted in one window moves the cursor of the other down
+        let buffer = editor.current_buffer().unwrap();
+        buffer.state.mode = EditorMode::Insert;
+        edtui::actions::InsertText("new\n".to_string()).execute(&mut buffer.state);
+        editor.focus_window(second);
+        let buffer = editor.current_buffer().unwrap();
+        assert_eq!(buffer.state.cursor, Index2::new(1, 0));
+        assert_eq!(buffer.state.lines.line(1).unwrap().to_string(), "text");
+    }
+
+    #[test]
+    fn test_quit_modified() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut editor = editor_with_files(&dir);
+        let buffer = editor.current_buffer().unwrap();
+        edtui::actions::InsertText("edit".to_string()).execute(&mut buffer.state);
+        assert!(buffer.modified());
+
+        assert_eq!(editor.handle_custom(PapierAction::Quit).unwrap(), None);
+        assert_eq!(editor.buffers.len(), 2);
+        assert_eq!(editor.messages.current().unwrap().1.text(), NOT_SAVED);
+        editor.handle_custom(PapierAction::ForceQuit).unwrap();
+        assert_eq!(editor.buffers.len(), 1);
+
+        let buffer = editor.current_buffer().unwrap();
+        edtui::actions::InsertText("edit".to_string()).execute(&mut buffer.state);
+        assert_eq!(editor.handle_custom(PapierAction::QuitAll).unwrap(), None);
+        assert_eq!(editor.quit_prompt, Some(1));
+        let key = KeyEvent::from(KeyCode::Char('y'));
+        assert_eq!(editor.handle_key_events(key).unwrap(), Some(Action::Quit));
+        assert_eq!(editor.handle_custom(PapierAction::ForceQuitAll).unwrap(), Some(Action::Quit));
+    }
+
+    #[test]
+    fn test_write_quit_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut editor = editor_with_files(&dir);
+        let buffer = Buffer::new(None, editor.config.keybindings.clone(), None, None, Decoding::default()).unwrap();
+        editor.buffers.push(buffer);
+        let buffer = editor.current_buffer().unwrap();
+        edtui::actions::InsertText("!".to_string()).execute(&mut buffer.state);
+
+        // Only the modified buffers with a file are written
+        let modified = fs::metadata(dir.path().join("b.txt")).unwrap().modified().unwrap();
+        assert_eq!(editor.handle_custom(PapierAction::WriteQuitAll).unwrap(), Some(Action::Quit));
+        assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "t!ext\n");
+        assert_eq!(fs::metadata(dir.path().join("b.txt")).unwrap().modified().unwrap(), modified);
+
+        // A failed write is reported and the app keeps running
+        let buffer = editor.current_buffer().unwrap();
+        edtui::actions::InsertText("!".to_string()).execute(&mut buffer.state);
+        fs::remove_dir_all(dir.path()).unwrap();
+        assert_eq!(editor.handle_custom(PapierAction::WriteQuitAll).unwrap(), None);
+        assert!(editor.messages.current().unwrap().1.text().starts_with("Cannot write"));
+    }
+
+    #[test]
+    fn test_save_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = ["binary.dat", "notes.txt"].map(|name| dir.path().join(name));
+        fs::write(&paths[0], b"\0bin\n").unwrap();
+        fs::write(&paths[1], "notes\n").unwrap();
+        let mut editor = new_editor(&dir, paths.to_vec());
+        for index in 0..2 {
+            edtui::actions::InsertText("!".to_string()).execute(&mut editor.buffers[index].state);
+        }
+
+        // The read-only buffer does not keep the others from being written
+        editor.handle_custom(PapierAction::SaveAll).unwrap();
+        assert_eq!(editor.messages.current().unwrap().1.text(), "All files written");
+        assert_eq!(fs::read(&paths[0]).unwrap(), b"\0bin\n");
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "n!otes\n");
+    }
+
+    #[test]
+    fn test_dashboard() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = ["notes.txt", "todo.txt"].map(|name| dir.path().join(name));
+        let mut editor = new_editor(&dir, Vec::new());
+        assert!(editor.dashboard.is_some());
+        assert_eq!(editor.buffers.len(), 1);
+        for path in paths.iter().rev() {
+            fs::write(path, "text\n").unwrap();
+            editor.recent.add(path);
+        }
+
+        // So does a file opened with :o
+        let mut opened = new_editor(&dir, Vec::new());
+        opened.handle_custom(PapierAction::Open(paths[1].to_string_lossy().to_string())).unwrap();
+        assert_eq!(opened.buffers.len(), 1);
+        assert_eq!(opened.buffers[0].path.as_ref(), Some(&paths[1]));
+
+        // The file replaces the empty buffer behind the dashboard
+        editor.dashboard = Some(editor.new_dashboard());
+        editor.handle_key_events(KeyEvent::from(KeyCode::Char('1'))).unwrap();
+        assert!(editor.dashboard.is_none());
+        assert_eq!(editor.buffers.len(), 1);
+        assert_eq!(editor.buffers[0].path.as_ref(), Some(&paths[0]));
+
+        // The recent files can be found with the picker
+        editor.handle_custom(PapierAction::RecentFiles).unwrap();
+        for c in "todo".chars() {
+            editor.handle_key_events(KeyEvent::from(KeyCode::Char(c))).unwrap();
+        }
+        editor.handle_key_events(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert!(editor.picker.is_none());
+        assert_eq!(editor.current_buffer().unwrap().path.as_ref(), Some(&paths[1]));
+        assert_eq!(editor.recent.files()[0], paths[1]);
+    }
+
+    #[test]
+    fn test_file_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dos.txt");
+        fs::write(&path, "a\r\nb\r\n").unwrap();
+        let mut editor = new_editor(&dir, vec![path.clone()]);
+        editor.handle_custom(PapierAction::Save).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\r\nb\r\n");
+
+        editor.handle_custom(PapierAction::Set("ff=unix".to_string())).unwrap();
+        assert!(editor.current_buffer().unwrap().modified());
+        editor.handle_custom(PapierAction::Save).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\n");
+        assert!(!editor.current_buffer().unwrap().modified());
+
+        editor.handle_custom(PapierAction::Set("ff=cpm".to_string())).unwrap();
+        assert_eq!(editor.messages.current().unwrap().1.text(), "Invalid file format: cpm");
+    }
+
+    #[test]
+    fn test_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "saved").unwrap();
+        let mut editor = new_editor(&dir, vec![path.clone()]);
+        let buffer = editor.current_buffer().unwrap();
+        edtui::actions::InsertText("un".to_string()).execute(&mut buffer.state);
+
+        // The recovery file is written once no key was pressed for a while
+        editor.update(Action::Tick).unwrap();
+        assert!(!editor.recovery.exists(&path));
+        editor.last_input -= editor.config.updatetime();
+        editor.update(Action::Tick).unwrap();
+        assert!(editor.recovery.exists(&path));
+
+        // Opening the file again asks to recover the changes
+        editor.handle_custom(PapierAction::Open(path.to_string_lossy().to_string())).unwrap();
+        assert_eq!(editor.recovery_prompts, [1]);
+        editor.handle_key_events(KeyEvent::from(KeyCode::Char('f'))).unwrap();
+        assert!(editor.recovery_prompts.is_empty());
+        assert_eq!(editor.current_buffer().unwrap().name.as_deref(), Some("notes.txt (recovery diff)"));
+        assert_eq!(editor.current_buffer().unwrap().state.lines, Lines::from("- saved\n+ sunaved"));
+        editor.show_buffer(1);
+        editor.handle_custom(PapierAction::Recover).unwrap();
+        assert_eq!(editor.buffers[1].state.lines, Lines::from("sunaved"));
+        assert!(editor.buffers[1].modified());
+
+        editor.handle_custom(PapierAction::Save).unwrap();
+        assert!(!editor.recovery.exists(&path));
+    }
+
+    #[test]
+    fn test_external_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "first\n").unwrap();
+        let mut editor = new_editor(&dir, vec![path.clone()]);
+
+        // Unmodified buffers are reloaded
+        fs::write(&path, "second\n").unwrap();
+        editor.check_files();
+        assert_eq!(editor.current_buffer().unwrap().state.lines, Lines::from("second"));
+        assert!(!editor.current_buffer().unwrap().modified());
+
+        // Modified buffers ask first and are not written over the change
+        let buffer = editor.current_buffer().unwrap();
+        edtui::actions::InsertText("!".to_string()).execute(&mut buffer.state);
+        fs::write(&path, "third\n").unwrap();
+        editor.check_files();
+        assert_eq!(editor.reload_prompts, [0]);
+        assert!(editor.handle_custom(PapierAction::Save).is_err());
+        editor.handle_key_events(KeyEvent::from(KeyCode::Char('k'))).unwrap();
+        assert!(editor.reload_prompts.is_empty());
+        editor.handle_custom(PapierAction::Save).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "s!econd\n");
+
+        fs::write(&path, "fourth\n").unwrap();
+        assert!(editor.handle_custom(PapierAction::Save).is_err());
+        editor.handle_custom(PapierAction::ForceSave).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "s!econd\n");
+    }
+
+    #[test]
+    fn test_autosave() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut editor = editor_with_files(&dir);
+        let read = |name| fs::read_to_string(dir.path().join(name)).unwrap();
+        let edit = |editor: &mut Editor| {
+            let buffer = editor.current_buffer().unwrap();
+            edtui::actions::InsertText("!".to_string()).execute(&mut buffer.state);
+        };
+
+        // Autosave is opt-in
+        edit(&mut editor);
+        editor.handle_focus_events(false).unwrap();
+        assert_eq!(read("a.txt"), "text\n");
+
+        editor.config.autosave.focus_lost = true;
+        editor.handle_focus_events(false).unwrap();
+        assert_eq!(read("a.txt"), "t!ext\n");
+
+        editor.config.autosave.buffer_switch = true;
+        edit(&mut editor);
+        editor.handle_custom(PapierAction::NextBuffer).unwrap();
+        assert_eq!(read("a.txt"), "t!!ext\n");
+
+        editor.config.autosave.idle = Some(10);
+        edit(&mut editor);
+        // A key was pressed 10 seconds ago, after the last autosave
+        editor.last_autosave -= Duration::from_secs(20);
+        editor.last_input -= Duration::from_secs(10);
+        editor.update(Action::Tick).unwrap();
+        assert_eq!(read("b.txt"), "t!ext\n");
+    }
+
+    #[test]
+    fn test_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut editor = editor_with_files(&dir);
+        editor.handle_custom(PapierAction::VSplit(String::new())).unwrap();
+        editor.handle_custom(PapierAction::NextBuffer).unwrap();
+        editor.handle_custom(PapierAction::ToggleExplorer).unwrap();
+        editor.current_buffer().unwrap().state.cursor = Index2::new(0, 2);
+        editor.handle_custom(PapierAction::MkSession("work".to_string())).unwrap();
+
+        let mut restored = new_editor(&dir, Vec::new());
+        restored.handle_custom(PapierAction::Session("load work".to_string())).unwrap();
+        let restored_paths = restored.buffers.iter().map(|buffer| buffer.path.clone().unwrap()).collect::<Vec<_>>();
+        assert_eq!(restored_paths, ["a.txt", "b.txt"].map(|name| dir.path().join(name)));
+        assert_eq!(restored.tab_pages[restored.tab_page].layout, editor.tab_pages[editor.tab_page].layout);
+        assert_eq!(restored.buffer_index(), 1);
+        let buffer = restored.current_buffer().unwrap();
+        assert_eq!(buffer.state.cursor, Index2::new(0, 2));
+        assert!(buffer.explorer_state.open);
+
+        // Loading a session does not discard changes
+        edtui::actions::InsertText("!".to_string()).execute(&mut buffer.state);
+        assert!(restored.handle_custom(PapierAction::Session("load work".to_string())).is_err());
+        assert!(restored.handle_custom(PapierAction::Session("work".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("latin1.txt");
+        fs::write(&path, b"caf\xE9\n").unwrap();
+        let mut editor = new_editor(&dir, vec![path.clone()]);
+        let buffer = editor.current_buffer().unwrap();
+        assert_eq!(buffer.state.lines, Lines::from("café"));
+        edtui::actions::InsertText("!".to_string()).execute(&mut buffer.state);
+        editor.handle_custom(PapierAction::Save).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"c!af\xE9\n");
+
+        // Reading the file again as UTF-8 makes it read-only
+        editor.handle_custom(PapierAction::Edit("++enc=utf-8".to_string())).unwrap();
+        let buffer = editor.current_buffer().unwrap();
+        assert_eq!(buffer.read_only.as_deref(), Some("not valid utf-8"));
+        assert!(editor.handle_custom(PapierAction::Save).is_err());
+        assert!(editor.handle_custom(PapierAction::Edit("++enc=klingon".to_string())).is_err());
+    }
+}
@@ -0,0 +1,573 @@
+//! Reading and writing the files of the buffers.
+use std::{
+    ffi::OsString,
+    fmt, fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+    time::SystemTime,
+};
+
+use edtui::{text::TextStorage, Lines};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+/// The line endings of a file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileFormat {
+    /// `\n`
+    #[default]
+    Unix,
+    /// `\r\n`
+    Dos,
+    /// `\r`
+    Mac,
+}
+
+impl FileFormat {
+    pub fn line_ending(self) -> &'static str {
+        match self {
+            Self::Unix => "\n",
+            Self::Dos => "\r\n",
+            Self::Mac => "\r",
+        }
+    }
+
+    /// Detects the format by the most common line ending of a text, so the
+    /// stray line endings of a file with mixed line endings do not decide it.
+    fn detect(text: &str) -> Self {
+        let dos = text.matches("\r\n").count();
+        let unix = text.matches('\n').count() - dos;
+        let mac = text.matches('\r').count() - dos;
+        if dos > unix && dos >= mac {
+            Self::Dos
+        } else if mac > unix && mac > dos {
+            Self::Mac
+        } else {
+            Self::Unix
+        }
+    }
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Unix => "unix",
+            Self::Dos => "dos",
+            Self::Mac => "mac",
+        })
+    }
+}
+
+impl FromStr for FileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unix" => Ok(Self::Unix),
+            "dos" => Ok(Self::Dos),
+            "mac" => Ok(Self::Mac),
+            _ => Err(format!("Invalid file format: {s}")),
+        }
+    }
+}
+
+/// The properties of a file that are kept when it is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileOptions {
+    pub format: FileFormat,
+    /// Whether the last line ends with a line ending.
+    pub eol: bool,
+    pub encoding: &'static Encoding,
+    /// Whether the file starts with a byte order mark.
+    pub bom: bool,
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        Self { format: FileFormat::default(), eol: false, encoding: UTF_8, bom: false }
+    }
+}
+
+impl FileOptions {
+    /// Returns the name of the encoding, e.g. `utf-8`.
+    pub fn encoding_name(&self) -> String {
+        self.encoding.name().to_lowercase()
+    }
+}
+
+/// How the content of a file is decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decoding {
+    /// The encoding chosen by the user, detected if `None`.
+    pub encoding: Option<&'static Encoding>,
+    /// The encoding of files that are neither UTF-8 nor UTF-16.
+    pub fallback: &'static Encoding,
+}
+
+impl Default for Decoding {
+    fn default() -> Self {
+        Self { encoding: None, fallback: WINDOWS_1252 }
+    }
+}
+
+/// The text of a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decoded {
+    /// The text with `\n` line endings and without the final line ending.
+    pub text: String,
+    /// The options to write the text back.
+    pub options: FileOptions,
+    /// Why the text cannot be written back unchanged, e.g. `binary`.
+    pub read_only: Option<String>,
+}
+
+/// Returns the encoding for a label, e.g. `latin1` or `utf-16le`.
+pub fn encoding_for_label(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.as_bytes()).ok_or_else(|| format!("Unknown encoding: {label}"))
+}
+
+/// Detects UTF-16 without a byte order mark by the zero bytes of ASCII
+/// characters.
+fn detect_utf16(content: &[u8]) -> Option<&'static Encoding> {
+    if content.is_empty() || !content.len().is_multiple_of(2) {
+        return None;
+    }
+    let sample = &content[..content.len().min(1024)];
+    let zeros = |parity: usize| sample.iter().skip(parity).step_by(2).filter(|&&b| b == 0).count();
+    let (even, odd) = (zeros(0), zeros(1));
+    let half = sample.len() / 4;
+    match (even, odd) {
+        (0, odd) if odd >= half => Some(UTF_16LE),
+        (even, 0) if even >= half => Some(UTF_16BE),
+        _ => None,
+    }
+}
+
+/// Decodes the content of a file. Without a chosen encoding, the encoding
+/// is detected by the byte order mark, UTF-16 and UTF-8 are tried and the
+/// fallback encoding is used otherwise. Binary files and files that are not
+/// valid in their encoding are read-only.
+pub fn decode(content: &[u8], decoding: Decoding) -> Decoded {
+    let bom = Encoding::for_bom(content);
+    let (encoding, bom_len) = match (decoding.encoding, bom) {
+        (Some(encoding), Some((bom_encoding, len))) if bom_encoding == encoding => (encoding, len),
+        (Some(encoding), _) => (encoding, 0),
+        (None, Some(bom)) => bom,
+        (None, None) => {
+            let encoding = detect_utf16(content).unwrap_or_else(|| {
+                let utf8 = !content.contains(&0) && std::str::from_utf8(content).is_ok();
+                if utf8 {
+                    UTF_8
+                } else {
+                    decoding.fallback
+                }
+            });
+            (encoding, 0)
+        },
+    };
+    let content = &content[bom_len..];
+    let utf16 = encoding == UTF_16LE || encoding == UTF_16BE;
+    let binary = decoding.encoding.is_none() && bom_len == 0 && !utf16 && content.contains(&0);
+    let (text, malformed) = encoding.decode_without_bom_handling(content);
+
+    let (text, format, eol) = split_lines(&text);
+    let read_only = if binary {
+        Some("binary".to_string())
+    } else if malformed {
+        Some(format!("not valid {}", encoding.name().to_lowercase()))
+    } else {
+        None
+    };
+    Decoded { text, options: FileOptions { format, eol, encoding, bom: bom_len > 0 }, read_only }
+}
+
+/// Splits a text into `\n` separated lines. Returns the text without the
+/// final line ending, the format and whether there was a final line ending.
+fn split_lines(content: &str) -> (String, FileFormat, bool) {
+    let format = FileFormat::detect(content);
+    let mut text = content.replace("\r\n", "\n");
+    if format == FileFormat::Mac {
+        text = text.replace('\r', "\n");
+    }
+    let eol = text.ends_with('\n');
+    if eol {
+        text.pop();
+    }
+    (text, format, eol)
+}
+
+/// The state of a file on disk when it was read or written, to notice when
+/// another program changes it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl FileStamp {
+    fn new(metadata: &fs::Metadata, content: &[u8]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Self { modified: metadata.modified().ok(), len: metadata.len(), hash: hasher.finish() }
+    }
+
+    /// Returns the stamp of a file if its content changed since this stamp.
+    /// A file that was only touched is not changed, this stamp takes its
+    /// modification time so that it is not read again. Fails if the file
+    /// cannot be read, e.g. because it was deleted.
+    pub fn changed(&mut self, path: &Path) -> io::Result<Option<FileStamp>> {
+        let metadata = fs::metadata(path)?;
+        if metadata.modified().ok() == self.modified && metadata.len() == self.len {
+            return Ok(None);
+        }
+        let stamp = Self::new(&metadata, &fs::read(path)?);
+        if stamp.hash == self.hash && stamp.len == self.len {
+            *self = stamp;
+            return Ok(None);
+        }
+        Ok(Some(stamp))
+    }
+}
+
+/// Reads a file, see [`decode`].
+pub fn read(path: &Path, decoding: Decoding) -> io::Result<(Decoded, FileStamp)> {
+    let content = fs::read(path)?;
+    let stamp = FileStamp::new(&fs::metadata(path)?, &content);
+    Ok((decode(&content, decoding), stamp))
+}
+
+/// Encodes a text in the encoding of `options`. Fails if the encoding
+/// cannot represent a character of the text.
+pub fn encode(text: &str, options: FileOptions) -> io::Result<Vec<u8>> {
+    let encoding = options.encoding;
+    let mut content = Vec::with_capacity(text.len() + 3);
+    if options.bom {
+        content.extend_from_slice(match encoding {
+            e if e == UTF_8 => &[0xEF, 0xBB, 0xBF],
+            e if e == UTF_16LE => &[0xFF, 0xFE],
+            e if e == UTF_16BE => &[0xFE, 0xFF],
+            _ => &[],
+        });
+    }
+    // encoding_rs only encodes to UTF-8 and legacy encodings.
+    if encoding == UTF_16LE {
+        content.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    } else if encoding == UTF_16BE {
+        content.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+    } else {
+        let (encoded, _, unmappable) = encoding.encode(text);
+        if unmappable {
+            let message = format!("Cannot convert the text to {}", options.encoding_name());
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        content.extend_from_slice(&encoded);
+    }
+    Ok(content)
+}
+
+/// Writes the lines of a buffer atomically, see [`write_atomic`]. Returns the
+/// stamp of the written file.
+pub fn write(path: &Path, backup: bool, lines: &Lines, options: FileOptions) -> io::Result<FileStamp> {
+    let content = encode_lines(lines, options)?;
+    write_atomic(path, backup, |f| f.write_all(&content))?;
+    Ok(FileStamp::new(&fs::metadata(path)?, &content))
+}
+
+/// Encodes the lines of a buffer with the line endings and the encoding of
+/// `options`.
+fn encode_lines(lines: &Lines, options: FileOptions) -> io::Result<Vec<u8>> {
+    let ending = options.format.line_ending();
+    let mut text = String::new();
+    for row in 0..lines.len() {
+        if row > 0 {
+            text.push_str(ending);
+        }
+        text.push_str(&lines.line(row).unwrap_or_default());
+    }
+    if options.eol {
+        text.push_str(ending);
+    }
+    encode(&text, options)
+}
+
+/// Returns the path of the backup of a file, e.g. `main.rs~`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push("~");
+    path.with_file_name(name)
+}
+
+/// Writes a file atomically. The content is written to a temporary file in
+/// the same directory, synced and renamed over the file, so a failed write
+/// leaves the file untouched. The permissions of an existing file are kept
+/// and symlinks are followed, i.e. the target of a symlink is replaced. If
+/// `backup` is set, a copy of the previous file is kept at [`backup_path`].
+pub fn write_atomic(path: &Path, backup: bool, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+    let path = match fs::canonicalize(path) {
+        Ok(target) => target,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => resolve_dangling(path),
+        Err(err) => return Err(err),
+    };
+    let metadata = fs::metadata(&path).ok();
+    if backup && metadata.is_some() {
+        fs::copy(&path, backup_path(&path))?;
+    }
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let (temp, file) = create_temp(dir, &path)?;
+
+    let result = (|| {
+        let mut file = io::BufWriter::new(file);
+        write(&mut file)?;
+        let file = file.into_inner().map_err(io::IntoInnerError::into_error)?;
+        file.sync_all()?;
+        if let Some(metadata) = &metadata {
+            file.set_permissions(metadata.permissions())?;
+            set_owner(&file, metadata);
+        }
+        fs::rename(&temp, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result?;
+    sync_dir(dir);
+    Ok(())
+}
+
+/// Creates a temporary file for `path` in `dir`, e.g. `.notes.txt.42.0.tmp`
+/// in process 42. Names taken by files left over from a crash, possibly of
+/// a process with the same id, are skipped.
+fn create_temp(dir: &Path, path: &Path) -> io::Result<(PathBuf, fs::File)> {
+    for attempt in 0.. {
+        let mut name = OsString::from(".");
+        name.push(path.file_name().unwrap_or_default());
+        name.push(format!(".{}.{attempt}.tmp", process::id()));
+        let temp = dir.join(name);
+        match fs::OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {},
+            result => return result.map(|file| (temp, file)),
+        }
+    }
+    unreachable!()
+}
+
+/// Follows a symlink whose target does not exist yet.
+fn resolve_dangling(path: &Path) -> PathBuf {
+    match fs::read_link(path) {
+        Ok(target) => path.parent().map_or(target.clone(), |dir| dir.join(target)),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Keeps the owner of the previous file, if permitted.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn set_owner(file: &fs::File, metadata: &fs::Metadata) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{fchown, MetadataExt};
+        if let Err(err) = fchown(file, Some(metadata.uid()), Some(metadata.gid())) {
+            log::debug!("Cannot keep the owner of the file: {err}");
+        }
+    }
+}
+
+/// Syncs a directory so a rename in it is durable.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Err(err) = fs::File::open(dir).and_then(|dir| dir.sync_all()) {
+        log::debug!("Cannot sync {}: {err}", dir.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_text(text: &str, options: FileOptions) -> Vec<u8> {
+        encode_lines(&Lines::from(text), options).unwrap()
+    }
+
+    #[test]
+    fn test_split_lines() {
+        assert_eq!(split_lines("a\nb\n"), ("a\nb".to_string(), FileFormat::Unix, true));
+        assert_eq!(split_lines("a\r\nb\r\n"), ("a\nb".to_string(), FileFormat::Dos, true));
+        assert_eq!(split_lines("a\rb"), ("a\nb".to_string(), FileFormat::Mac, false));
+        assert_eq!(split_lines("a"), ("a".to_string(), FileFormat::Unix, false));
+        assert_eq!(split_lines("\n"), (String::new(), FileFormat::Unix, true));
+        assert_eq!(split_lines("a\rb\n"), ("a\rb".to_string(), FileFormat::Unix, true));
+        // The most common line ending of a file with mixed line endings wins
+        assert_eq!(split_lines("a\r\nb\nc\r\n"), ("a\nb\nc".to_string(), FileFormat::Dos, true));
+        assert_eq!(split_lines("a\r\nb\nc\n"), ("a\nb\nc".to_string(), FileFormat::Unix, true));
+        assert_eq!(split_lines("a\nb\r\n"), ("a\nb".to_string(), FileFormat::Unix, true));
+    }
+
+    #[test]
+    fn test_decode() {
+        let decoded = decode("héllo\n".as_bytes(), Decoding::default());
+        assert_eq!((decoded.text.as_str(), decoded.options.encoding, decoded.read_only), ("héllo", UTF_8, None));
+
+        let decoded = decode(b"\xEF\xBB\xBFbom", Decoding::default());
+        assert_eq!((decoded.text.as_str(), decoded.options.bom), ("bom", true));
+
+        let decoded = decode(b"h\x00i\x00\n\x00", Decoding::default());
+        assert_eq!((decoded.text.as_str(), decoded.options.encoding), ("hi", UTF_16LE));
+        let decoded = decode(b"\xFE\xFF\x00h\x00i", Decoding::default());
+        assert_eq!((decoded.text.as_str(), decoded.options.encoding), ("hi", UTF_16BE));
+
+        // Invalid UTF-8 falls back to Latin-1 rather than losing the text
+        let decoded = decode(b"caf\xE9", Decoding::default());
+        assert_eq!((decoded.text.as_str(), decoded.options.encoding), ("café", WINDOWS_1252));
+
+        let decoded = decode(b"\x7FELF\x02\x01\x00\x00\x00", Decoding::default());
+        assert_eq!(decoded.read_only.as_deref(), Some("binary"));
+
+        let decoding = Decoding { encoding: Some(UTF_8), ..Decoding::default() };
+        let decoded = decode(b"caf\xE9", decoding);
+        assert_eq!(decoded.read_only.as_deref(), Some("not valid utf-8"));
+    }
+
+    #[test]
+    fn test_encode() {
+        let options = FileOptions { encoding: UTF_16LE, bom: true, ..FileOptions::default() };
+        assert_eq!(encode("hi", options).unwrap(), b"\xFF\xFEh\x00i\x00");
+        let options = FileOptions { encoding: WINDOWS_1252, ..FileOptions::default() };
+        assert_eq!(encode("café", options).unwrap(), b"caf\xE9");
+        assert!(encode("日本", options).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let contents: [&[u8]; 9] = [
+            b"a\nb\n",
+            b"a\nb",
+            b"a\r\n\r\nb\r\n",
+            b"a\rb\r",
+            b"",
+            b"\n",
+            b"\xEF\xBB\xBFa\r\n",
+            b"\xFF\xFEa\x00\n\x00",
+            b"caf\xE9\n",
+        ];
+        for content in contents {
+            let decoded = decode(content, Decoding::default());
+            assert_eq!(encode_text(&decoded.text, decoded.options), content);
+        }
+        let options = FileOptions { format: FileFormat::Dos, eol: true, ..FileOptions::default() };
+        assert_eq!(encode_text("a\nb", options), b"a\r\nb\r\n");
+
+        // A file with mixed line endings is written with the most common one
+        let decoded = decode(b"a\r\nb\nc\r\n", Decoding::default());
+        assert_eq!(encode_text(&decoded.text, decoded.options), b"a\r\nb\r\nc\r\n");
+    }
+
+    #[test]
+    fn test_file_format() {
+        assert_eq!("dos".parse(), Ok(FileFormat::Dos));
+        assert!("cpm".parse::<FileFormat>().is_err());
+        assert_eq!(FileFormat::Mac.to_string(), "mac");
+    }
+
+    #[test]
+    fn test_file_stamp() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        let mut stamp = write(&path, false, &Lines::from("first"), FileOptions::default()).unwrap();
+        assert_eq!(stamp.changed(&path).unwrap(), None);
+
+        // Touching the file does not change it, the stamp takes the new time
+        fs::write(&path, "first").unwrap();
+        let mut touched = FileStamp { modified: None, ..stamp };
+        assert_eq!(touched.changed(&path).unwrap(), None);
+        assert_eq!(touched.modified, fs::metadata(&path).unwrap().modified().ok());
+
+        fs::write(&path, "second").unwrap();
+        let (_, changed) = read(&path, Decoding::default()).unwrap();
+        assert_eq!(stamp.changed(&path).unwrap(), Some(changed));
+        fs::remove_file(&path).unwrap();
+        assert!(stamp.changed(&path).is_err());
+    }
+
+    fn write_str(path: &Path, backup: bool, content: &str) -> io::Result<()> {
+        write_atomic(path, backup, |f| f.write_all(content.as_bytes()))
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        write_str(&path, false, "hello").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello");
+        write_str(&path, false, "world").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "world");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // A temporary file left over from a crash is skipped
+        let stale = dir.path().join(format!(".file.txt.{}.0.tmp", process::id()));
+        fs::write(&stale, "stale").unwrap();
+        write_str(&path, false, "again").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "again");
+        assert_eq!(fs::read_to_string(&stale).unwrap(), "stale");
+    }
+
+    #[test]
+    fn test_failed_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, "original").unwrap();
+        let result = write_atomic(&path, false, |f| {
+            f.write_all(b"partial")?;
+            Err(io::Error::other("disk full"))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        write_str(&path, true, "first").unwrap();
+        assert!(!backup_path(&path).exists());
+        write_str(&path, true, "second").unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "first");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.sh");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o751)).unwrap();
+        write_str(&path, false, "echo").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o751);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target.txt");
+        let link = dir.path().join("link.txt");
+        fs::write(&target, "old").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        write_str(&link, false, "new").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+
+        let dangling = dir.path().join("dangling.txt");
+        std::os::unix::fs::symlink("created.txt", &dangling).unwrap();
+        write_str(&dangling, false, "created").unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("created.txt")).unwrap(), "created");
+    }
+}
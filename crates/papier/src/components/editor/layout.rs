@@ -0,0 +1,186 @@
+//! The layout of the editor windows, a binary tree of splits.
+use ratatui::layout::{Constraint, Direction, Layout as RatatuiLayout, Position, Rect};
+use serde::{Deserialize, Serialize};
+
+/// Identifies a window in the [`Layout`].
+pub type WindowId = usize;
+
+/// The smallest share of a split, in percent.
+const MIN_PERCENT: u16 = 10;
+
+/// A direction to move the focus in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowDirection {
+    Left,
+    Down,
+    Up,
+    Right,
+}
+
+/// Serializes the ratatui [`Direction`], which has no serde support.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Direction")]
+enum DirectionDef {
+    Horizontal,
+    Vertical,
+}
+
+/// The arrangement of the windows on the screen.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Layout {
+    Window(WindowId),
+    /// Two layouts side by side (horizontal) or stacked (vertical). The
+    /// first layout takes `percent` of the area.
+    Split {
+        #[serde(with = "DirectionDef")]
+        direction: Direction,
+        percent: u16,
+        first: Box<Layout>,
+        second: Box<Layout>,
+    },
+}
+
+impl Layout {
+    /// Splits a window, the new window is placed below or right of it.
+    pub fn split(&mut self, window: WindowId, new: WindowId, direction: Direction) -> bool {
+        match self {
+            Self::Window(id) if *id == window => {
+                let first = Box::new(Self::Window(window));
+                *self = Self::Split { direction, percent: 50, first, second: Box::new(Self::Window(new)) };
+                true
+            },
+            Self::Window(_) => false,
+            Self::Split { first, second, .. } => {
+                first.split(window, new, direction) || second.split(window, new, direction)
+            },
+        }
+    }
+
+    /// Removes a window, its sibling takes its space. Returns the first
+    /// window of the sibling. The last window cannot be removed.
+    pub fn close(&mut self, window: WindowId) -> Option<WindowId> {
+        let Self::Split { first, second, .. } = self else {
+            return None;
+        };
+        let sibling = if **first == Self::Window(window) {
+            second
+        } else if **second == Self::Window(window) {
+            first
+        } else {
+            return first.close(window).or_else(|| second.close(window));
+        };
+        *self = std::mem::replace(&mut **sibling, Self::Window(window));
+        self.windows().first().copied()
+    }
+
+    /// Returns the windows in the layout.
+    pub fn windows(&self) -> Vec<WindowId> {
+        match self {
+            Self::Window(id) => vec![*id],
+            Self::Split { first, second, .. } => [first.windows(), second.windows()].concat(),
+        }
+    }
+
+    /// Grows a window by `delta` percent of the nearest split in `direction`.
+    /// A negative `delta` shrinks the window.
+    pub fn resize(&mut self, window: WindowId, direction: Direction, delta: i16) -> bool {
+        let Self::Split { direction: split, percent, first, second } = self else {
+            return false;
+        };
+        if first.resize(window, direction, delta) || second.resize(window, direction, delta) {
+            return true;
+        }
+        let in_first = first.windows().contains(&window);
+        if *split != direction || !(in_first || second.windows().contains(&window)) {
+            return false;
+        }
+        let delta = if in_first { delta } else { -delta };
+        *percent = percent.saturating_add_signed(delta).clamp(MIN_PERCENT, 100 - MIN_PERCENT);
+        true
+    }
+
+    /// Returns the area of each window.
+    pub fn areas(&self, area: Rect) -> Vec<(WindowId, Rect)> {
+        match self {
+            Self::Window(id) => vec![(*id, area)],
+            Self::Split { direction, percent, first, second } => {
+                let [first_area, second_area] = RatatuiLayout::default()
+                    .direction(*direction)
+                    .constraints([Constraint::Percentage(*percent), Constraint::Min(0)])
+                    .areas(area);
+                [first.areas(first_area), second.areas(second_area)].concat()
+            },
+        }
+    }
+
+    /// Returns the window next to a window in a direction.
+    pub fn neighbor(&self, area: Rect, window: WindowId, direction: WindowDirection) -> Option<WindowId> {
+        let areas = self.areas(area);
+        let (_, current) = areas.iter().find(|(id, _)| *id == window)?;
+        let (center_x, center_y) = (current.x + current.width / 2, current.y + current.height / 2);
+        let position = match direction {
+            WindowDirection::Left => Position { x: current.left().checked_sub(1)?, y: center_y },
+            WindowDirection::Right => Position { x: current.right(), y: center_y },
+            WindowDirection::Up => Position { x: center_x, y: current.top().checked_sub(1)? },
+            WindowDirection::Down => Position { x: center_x, y: current.bottom() },
+        };
+        areas.iter().find(|(_, area)| area.contains(position)).map(|(id, _)| *id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Splits window 0 into 0 | 1, then 1 into 1 above 2.
+    fn layout() -> Layout {
+        let mut layout = Layout::Window(0);
+        assert!(layout.split(0, 1, Direction::Horizontal));
+        assert!(layout.split(1, 2, Direction::Vertical));
+        layout
+    }
+
+    #[test]
+    fn test_areas() {
+        let areas = layout().areas(Rect::new(0, 0, 80, 20));
+        assert_eq!(areas, vec![
+            (0, Rect::new(0, 0, 40, 20)),
+            (1, Rect::new(40, 0, 40, 10)),
+            (2, Rect::new(40, 10, 40, 10))
+        ]);
+    }
+
+    #[test]
+    fn test_neighbor() {
+        let layout = layout();
+        let area = Rect::new(0, 0, 80, 20);
+        assert_eq!(layout.neighbor(area, 0, WindowDirection::Right), Some(2));
+        assert_eq!(layout.neighbor(area, 1, WindowDirection::Down), Some(2));
+        assert_eq!(layout.neighbor(area, 2, WindowDirection::Up), Some(1));
+        assert_eq!(layout.neighbor(area, 2, WindowDirection::Left), Some(0));
+        assert_eq!(layout.neighbor(area, 0, WindowDirection::Left), None);
+    }
+
+    #[test]
+    fn test_close() {
+        let mut layout = layout();
+        assert_eq!(layout.close(1), Some(2));
+        assert_eq!(layout.windows(), vec![0, 2]);
+        assert_eq!(layout.close(0), Some(2));
+        assert_eq!(layout, Layout::Window(2));
+        assert_eq!(layout.close(2), None);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut layout = layout();
+        assert!(layout.resize(2, Direction::Horizontal, 10));
+        assert!(layout.resize(2, Direction::Vertical, 20));
+        let areas = layout.areas(Rect::new(0, 0, 100, 10));
+        assert_eq!(areas[0].1.width, 40);
+        assert_eq!(areas[1].1.height, 3);
+        assert!(!Layout::Window(0).resize(0, Direction::Vertical, 10));
+    }
+}
@@ -0,0 +1,170 @@
+//! The messages of the editor. The latest message is shown in the status line,
+//! the history is listed by `:messages`.
+use std::{collections::VecDeque, time::Duration};
+
+use edtui::view::EditorMessage;
+use ratatui::{prelude::*, widgets::*};
+
+/// The number of messages kept in the history.
+const HISTORY: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Returns how long a message is shown in the status line.
+    fn duration(self) -> Duration {
+        match self {
+            Self::Info => Duration::from_secs(3),
+            Self::Warning => Duration::from_secs(5),
+            Self::Error => Duration::from_secs(8),
+        }
+    }
+
+    /// Returns the style key of the messages, e.g. `message_error`.
+    pub fn style_key(self) -> &'static str {
+        match self {
+            Self::Info => "message_info",
+            Self::Warning => "message_warning",
+            Self::Error => "message_error",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    pub severity: Severity,
+    pub text: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Messages {
+    history: VecDeque<Message>,
+    current: Option<(Severity, EditorMessage)>,
+}
+
+impl Messages {
+    /// Adds a message to the history and shows it in the status line. The
+    /// message is logged as well.
+    pub fn push(&mut self, severity: Severity, text: impl Into<String>) {
+        let text = text.into();
+        match severity {
+            Severity::Info => log::info!("{text}"),
+            Severity::Warning => log::warn!("{text}"),
+            Severity::Error => log::error!("{text}"),
+        }
+        self.current = Some((severity, EditorMessage::new(text.clone(), severity.duration())));
+        self.history.push_back(Message { severity, text });
+        if self.history.len() > HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(Severity::Info, text);
+    }
+
+    pub fn warn(&mut self, text: impl Into<String>) {
+        self.push(Severity::Warning, text);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(Severity::Error, text);
+    }
+
+    /// Returns the message in the status line, if it is not expired.
+    pub fn current(&self) -> Option<(Severity, &EditorMessage)> {
+        self.current
+            .as_ref()
+            .filter(|(_, message)| !message.is_expired())
+            .map(|(severity, message)| (*severity, message))
+    }
+
+    /// Returns the messages, the oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &Message> {
+        self.history.iter()
+    }
+}
+
+/// A popup listing the message history, the latest message at the bottom.
+pub struct History<'a> {
+    messages: &'a Messages,
+    style: Style,
+    severity_style: Box<dyn Fn(Severity) -> Style + 'a>,
+}
+
+impl<'a> History<'a> {
+    pub fn new(messages: &'a Messages, severity_style: impl Fn(Severity) -> Style + 'a) -> Self {
+        Self { messages, style: Style::default(), severity_style: Box::new(severity_style) }
+    }
+
+    /// Sets the style of the popup.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl Widget for History<'_> {
+    /// Renders the popup over the lower half of the area.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let height = (area.height / 2).max(area.height.min(3));
+        let popup = Rect { y: area.bottom() - height, height, ..area };
+        let lines = self
+            .messages
+            .history()
+            .map(|message| Line::styled(message.text.clone(), (self.severity_style)(message.severity)))
+            .collect::<Vec<_>>();
+        let hidden = lines.len().saturating_sub(popup.height.saturating_sub(2) as usize);
+
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .scroll((hidden as u16, 0))
+            .block(Block::bordered().title(" Messages ").title_bottom(" Press any key to continue "))
+            .style(self.style)
+            .render(popup, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        let mut messages = Messages::default();
+        assert!(messages.current().is_none());
+        messages.info("saved");
+        messages.error("permission denied");
+        let (severity, message) = messages.current().unwrap();
+        assert_eq!((severity, message.text()), (Severity::Error, "permission denied"));
+
+        for i in 0..HISTORY {
+            messages.warn(format!("warning {i}"));
+        }
+        assert_eq!(messages.history().count(), HISTORY);
+        assert_eq!(messages.history().next().unwrap().text, "warning 0");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut messages = Messages::default();
+        for i in 0..5 {
+            messages.info(format!("message {i}"));
+        }
+        let area = Rect::new(0, 0, 30, 8);
+        let mut buf = Buffer::empty(area);
+        History::new(&messages, |_| Style::default()).render(area, &mut buf);
+        // Only the latest messages fit into the popup
+        let rows = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf.get(x, y).symbol()).collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows[5].trim_end(), "│message 3                   │");
+        assert_eq!(rows[6].trim_end(), "│message 4                   │");
+    }
+}
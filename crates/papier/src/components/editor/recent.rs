@@ -0,0 +1,92 @@
+//! The most recently used files, kept in the data dir across runs.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::file;
+
+/// The number of files that are remembered.
+const MAX_RECENT: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct RecentFiles {
+    /// The JSON file the list is kept in.
+    path: PathBuf,
+    /// The files, the most recent first.
+    files: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    /// Loads the list from `path`. A missing or invalid file is an empty
+    /// list.
+    pub fn load(path: PathBuf) -> Self {
+        let files = read(&path).unwrap_or_else(|err| {
+            if err.kind() != io::ErrorKind::NotFound {
+                log::warn!("Cannot read the recent files from {}: {err}", path.display());
+            }
+            Vec::new()
+        });
+        Self { path, files }
+    }
+
+    /// Returns the files, the most recent first.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Moves a file to the front of the list.
+    pub fn add(&mut self, file: &Path) {
+        let file = std::path::absolute(file).unwrap_or_else(|_| file.to_path_buf());
+        self.files.retain(|recent| *recent != file);
+        self.files.insert(0, file);
+        self.files.truncate(MAX_RECENT);
+    }
+
+    /// Writes the list. Files used by other instances since the list was
+    /// loaded are kept after the files of this one.
+    pub fn save(&self) -> io::Result<()> {
+        let mut files = self.files.clone();
+        for file in read(&self.path).unwrap_or_default() {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        files.truncate(MAX_RECENT);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        file::write_atomic(&self.path, false, |f| serde_json::to_writer_pretty(f, &files).map_err(io::Error::from))
+    }
+}
+
+fn read(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let bytes = fs::read(path)?;
+    serde_json::from_slice(&bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recent.json");
+        let mut recent = RecentFiles::load(path.clone());
+        assert!(recent.files().is_empty());
+
+        recent.add(&dir.path().join("a.txt"));
+        recent.add(&dir.path().join("b.txt"));
+        recent.add(&dir.path().join("a.txt"));
+        assert_eq!(recent.files(), [dir.path().join("a.txt"), dir.path().join("b.txt")]);
+
+        // Another instance saved in the meantime
+        let mut other = RecentFiles::load(path.clone());
+        other.add(&dir.path().join("c.txt"));
+        other.save().unwrap();
+        recent.save().unwrap();
+        let files = RecentFiles::load(path).files().to_vec();
+        assert_eq!(files, ["a.txt", "b.txt", "c.txt"].map(|name| dir.path().join(name)));
+    }
+}
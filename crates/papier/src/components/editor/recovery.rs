@@ -0,0 +1,151 @@
+//! Recovery files of modified buffers. They keep the changes of a buffer
+//! until it is saved, so they can be recovered after a crash.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use edtui::{text::TextStorage, Lines};
+
+use super::file;
+
+/// The modified buffers at the last tick, written by [`dump`] if the app
+/// panics.
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
+
+struct Snapshot {
+    recovery: Recovery,
+    buffers: Vec<(PathBuf, Lines)>,
+}
+
+impl Snapshot {
+    fn write(&self) {
+        for (file, lines) in &self.buffers {
+            if let Err(err) = self.recovery.write(file, lines) {
+                log::error!("Cannot write the recovery file of {}: {err}", file.display());
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Recovery {
+    dir: PathBuf,
+}
+
+impl Recovery {
+    /// Keeps the recovery files in `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the recovery file of a file, named by its absolute path,
+    /// e.g. `%home%me%notes.txt.recovery`.
+    pub fn path(&self, file: &Path) -> PathBuf {
+        let file = std::path::absolute(file).unwrap_or_else(|_| file.to_path_buf());
+        let name = file.to_string_lossy().replace(['/', '\\', ':'], "%");
+        self.dir.join(format!("{name}.recovery"))
+    }
+
+    /// Writes the recovery file of a file.
+    pub fn write(&self, file: &Path, lines: &Lines) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        file::write_atomic(&self.path(file), false, |f| lines.write_to(f))
+    }
+
+    /// Reads the recovery file of a file.
+    pub fn read(&self, file: &Path) -> io::Result<String> {
+        fs::read_to_string(self.path(file))
+    }
+
+    /// Removes the recovery file of a file, if there is one.
+    pub fn remove(&self, file: &Path) {
+        match fs::remove_file(self.path(file)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                log::warn!("Cannot remove the recovery file of {}: {err}", file.display());
+            },
+            _ => {},
+        }
+    }
+
+    /// Whether a file has a recovery file that is not older than the file.
+    pub fn exists(&self, file: &Path) -> bool {
+        let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+        match (modified(&self.path(file)), modified(file)) {
+            (Ok(recovery), Ok(file)) => recovery >= file,
+            (Ok(_), Err(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Keeps the modified buffers for [`dump`].
+    pub fn snapshot(&self, buffers: Vec<(PathBuf, Lines)>) {
+        if let Ok(mut snapshot) = SNAPSHOT.lock() {
+            *snapshot = Some(Snapshot { recovery: self.clone(), buffers });
+        }
+    }
+}
+
+/// Writes the recovery files of the last snapshot. It is called by the panic
+/// hook and does its best without blocking.
+pub fn dump() {
+    let Ok(snapshot) = SNAPSHOT.try_lock() else {
+        return;
+    };
+    if let Some(snapshot) = snapshot.as_ref() {
+        snapshot.write();
+    }
+}
+
+/// Returns a line diff from `old` to `new`. Each line is marked with `-` if
+/// only `old` has it, `+` if only `new` has it and a space otherwise.
+pub fn line_diff(old: &str, new: &str) -> String {
+    let lines = diff::lines(old, new).into_iter().map(|line| {
+        match line {
+            diff::Result::Left(line) => format!("- {line}"),
+            diff::Result::Right(line) => format!("+ {line}"),
+            diff::Result::Both(line, _) => format!("  {line}"),
+        }
+    });
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let recovery = Recovery::new(dir.path().join("recovery"));
+        let file = dir.path().join("notes.txt");
+        assert!(!recovery.exists(&file));
+
+        recovery.write(&file, &Lines::from("changed")).unwrap();
+        assert!(recovery.exists(&file));
+        assert_eq!(recovery.read(&file).unwrap(), "changed");
+        assert!(recovery.path(&file).file_name().unwrap().to_string_lossy().ends_with("%notes.txt.recovery"));
+
+        recovery.remove(&file);
+        assert!(!recovery.exists(&file));
+        recovery.remove(&file);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let recovery = Recovery::new(dir.path().to_path_buf());
+        let file = dir.path().join("notes.txt");
+        // The global snapshot is shared with other tests
+        let snapshot = Snapshot { recovery: recovery.clone(), buffers: vec![(file.clone(), Lines::from("unsaved"))] };
+        snapshot.write();
+        assert_eq!(recovery.read(&file).unwrap(), "unsaved");
+    }
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(line_diff("a\nb\nc", "a\nB\nc\nd"), "  a\n- b\n+ B\n  c\n+ d");
+        assert_eq!(line_diff("same", "same"), "  same");
+    }
+}
@@ -0,0 +1,222 @@
+//! Sessions, the open files and windows of the editor saved as JSON to be
+//! restored later, see `:mksession` and `--session`.
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use edtui::{state::window::WindowState, Index2};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    file,
+    layout::{Layout, WindowId},
+};
+
+/// The open buffers with a file, the windows on them and the tab pages.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub buffers: Vec<SessionBuffer>,
+    pub windows: HashMap<WindowId, SessionWindow>,
+    pub tab_pages: Vec<SessionTabPage>,
+    /// The index of the current tab page.
+    pub tab_page: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionBuffer {
+    pub path: PathBuf,
+    /// Where the buffer was last left.
+    pub view: View,
+    /// Whether the file explorer is open.
+    pub explorer: bool,
+    /// The directory shown by the file explorer.
+    pub explorer_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionWindow {
+    /// The index of the buffer in [`Session::buffers`], `None` if the
+    /// buffer has no file.
+    pub buffer: Option<usize>,
+    pub view: View,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionTabPage {
+    pub layout: Layout,
+    /// The focused window.
+    pub focus: WindowId,
+}
+
+/// The cursor and the offset of the viewport, as row and column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct View {
+    pub cursor: (usize, usize),
+    pub offset: (usize, usize),
+}
+
+impl From<&WindowState> for View {
+    fn from(window: &WindowState) -> Self {
+        let (cursor, offset) = (window.cursor(), window.offset());
+        Self { cursor: (cursor.row, cursor.col), offset: (offset.row, offset.col) }
+    }
+}
+
+impl From<View> for WindowState {
+    fn from(view: View) -> Self {
+        WindowState::new(Index2::new(view.cursor.0, view.cursor.1), Index2::new(view.offset.0, view.offset.1))
+    }
+}
+
+impl Session {
+    /// Checks that the tab pages only show windows of the session and that
+    /// the windows show buffers of the session.
+    fn validate(&self) -> Result<(), String> {
+        if self.buffers.is_empty() {
+            return Err("no buffers".to_string());
+        }
+        if self.tab_page >= self.tab_pages.len() {
+            return Err("no current tab page".to_string());
+        }
+        for tab_page in &self.tab_pages {
+            let windows = tab_page.layout.windows();
+            if !windows.contains(&tab_page.focus) {
+                return Err(format!("window {} is not in its tab page", tab_page.focus));
+            }
+            for id in windows {
+                let window = self.windows.get(&id).ok_or_else(|| format!("no window {id}"))?;
+                if window.buffer.is_some_and(|buffer| buffer >= self.buffers.len()) {
+                    return Err(format!("no buffer for window {id}"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The saved sessions, one JSON file per name.
+#[derive(Clone, Debug)]
+pub struct Sessions {
+    dir: PathBuf,
+}
+
+impl Sessions {
+    /// Keeps the sessions in `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns the file of a session. Names cannot contain path separators.
+    pub fn path(&self, name: &str) -> io::Result<PathBuf> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            let message = format!("Invalid session name: {name}");
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        Ok(self.dir.join(format!("{name}.json")))
+    }
+
+    /// Whether a session was saved.
+    pub fn exists(&self, name: &str) -> bool {
+        self.path(name).is_ok_and(|path| path.exists())
+    }
+
+    /// Saves a session, replacing the one of the same name.
+    pub fn save(&self, name: &str, session: &Session) -> io::Result<()> {
+        let path = self.path(name)?;
+        fs::create_dir_all(&self.dir)?;
+        file::write_atomic(&path, false, |f| serde_json::to_writer_pretty(f, session).map_err(io::Error::from))
+    }
+
+    /// Returns the names of the saved sessions, the most recent first.
+    pub fn list(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut sessions = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().to_string_lossy().strip_suffix(".json")?.to_string();
+                let modified = entry.metadata().and_then(|metadata| metadata.modified()).ok();
+                Some((modified, name))
+            })
+            .collect::<Vec<_>>();
+        sessions.sort_by(|a, b| b.cmp(a));
+        sessions.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Loads a session.
+    pub fn load(&self, name: &str) -> io::Result<Session> {
+        let path = self.path(name)?;
+        let bytes = fs::read(&path).map_err(|err| {
+            match err.kind() {
+                io::ErrorKind::NotFound => io::Error::new(err.kind(), format!("No session {name}")),
+                _ => err,
+            }
+        })?;
+        let invalid =
+            |err: String| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid session {name}: {err}"));
+        let session: Session = serde_json::from_slice(&bytes).map_err(|err| invalid(err.to_string()))?;
+        session.validate().map_err(invalid)?;
+        Ok(session)
+    }
+}
+
+/// Returns the name of the session of a working directory, named by its
+/// path, e.g. `%home%me%project`.
+pub fn dir_name(dir: &Path) -> String {
+    dir.to_string_lossy().replace(['/', '\\', ':'], "%")
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Direction;
+
+    use super::*;
+
+    fn session() -> Session {
+        let mut layout = Layout::Window(0);
+        layout.split(0, 1, Direction::Horizontal);
+        let view = View { cursor: (3, 2), offset: (1, 0) };
+        let buffer = SessionBuffer { path: "notes.txt".into(), view, explorer: true, explorer_dir: "/".into() };
+        Session {
+            buffers: vec![buffer],
+            windows: HashMap::from([
+                (0, SessionWindow { buffer: Some(0), view }),
+                (1, SessionWindow { buffer: None, view: View::default() }),
+            ]),
+            tab_pages: vec![SessionTabPage { layout, focus: 1 }],
+            tab_page: 0,
+        }
+    }
+
+    #[test]
+    fn test_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = Sessions::new(dir.path().join("sessions"));
+        assert!(!sessions.exists("work"));
+        assert_eq!(sessions.load("work").unwrap_err().to_string(), "No session work");
+
+        sessions.save("work", &session()).unwrap();
+        assert!(sessions.exists("work"));
+        assert_eq!(sessions.load("work").unwrap(), session());
+        assert_eq!(sessions.list(), ["work"]);
+
+        assert!(sessions.save("../work", &session()).is_err());
+        assert_eq!(dir_name(Path::new("/home/me/project")), "%home%me%project");
+    }
+
+    #[test]
+    fn test_invalid_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = Sessions::new(dir.path().to_path_buf());
+        let mut invalid = session();
+        invalid.windows.remove(&1);
+        sessions.save("invalid", &invalid).unwrap();
+        assert_eq!(sessions.load("invalid").unwrap_err().to_string(), "Invalid session invalid: no window 1");
+
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+        assert!(sessions.load("broken").unwrap_err().to_string().starts_with("Invalid session broken: "));
+    }
+}
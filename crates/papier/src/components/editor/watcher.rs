@@ -0,0 +1,124 @@
+//! Watches the open files for changes on disk.
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::action::Action;
+
+/// Watches the directories of the files rather than the files, so a file
+/// replaced by a rename, as most editors save, stays watched.
+pub struct FileWatcher {
+    /// `None` if the watcher could not be started.
+    watcher: Option<RecommendedWatcher>,
+    /// The watched files, shared with the thread of the watcher.
+    files: Arc<Mutex<HashSet<PathBuf>>>,
+    /// The watched directories.
+    dirs: HashSet<PathBuf>,
+    /// The directories that could not be watched.
+    failed: HashSet<PathBuf>,
+}
+
+impl FileWatcher {
+    /// Starts a watcher on a background thread, it sends
+    /// [`Action::FilesChanged`] when a watched file changes.
+    pub fn new(tx: UnboundedSender<Action>) -> Self {
+        let files = Arc::new(Mutex::new(HashSet::new()));
+        let watched = Arc::clone(&files);
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let changed = match event {
+                Ok(event) if event.need_rescan() => true,
+                Ok(event) => {
+                    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+                        && event.paths.iter().any(|path| watched.lock().unwrap().contains(path))
+                },
+                // Events may have been lost, the files are checked
+                Err(err) => {
+                    log::debug!("File watcher error: {err}");
+                    true
+                },
+            };
+            if changed {
+                let _ = tx.send(Action::FilesChanged);
+            }
+        });
+        let watcher = watcher.map_err(|err| log::warn!("Cannot watch files, they are polled: {err}")).ok();
+        Self { watcher, files, dirs: HashSet::new(), failed: HashSet::new() }
+    }
+
+    /// Watches `files` instead of the files watched so far.
+    pub fn watch<'a>(&mut self, files: impl IntoIterator<Item = &'a Path>) {
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        let files: HashSet<_> = files.into_iter().filter_map(|file| std::path::absolute(file).ok()).collect();
+        let dirs: HashSet<_> = files.iter().filter_map(|file| file.parent()).map(Path::to_path_buf).collect();
+        for dir in self.dirs.difference(&dirs) {
+            if let Err(err) = watcher.unwatch(dir) {
+                log::debug!("Cannot unwatch {}: {err}", dir.display());
+            }
+        }
+        self.failed.retain(|dir| dirs.contains(dir));
+        for dir in dirs.difference(&self.dirs) {
+            if self.failed.contains(dir) {
+                continue;
+            }
+            if let Err(err) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                log::debug!("Cannot watch {}, its files are polled: {err}", dir.display());
+                self.failed.insert(dir.clone());
+            }
+        }
+        self.dirs = dirs.into_iter().filter(|dir| !self.failed.contains(dir)).collect();
+        *self.files.lock().unwrap() = files;
+    }
+
+    /// Whether the files have to be polled, as the watcher could not be
+    /// started or could not watch some of them.
+    pub fn polling(&self) -> bool {
+        self.watcher.is_none() || !self.failed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_watch() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, other) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+        fs::write(&file, "text").unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut watcher = FileWatcher::new(tx);
+        watcher.watch([file.as_path()]);
+        assert!(!watcher.polling());
+
+        // Only the watched files are reported
+        let changed = |rx: &mut mpsc::UnboundedReceiver<Action>| {
+            std::thread::sleep(Duration::from_millis(200));
+            let mut changed = false;
+            while let Ok(action) = rx.try_recv() {
+                changed |= action == Action::FilesChanged;
+            }
+            changed
+        };
+        fs::write(&other, "other").unwrap();
+        assert!(!changed(&mut rx));
+        fs::write(&file, "changed").unwrap();
+        assert!(changed(&mut rx));
+
+        // A directory that does not exist cannot be watched
+        watcher.watch([dir.path().join("missing/a.txt").as_path()]);
+        assert!(watcher.polling());
+        fs::write(&file, "unwatched").unwrap();
+        assert!(!changed(&mut rx));
+    }
+}
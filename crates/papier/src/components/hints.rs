@@ -0,0 +1,169 @@
+//! A popup listing the keys that continue a pending key sequence, derived from
+//! the registered key bindings and the command descriptions.
+use std::time::Duration;
+
+use edtui::{
+    actions::{Action as EdTuiAction, Custom},
+    input::key::Key,
+    state::command::Command,
+    EditorMode, Input,
+};
+use ratatui::{prelude::*, widgets::*};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::PapierAction;
+
+/// The time a key sequence is pending before the popup shows up.
+pub const HINT_DELAY: Duration = Duration::from_millis(300);
+
+/// The keys that continue the pending key sequence and what they do.
+#[derive(Clone, Debug, Default)]
+pub struct KeyHints {
+    hints: Vec<(String, String)>,
+    style: Style,
+    key_style: Style,
+}
+
+impl KeyHints {
+    /// Collects the hints of the pending key sequence, or returns `None`
+    /// if no sequence is pending for at least `delay`, usually
+    /// [`HINT_DELAY`].
+    pub fn new(input: &Input<PapierAction>, mode: EditorMode, delay: Duration) -> Option<Self> {
+        if input.register.pending_for()? < delay {
+            return None;
+        }
+        let commands = &input.command.available_commands;
+        let mut hints = input
+            .register
+            .continuations(mode)
+            .into_iter()
+            .map(|continuation| {
+                let description = match (continuation.action, continuation.followups) {
+                    (Some(action), 0) => describe(action, commands),
+                    (Some(action), n) => format!("{} (+{n})", describe(action, commands)),
+                    (None, n) => format!("+{n} more"),
+                };
+                (Key::from(continuation.key).to_string(), description)
+            })
+            .collect::<Vec<_>>();
+        if hints.is_empty() {
+            return None;
+        }
+        hints.sort();
+        Some(Self { hints, ..Self::default() })
+    }
+
+    /// Sets the style of the popup.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the style of the keys.
+    #[must_use]
+    pub fn key_style(mut self, style: Style) -> Self {
+        self.key_style = style;
+        self
+    }
+}
+
+impl Widget for KeyHints {
+    /// Renders the popup into the bottom right corner of the area, above
+    /// the status line.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let key_width = self.hints.iter().map(|(key, _)| key.chars().count()).max().unwrap_or_default();
+        let line_width = self.hints.iter().map(|(_, description)| key_width + 2 + description.chars().count()).max();
+        let width = (line_width.unwrap_or_default() as u16 + 4).min(area.width);
+        let height = (self.hints.len() as u16 + 2).min(area.height.saturating_sub(1));
+        let popup = Rect {
+            x: area.right().saturating_sub(width + 1),
+            y: area.bottom().saturating_sub(height + 1),
+            width,
+            height,
+        };
+
+        let lines = self.hints.into_iter().map(|(key, description)| {
+            Line::from(vec![
+                Span::styled(format!("{key:>key_width$}"), self.key_style),
+                Span::raw("  "),
+                description.into(),
+            ])
+        });
+        Clear.render(popup, buf);
+        Paragraph::new(lines.collect::<Vec<_>>())
+            .block(Block::bordered().padding(Padding::horizontal(1)))
+            .style(self.style)
+            .render(popup, buf);
+    }
+}
+
+/// Describes an action with the description of the command that triggers
+/// the same action, or with the name of the action.
+fn describe(action: &EdTuiAction<PapierAction>, commands: &[Command<PapierAction>]) -> String {
+    if let EdTuiAction::Custom(Custom(custom)) = action {
+        let name = variant_name(custom);
+        let command = commands.iter().find(|command| variant_name(&(command.action)(String::new())) == name);
+        return command.map_or_else(|| humanize(&name), |command| command.description.clone());
+    }
+    match serde_json::to_value(action) {
+        Ok(JsonValue::Object(map)) => map.get("action").and_then(JsonValue::as_str).map(humanize).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Returns the name of the enum variant a value serializes to.
+fn variant_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(JsonValue::String(name)) => name,
+        Ok(JsonValue::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Splits a name like `MoveToFirstLine` into lowercase words.
+fn humanize(name: &str) -> String {
+    let mut words = String::new();
+    for c in name.chars() {
+        if c.is_uppercase() && !words.is_empty() {
+            words.push(' ');
+        }
+        words.extend(c.to_lowercase());
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use edtui::{
+        actions::{motion::MoveToFirstLine, DeleteLine},
+        input::register::RegisterKey,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let commands =
+            vec![Command::new("save".into(), "Save the current file".into(), vec![], |_| PapierAction::Save)];
+        assert_eq!(describe(&Custom(PapierAction::Save).into(), &commands), "Save the current file");
+        assert_eq!(describe(&Custom(PapierAction::ToggleLogger).into(), &commands), "toggle logger");
+        assert_eq!(describe(&MoveToFirstLine().into(), &commands), "move to first line");
+    }
+
+    #[test]
+    fn test_hints() {
+        let mut input = Input::<PapierAction>::default();
+        input.register.set_timeout(Duration::from_secs(60));
+        input.register.insert(RegisterKey::n(vec![Key::char(' '), Key::char('d')]), DeleteLine(1));
+        input.register.insert(RegisterKey::n(vec![Key::char(' '), Key::char('b'), Key::char('n')]), DeleteLine(1));
+        assert!(input.register.get(Key::char(' ').into(), EditorMode::Normal).is_empty());
+        assert!(KeyHints::new(&input, EditorMode::Normal, HINT_DELAY).is_none());
+
+        let hints = KeyHints::new(&input, EditorMode::Normal, Duration::ZERO).unwrap().hints;
+        let expected = [("b", "+1 more"), ("d", "delete line")].map(|(k, d)| (k.to_string(), d.to_string()));
+        assert_eq!(hints, expected);
+    }
+}
@@ -0,0 +1,235 @@
+//! A popup to pick an item by typing parts of it, e.g. a recent file. The
+//! typed characters match the items fuzzily, in order but not necessarily
+//! next to each other.
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*};
+
+/// What the user did in the picker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickerEvent {
+    /// Picked the item at the index.
+    Pick(usize),
+    Cancel,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Picker {
+    title: String,
+    items: Vec<String>,
+    query: String,
+    /// The indices of the items matching the query and the positions of the
+    /// matching characters, the best match first.
+    matches: Vec<(usize, Vec<usize>)>,
+    /// The index of the selected match.
+    selected: usize,
+}
+
+impl Picker {
+    #[must_use]
+    pub fn new(title: impl Into<String>, items: Vec<String>) -> Self {
+        let mut picker = Self { title: title.into(), items, ..Self::default() };
+        picker.update();
+        picker
+    }
+
+    /// Handles a key, typing into the query or moving the selection.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<PickerEvent> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Some(PickerEvent::Cancel),
+            KeyCode::Enter => return self.matches.get(self.selected).map(|(index, _)| PickerEvent::Pick(*index)),
+            KeyCode::Up | KeyCode::BackTab => self.select(-1),
+            KeyCode::Char('p' | 'k') if ctrl => self.select(-1),
+            KeyCode::Down | KeyCode::Tab => self.select(1),
+            KeyCode::Char('n' | 'j') if ctrl => self.select(1),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update();
+            },
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.update();
+            },
+            _ => {},
+        }
+        None
+    }
+
+    /// Returns the indices of the matching items, the best match first.
+    pub fn matches(&self) -> impl Iterator<Item = usize> + '_ {
+        self.matches.iter().map(|(index, _)| *index)
+    }
+
+    /// Moves the selection, wrapping around.
+    fn select(&mut self, delta: isize) {
+        if !self.matches.is_empty() {
+            self.selected = (self.selected as isize + delta).rem_euclid(self.matches.len() as isize) as usize;
+        }
+    }
+
+    /// Matches the items against the query. Equal scores keep the order of
+    /// the items.
+    fn update(&mut self) {
+        let mut matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                fuzzy_match(&self.query, item).map(|(score, positions)| (score, index, positions))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(score, index, _)| (-score, *index));
+        self.matches = matches.into_iter().map(|(_, index, positions)| (index, positions)).collect();
+        self.selected = 0;
+    }
+}
+
+/// Matches the characters of `query` in order against `candidate`, ignoring
+/// case. Returns a score, higher for consecutive characters and characters at
+/// the start of words, and the char positions of the matching characters.
+/// An empty query matches everything equally.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let mut positions = Vec::new();
+    let mut score = 0;
+    let mut chars = candidate.chars().enumerate();
+    let mut previous: Option<(usize, char)> = None;
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let (position, c) = loop {
+            let (position, c) = chars.next()?;
+            if c.to_lowercase().eq(std::iter::once(q)) {
+                break (position, c);
+            }
+            previous = Some((position, c));
+        };
+        score += 1;
+        match previous {
+            Some((before, _)) if positions.last() == Some(&before) => score += 5,
+            None => score += 8,
+            Some((_, '/' | '\\' | '_' | '-' | '.' | ' ')) => score += 8,
+            Some((_, lower)) if lower.is_lowercase() && c.is_uppercase() => score += 8,
+            _ => {},
+        }
+        positions.push(position);
+        previous = Some((position, c));
+    }
+    Some((score * 100 - candidate.chars().count() as i64, positions))
+}
+
+/// Renders a [`Picker`] as a popup in the middle of the area.
+pub struct PickerView<'a> {
+    picker: &'a Picker,
+    style: Style,
+    selected_style: Style,
+    match_style: Style,
+}
+
+impl<'a> PickerView<'a> {
+    #[must_use]
+    pub fn new(picker: &'a Picker) -> Self {
+        Self { picker, style: Style::default(), selected_style: Style::default(), match_style: Style::default() }
+    }
+
+    /// Sets the style of the popup.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the style of the selected item.
+    #[must_use]
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    /// Sets the style of the characters matching the query.
+    #[must_use]
+    pub fn match_style(mut self, style: Style) -> Self {
+        self.match_style = style;
+        self
+    }
+}
+
+impl Widget for PickerView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let picker = self.picker;
+        let width = (area.width * 3 / 4).max(area.width.min(40));
+        let height = (area.height * 3 / 4).max(area.height.min(5));
+        let popup =
+            Rect { x: area.x + (area.width - width) / 2, y: area.y + (area.height - height) / 2, width, height };
+        let block = Block::bordered().title(format!(" {} ", picker.title)).title_bottom(format!(
+            " {}/{} ",
+            picker.matches.len(),
+            picker.items.len()
+        ));
+        let inner = block.inner(popup);
+        Clear.render(popup, buf);
+        block.style(self.style).render(popup, buf);
+        if inner.height == 0 {
+            return;
+        }
+
+        Line::from(format!("> {}", picker.query)).render(Rect { height: 1, ..inner }, buf);
+        let rows = inner.height.saturating_sub(1) as usize;
+        let first = (picker.selected + 1).saturating_sub(rows);
+        let lines = picker.matches.iter().enumerate().skip(first).take(rows).map(|(i, (index, positions))| {
+            let spans = picker.items[*index].chars().enumerate().map(|(position, c)| {
+                let style = if positions.contains(&position) { self.match_style } else { Style::default() };
+                Span::styled(c.to_string(), style)
+            });
+            let line = Line::from(spans.collect::<Vec<_>>());
+            if i == picker.selected {
+                line.style(self.selected_style)
+            } else {
+                line
+            }
+        });
+        let list = Rect { y: inner.y + 1, height: inner.height - 1, ..inner };
+        for (line, row) in lines.zip(list.rows()) {
+            line.render(row, buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("", "main.rs").unwrap().1, Vec::<usize>::new());
+        assert_eq!(fuzzy_match("mrs", "src/main.rs").unwrap().1, vec![4, 9, 10]);
+        assert_eq!(fuzzy_match("MAIN", "src/main.rs").unwrap().1, vec![4, 5, 6, 7]);
+        assert!(fuzzy_match("rsm", "src/main.rs").is_none());
+
+        // Consecutive characters and word starts score higher
+        let score = |candidate| fuzzy_match("ed", candidate).unwrap().0;
+        assert!(score("src/editor.rs") > score("src/shared.rs"));
+        assert!(score("edit.rs") > score("src/edit.rs"));
+    }
+
+    #[test]
+    fn test_picker() {
+        let items = ["src/main.rs", "src/app.rs", "README.md"].map(String::from).to_vec();
+        let mut picker = Picker::new("Recent files", items);
+        assert_eq!(picker.matches().collect::<Vec<_>>(), [0, 1, 2]);
+
+        for c in "app".chars() {
+            picker.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert_eq!(picker.matches().collect::<Vec<_>>(), [1]);
+        picker.handle_key(KeyEvent::from(KeyCode::Backspace));
+        picker.handle_key(KeyEvent::from(KeyCode::Backspace));
+        // "a" starts a word only in "app.rs"
+        assert_eq!(picker.matches().collect::<Vec<_>>(), [1, 2, 0]);
+        picker.handle_key(KeyEvent::from(KeyCode::Down));
+        assert_eq!(picker.handle_key(KeyEvent::from(KeyCode::Enter)), Some(PickerEvent::Pick(2)));
+        assert_eq!(picker.handle_key(KeyEvent::from(KeyCode::Esc)), Some(PickerEvent::Cancel));
+    }
+}
@@ -0,0 +1,255 @@
+//! The tab line above the windows. It lists the buffers or tab pages and
+//! scrolls to keep the active one visible.
+use std::path::Path;
+
+use ratatui::{prelude::*, widgets::*};
+
+/// The marker of a tab with modified buffers.
+const MODIFIED: &str = " +";
+
+/// The close button of a tab.
+const CLOSE: &str = " ×";
+
+/// The indicators of tabs hidden left and right of the tab line.
+const OVERFLOW: (&str, &str) = ("<", ">");
+
+/// A tab of the tab line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tab {
+    pub label: String,
+    pub modified: bool,
+}
+
+impl Tab {
+    #[must_use]
+    pub fn new(label: String, modified: bool) -> Self {
+        Self { label, modified }
+    }
+}
+
+/// The screen area of a visible tab and of its close button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TabArea {
+    pub index: usize,
+    pub area: Rect,
+    pub close: Option<Rect>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TabLine {
+    tabs: Vec<Tab>,
+    active: usize,
+    style: Style,
+    active_style: Style,
+    modified_style: Style,
+    overflow_style: Style,
+    close_style: Style,
+    close_button: bool,
+}
+
+impl TabLine {
+    #[must_use]
+    pub fn new(tabs: Vec<Tab>, active: usize) -> Self {
+        Self { tabs, active, ..Self::default() }
+    }
+
+    /// Sets the style of the tabs.
+    #[must_use]
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the style of the active tab.
+    #[must_use]
+    pub fn active_style(mut self, style: Style) -> Self {
+        self.active_style = style;
+        self
+    }
+
+    /// Sets the style of the modified marker.
+    #[must_use]
+    pub fn modified_style(mut self, style: Style) -> Self {
+        self.modified_style = style;
+        self
+    }
+
+    /// Sets the style of the overflow indicators.
+    #[must_use]
+    pub fn overflow_style(mut self, style: Style) -> Self {
+        self.overflow_style = style;
+        self
+    }
+
+    /// Sets the style of the close buttons.
+    #[must_use]
+    pub fn close_style(mut self, style: Style) -> Self {
+        self.close_style = style;
+        self
+    }
+
+    /// Shows a close button in each tab.
+    #[must_use]
+    pub fn close_button(mut self, close_button: bool) -> Self {
+        self.close_button = close_button;
+        self
+    }
+
+    /// Returns the text of a tab, without the marker and the close button.
+    fn text(&self, index: usize) -> String {
+        format!(" {}: {}", index + 1, self.tabs[index].label)
+    }
+
+    /// Returns the width of a tab.
+    fn width(&self, index: usize) -> u16 {
+        let mut width = self.text(index).chars().count() + 1;
+        if self.tabs[index].modified {
+            width += MODIFIED.chars().count();
+        }
+        if self.close_button {
+            width += CLOSE.chars().count();
+        }
+        width as u16
+    }
+
+    /// Returns the areas of the visible tabs. If the tabs do not fit, the
+    /// line scrolls such that the active tab is visible and leaves a column
+    /// on each side for the overflow indicators.
+    #[must_use]
+    pub fn areas(&self, area: Rect) -> Vec<TabArea> {
+        let widths = (0..self.tabs.len()).map(|i| self.width(i)).collect::<Vec<_>>();
+        let (first, mut x, right) = if widths.iter().sum::<u16>() <= area.width {
+            (0, area.x, area.right())
+        } else {
+            let available = area.width.saturating_sub(2);
+            let active = self.active.min(widths.len().saturating_sub(1));
+            let first = (0..=active).find(|&first| widths[first..=active].iter().sum::<u16>() <= available);
+            (first.unwrap_or(active), area.x + 1, area.right().saturating_sub(1))
+        };
+
+        let mut areas = Vec::new();
+        for (index, &width) in widths.iter().enumerate().skip(first) {
+            if x + width > right && index != self.active {
+                break;
+            }
+            let tab = Rect::new(x, area.y, width.min(right.saturating_sub(x)), 1.min(area.height));
+            let close = self.close_button.then(|| Rect::new(x + width - 2, area.y, 1, 1)).filter(|c| c.x < right);
+            areas.push(TabArea { index, area: tab, close });
+            x += width;
+        }
+        areas
+    }
+}
+
+impl Widget for TabLine {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let areas = self.areas(area);
+        buf.set_style(area, self.style);
+        for tab in &areas {
+            let style = if tab.index == self.active { self.active_style } else { self.style };
+            let mut spans = vec![Span::styled(self.text(tab.index), style)];
+            if self.tabs[tab.index].modified {
+                spans.push(Span::styled(MODIFIED, style.patch(self.modified_style)));
+            }
+            if self.close_button {
+                spans.push(Span::styled(CLOSE, style.patch(self.close_style)));
+            }
+            spans.push(Span::styled(" ", style));
+            Line::from(spans).render(tab.area, buf);
+        }
+
+        let (Some(first), Some(last)) = (areas.first(), areas.last()) else {
+            return;
+        };
+        if first.index > 0 {
+            buf.set_string(area.x, area.y, OVERFLOW.0, self.overflow_style);
+        }
+        if last.index + 1 < self.tabs.len() {
+            buf.set_string(area.right().saturating_sub(1), area.y, OVERFLOW.1, self.overflow_style);
+        }
+    }
+}
+
+/// Labels files by their name, with as many parent directories as needed to
+/// tell files with the same name apart, e.g. `src/main.rs` and
+/// `tests/main.rs`.
+pub fn unique_labels(paths: &[&Path]) -> Vec<String> {
+    let components = paths
+        .iter()
+        .map(|path| path.iter().rev().map(|c| c.to_string_lossy().to_string()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let label = |i: usize, depth: usize| {
+        let parts = &components[i][..depth.min(components[i].len())];
+        parts.iter().rev().cloned().collect::<Vec<_>>().join("/")
+    };
+
+    let mut depths = vec![1; paths.len()];
+    loop {
+        let labels = (0..paths.len()).map(|i| label(i, depths[i])).collect::<Vec<_>>();
+        let mut changed = false;
+        for (i, depth) in depths.iter_mut().enumerate() {
+            let duplicate = labels.iter().enumerate().any(|(j, other)| j != i && *other == labels[i]);
+            if duplicate && *depth < components[i].len() {
+                *depth += 1;
+                changed = true;
+            }
+        }
+        if !changed {
+            return labels;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn tabs(n: usize) -> Vec<Tab> {
+        (0..n).map(|i| Tab::new(format!("tab{i}"), i == 1)).collect()
+    }
+
+    #[test]
+    fn test_areas() {
+        // " 1: tab0 " is 9 columns wide, " 2: tab1 + " 11 columns
+        let line = TabLine::new(tabs(3), 0);
+        let areas = line.areas(Rect::new(0, 0, 40, 1));
+        assert_eq!(areas.iter().map(|tab| (tab.area.x, tab.area.width)).collect::<Vec<_>>(), [
+            (0, 9),
+            (9, 11),
+            (20, 9)
+        ]);
+        assert_eq!(areas[0].close, None);
+
+        let areas = TabLine::new(tabs(3), 0).close_button(true).areas(Rect::new(0, 0, 40, 1));
+        assert_eq!(areas[1].close, Some(Rect::new(22, 0, 1, 1)));
+    }
+
+    #[test]
+    fn test_scroll_to_active() {
+        let area = Rect::new(0, 0, 30, 1);
+        let areas = TabLine::new(tabs(10), 0).areas(area);
+        assert_eq!(areas.iter().map(|tab| tab.index).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(areas[0].area.x, 1);
+
+        let areas = TabLine::new(tabs(10), 7).areas(area);
+        assert_eq!(areas.iter().map(|tab| tab.index).collect::<Vec<_>>(), [5, 6, 7]);
+    }
+
+    #[test]
+    fn test_render_overflow() {
+        let area = Rect::new(0, 0, 30, 1);
+        let mut buf = Buffer::empty(area);
+        TabLine::new(tabs(10), 7).render(area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(vec!["< 6: tab5  7: tab6  8: tab7  >"]));
+    }
+
+    #[test]
+    fn test_unique_labels() {
+        let paths = ["src/main.rs", "tests/main.rs", "lib.rs", "a/src/mod.rs", "b/src/mod.rs"].map(Path::new);
+        let labels = unique_labels(&paths);
+        assert_eq!(labels, ["src/main.rs", "tests/main.rs", "lib.rs", "a/src/mod.rs", "b/src/mod.rs"]);
+        assert_eq!(unique_labels(&[Path::new("src/main.rs"), Path::new("lib.rs")]), ["main.rs", "lib.rs"]);
+    }
+}
@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     fmt::{self, Debug, Display},
     path::PathBuf,
+    time::Duration,
 };
 
 use color_eyre::eyre::Result;
@@ -9,9 +10,14 @@ use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
 use edtui::{
     actions::{Action as EdTuiAction, Execute},
-    input::{key::Key, register::RegisterKey},
-    EditorMode, Input,
+    highlight::SynopticHighlighter,
+    input::{
+        key::Key,
+        register::{RegisterKey, DEFAULT_TIMEOUT},
+    },
+    EditorMode, EditorState, Input,
 };
+use encoding_rs::{Encoding, WINDOWS_1252};
 use log::debug;
 use ratatui::style::{Color, Modifier, Style};
 use serde::{
@@ -20,7 +26,10 @@ use serde::{
 };
 use serde_json::Value as JsonValue;
 
-use crate::PapierAction;
+use crate::{
+    components::editor::file::{self, Decoding},
+    PapierAction,
+};
 
 const CONFIG: &str = include_str!("../.config/config.toml");
 
@@ -40,6 +49,62 @@ pub struct Config {
     pub keybindings: KeyBindings,
     #[serde(default)]
     pub styles: Styles,
+    #[serde(default)]
+    pub highlighter: Highlighters,
+    /// The color scheme, see [`crate::theme::load_colorscheme`].
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// The milliseconds to wait for the next key of an ambiguous key sequence.
+    #[serde(default)]
+    pub timeoutlen: Option<u64>,
+    /// The key of `<leader>` in the key bindings.
+    #[serde(default)]
+    pub leader: Option<String>,
+    /// Whether the tabs in the tab line have a close button.
+    #[serde(default)]
+    pub tab_close_button: bool,
+    /// Whether saving a file keeps a backup of the previous file, e.g.
+    /// `main.rs~`.
+    #[serde(default)]
+    pub backup: bool,
+    /// Whether a line ending is added to the last line of a file that has
+    /// none, true by default.
+    #[serde(default)]
+    pub fixeol: Option<bool>,
+    /// The encoding of files that are neither UTF-8 nor UTF-16, `latin1` by
+    /// default.
+    #[serde(default)]
+    pub fallback_encoding: Option<String>,
+    /// The milliseconds without a key press after which recovery files of
+    /// modified buffers are written, 4000 by default.
+    #[serde(default)]
+    pub updatetime: Option<u64>,
+    /// The milliseconds between checks whether open files changed on disk,
+    /// 1000 by default. The files are only polled when they cannot be
+    /// watched.
+    #[serde(default)]
+    pub check_interval: Option<u64>,
+    #[serde(default)]
+    pub autosave: AutoSave,
+    /// Whether the session of the working directory is saved on quit and
+    /// restored when no files are given.
+    #[serde(default)]
+    pub restore_session: bool,
+}
+
+/// When modified buffers are saved automatically. Buffers without a file
+/// and read-only buffers are never saved.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AutoSave {
+    /// Saves all buffers when the terminal loses the focus.
+    #[serde(default)]
+    pub focus_lost: bool,
+    /// Saves the buffer a window switches away from.
+    #[serde(default)]
+    pub buffer_switch: bool,
+    /// Saves all buffers after the seconds without a key press.
+    #[serde(default)]
+    pub idle: Option<u64>,
 }
 
 impl Config {
@@ -77,20 +142,83 @@ impl Config {
                 user_bindings.entry(key.clone()).or_insert_with(|| cmd.clone());
             }
         }
-        for (mode, default_styles) in default_config.styles.iter() {
-            let user_styles = cfg.styles.entry(*mode).or_default();
-            for (style_key, style) in default_styles.iter() {
-                user_styles.entry(style_key.clone()).or_insert_with(|| *style);
-            }
+        cfg.styles.merge_defaults(&default_config.styles);
+        for (ext, backend) in default_config.highlighter.iter() {
+            cfg.highlighter.entry(ext.clone()).or_insert(*backend);
         }
+        let leader = cfg.leader().map_err(|err| config::ConfigError::Message(format!("invalid leader: {err}")))?;
+        cfg.keybindings.set_leader(leader);
+        cfg.fallback_encoding().map_err(config::ConfigError::Message)?;
 
         Ok(cfg)
     }
+
+    /// Returns the built-in configuration, used if the configuration files
+    /// cannot be loaded.
+    pub fn builtin() -> Self {
+        let mut config: Config = toml::from_str(CONFIG).unwrap();
+        config.keybindings.set_leader(Key::char(' ').into());
+        config
+    }
+
+    /// Returns the configured `timeoutlen`.
+    pub fn timeout(&self) -> Duration {
+        self.timeoutlen.map_or(DEFAULT_TIMEOUT, Duration::from_millis)
+    }
+
+    /// Returns the configured `fixeol`.
+    pub fn fixeol(&self) -> bool {
+        self.fixeol.unwrap_or(true)
+    }
+
+    /// Returns the configured `updatetime`.
+    pub fn updatetime(&self) -> Duration {
+        Duration::from_millis(self.updatetime.unwrap_or(4000))
+    }
+
+    /// Returns the configured `check_interval`.
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_millis(self.check_interval.unwrap_or(1000))
+    }
+
+    /// Returns the configured `fallback_encoding`.
+    pub fn fallback_encoding(&self) -> Result<&'static Encoding, String> {
+        self.fallback_encoding.as_deref().map_or(Ok(WINDOWS_1252), file::encoding_for_label)
+    }
+
+    /// Returns how files are decoded, with `encoding` or a detected one.
+    pub fn decoding(&self, encoding: Option<&'static Encoding>) -> Decoding {
+        Decoding { encoding, fallback: self.fallback_encoding().unwrap_or(WINDOWS_1252) }
+    }
+
+    /// Returns the configured `<leader>` key, space by default.
+    pub fn leader(&self) -> Result<KeyEvent, String> {
+        self.leader.as_deref().map_or(Ok(Key::char(' ').into()), parse_key_event)
+    }
 }
 
+/// Stands in for `<leader>` in parsed key sequences until
+/// [`KeyBindings::set_leader`] replaces it with the configured key.
+pub const LEADER: KeyEvent = KeyEvent::new(KeyCode::Null, KeyModifiers::NONE);
+
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct KeyBindings(pub HashMap<EditorMode, HashMap<Vec<KeyEvent>, EdTuiAction<PapierAction>>>);
 
+impl KeyBindings {
+    /// Replaces `<leader>` in all key sequences.
+    pub fn set_leader(&mut self, leader: KeyEvent) {
+        for bindings in self.0.values_mut() {
+            *bindings = bindings
+                .drain()
+                .map(|(keys, action)| {
+                    let keys = keys.into_iter().map(|key| if key == LEADER { leader } else { key }).collect();
+                    (keys, action)
+                })
+                .collect();
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for KeyBindings {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -101,11 +229,13 @@ impl<'de> Deserialize<'de> for KeyBindings {
         let keybindings = parsed_map
             .into_iter()
             .map(|(mode, inner_map)| {
-                let converted_inner_map =
-                    inner_map.into_iter().map(|(key_str, cmd)| (parse_key_sequence(&key_str).unwrap(), cmd)).collect();
-                (mode, converted_inner_map)
+                let converted_inner_map = inner_map
+                    .into_iter()
+                    .map(|(key_str, cmd)| Ok((parse_key_sequence(&key_str).map_err(de::Error::custom)?, cmd)))
+                    .collect::<Result<_, D::Error>>()?;
+                Ok((mode, converted_inner_map))
             })
-            .collect();
+            .collect::<Result<_, D::Error>>()?;
 
         log::debug!("Keybindings: {:#?}", keybindings);
 
@@ -134,286 +264,294 @@ impl From<KeyBindings> for Input<PapierAction> {
     }
 }
 
-fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
-    let raw_lower = raw.to_ascii_lowercase();
-    let (remaining, modifiers) = extract_modifiers(&raw_lower);
-    parse_key_code_with_modifiers(remaining, modifiers)
+/// Parses a key like `ctrl-r`, see [`Key`].
+pub fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
+    raw.parse::<Key>().map(KeyEvent::from).map_err(|err| err.to_string())
 }
 
-fn extract_modifiers(raw: &str) -> (&str, KeyModifiers) {
-    let mut modifiers = KeyModifiers::empty();
-    let mut current = raw;
+/// Formats a key such that [`parse_key_event`] parses it back.
+pub fn key_event_to_string(key_event: &KeyEvent) -> String {
+    Key::from(*key_event).to_string()
+}
 
-    loop {
-        match current {
-            rest if rest.starts_with("ctrl-") => {
-                modifiers.insert(KeyModifiers::CONTROL);
-                current = &rest[5..];
-            },
-            rest if rest.starts_with("alt-") => {
-                modifiers.insert(KeyModifiers::ALT);
-                current = &rest[4..];
-            },
-            rest if rest.starts_with("shift-") => {
-                modifiers.insert(KeyModifiers::SHIFT);
-                current = &rest[6..];
-            },
-            _ => break, // break out of the loop if no known prefix is detected
+/// Parses a key sequence. A key like `ctrl-w` stands alone, in a sequence
+/// keys are written in angle brackets and characters as is, e.g.
+/// `<ctrl-w>j` or `<leader>ff`. `<leader>` parses to [`LEADER`].
+pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
+    if !raw.contains('<') {
+        return parse_key_event(raw).map(|key| vec![key]);
+    }
+    let mut keys = Vec::new();
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        let key = if c == '<' {
+            let name = chars.by_ref().take_while(|c| *c != '>').collect::<String>();
+            match name.to_lowercase().as_str() {
+                "leader" => LEADER,
+                _ => parse_key_event(&name)?,
+            }
+        } else {
+            parse_key_event(&c.to_string())?
         };
+        keys.push(key);
     }
+    if raw.matches('<').count() != raw.matches('>').count() {
+        return Err(format!("Unable to parse `{}`", raw));
+    }
+    Ok(keys)
+}
 
-    (current, modifiers)
+/// Styles by key, e.g. `cursor` or `syntax.keyword`. Nested tables are
+/// flattened into dotted keys. A table named after an [`EditorMode`] overrides
+/// the styles in that mode.
+#[derive(Clone, Debug, Default)]
+pub struct Styles {
+    pub base: HashMap<String, Style>,
+    pub modes: HashMap<EditorMode, HashMap<String, Style>>,
 }
 
-fn parse_key_code_with_modifiers(raw: &str, mut modifiers: KeyModifiers) -> Result<KeyEvent, String> {
-    let c = match raw {
-        "esc" => KeyCode::Esc,
-        "enter" => KeyCode::Enter,
-        "left" => KeyCode::Left,
-        "right" => KeyCode::Right,
-        "up" => KeyCode::Up,
-        "down" => KeyCode::Down,
-        "home" => KeyCode::Home,
-        "end" => KeyCode::End,
-        "pageup" => KeyCode::PageUp,
-        "pagedown" => KeyCode::PageDown,
-        "backtab" => {
-            modifiers.insert(KeyModifiers::SHIFT);
-            KeyCode::BackTab
-        },
-        "backspace" => KeyCode::Backspace,
-        "delete" => KeyCode::Delete,
-        "insert" => KeyCode::Insert,
-        "f1" => KeyCode::F(1),
-        "f2" => KeyCode::F(2),
-        "f3" => KeyCode::F(3),
-        "f4" => KeyCode::F(4),
-        "f5" => KeyCode::F(5),
-        "f6" => KeyCode::F(6),
-        "f7" => KeyCode::F(7),
-        "f8" => KeyCode::F(8),
-        "f9" => KeyCode::F(9),
-        "f10" => KeyCode::F(10),
-        "f11" => KeyCode::F(11),
-        "f12" => KeyCode::F(12),
-        "space" => KeyCode::Char(' '),
-        "hyphen" => KeyCode::Char('-'),
-        "minus" => KeyCode::Char('-'),
-        "tab" => KeyCode::Tab,
-        c if c.len() == 1 => {
-            let mut c = c.chars().next().unwrap();
-            if modifiers.contains(KeyModifiers::SHIFT) {
-                c = c.to_ascii_uppercase();
+impl Styles {
+    /// Returns the style of a key in a mode, falling back to the style shared
+    /// by all modes.
+    pub fn get(&self, mode: EditorMode, key: &str) -> Option<Style> {
+        self.modes.get(&mode).and_then(|styles| styles.get(key)).or_else(|| self.base.get(key)).copied()
+    }
+
+    /// Adds the styles of `defaults` that are not set.
+    pub fn merge_defaults(&mut self, defaults: &Styles) {
+        for (key, style) in &defaults.base {
+            self.base.entry(key.clone()).or_insert(*style);
+        }
+        for (mode, default_styles) in &defaults.modes {
+            let styles = self.modes.entry(*mode).or_default();
+            for (key, style) in default_styles {
+                styles.entry(key.clone()).or_insert(*style);
             }
-            KeyCode::Char(c)
-        },
-        _ => return Err(format!("Unable to parse {raw}")),
-    };
-    Ok(KeyEvent::new(c, modifiers))
+        }
+    }
 }
 
-pub fn key_event_to_string(key_event: &KeyEvent) -> String {
-    let char;
-    let key_code = match key_event.code {
-        KeyCode::Backspace => "backspace",
-        KeyCode::Enter => "enter",
-        KeyCode::Left => "left",
-        KeyCode::Right => "right",
-        KeyCode::Up => "up",
-        KeyCode::Down => "down",
-        KeyCode::Home => "home",
-        KeyCode::End => "end",
-        KeyCode::PageUp => "pageup",
-        KeyCode::PageDown => "pagedown",
-        KeyCode::Tab => "tab",
-        KeyCode::BackTab => "backtab",
-        KeyCode::Delete => "delete",
-        KeyCode::Insert => "insert",
-        KeyCode::F(c) => {
-            char = format!("f({c})");
-            &char
-        },
-        KeyCode::Char(' ') => "space",
-        KeyCode::Char(c) => {
-            char = c.to_string();
-            &char
-        },
-        KeyCode::Esc => "esc",
-        KeyCode::Null => "",
-        KeyCode::CapsLock => "",
-        KeyCode::Menu => "",
-        KeyCode::ScrollLock => "",
-        KeyCode::Media(_) => "",
-        KeyCode::NumLock => "",
-        KeyCode::PrintScreen => "",
-        KeyCode::Pause => "",
-        KeyCode::KeypadBegin => "",
-        KeyCode::Modifier(_) => "",
-    };
-
-    let mut modifiers = Vec::with_capacity(3);
-
-    if key_event.modifiers.intersects(KeyModifiers::CONTROL) {
-        modifiers.push("ctrl");
-    }
-
-    if key_event.modifiers.intersects(KeyModifiers::SHIFT) {
-        modifiers.push("shift");
-    }
-
-    if key_event.modifiers.intersects(KeyModifiers::ALT) {
-        modifiers.push("alt");
-    }
-
-    let mut key = modifiers.join("-");
-
-    if !key.is_empty() {
-        key.push('-');
-    }
-    key.push_str(key_code);
-
-    key
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StyleValue {
+    Style(String),
+    Table(HashMap<String, StyleValue>),
 }
 
-pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
-    if raw.chars().filter(|c| *c == '>').count() != raw.chars().filter(|c| *c == '<').count() {
-        return Err(format!("Unable to parse `{}`", raw));
+fn flatten_styles(
+    prefix: &str,
+    table: HashMap<String, StyleValue>,
+    styles: &mut HashMap<String, Style>,
+) -> Result<(), String> {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key } else { format!("{prefix}.{key}") };
+        match value {
+            StyleValue::Style(style) => {
+                let style = parse_style(&style).map_err(|err| format!("invalid style `{key}` = \"{style}\": {err}"))?;
+                styles.insert(key, style);
+            },
+            StyleValue::Table(table) => flatten_styles(&key, table, styles)?,
+        }
     }
-    let raw = if !raw.contains("><") {
-        let raw = raw.strip_prefix('<').unwrap_or(raw);
-        let raw = raw.strip_prefix('>').unwrap_or(raw);
-        raw
-    } else {
-        raw
-    };
-    let sequences = raw
-        .split("><")
-        .map(|seq| {
-            if let Some(s) = seq.strip_prefix('<') {
-                s
-            } else if let Some(s) = seq.strip_suffix('>') {
-                s
-            } else {
-                seq
-            }
-        })
-        .collect::<Vec<_>>();
-
-    sequences.into_iter().map(parse_key_event).collect()
+    Ok(())
 }
 
-#[derive(Clone, Debug, Default, Deref, DerefMut)]
-pub struct Styles(pub HashMap<EditorMode, HashMap<String, Style>>);
-
 impl<'de> Deserialize<'de> for Styles {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let parsed_map = HashMap::<EditorMode, HashMap<String, String>>::deserialize(deserializer)?;
-
-        let styles = parsed_map
-            .into_iter()
-            .map(|(mode, inner_map)| {
-                let converted_inner_map =
-                    inner_map.into_iter().map(|(str, style)| (str, parse_style(&style))).collect();
-                (mode, converted_inner_map)
-            })
-            .collect();
+        let parsed_map = HashMap::<String, StyleValue>::deserialize(deserializer)?;
+
+        let mut styles = Styles::default();
+        let mut base = HashMap::new();
+        for (key, value) in parsed_map {
+            let mode = EditorMode::deserialize(de::value::StrDeserializer::<de::value::Error>::new(&key)).ok();
+            match (mode, value) {
+                (Some(mode), StyleValue::Table(table)) => {
+                    flatten_styles("", table, styles.modes.entry(mode).or_default())
+                        .map_err(|err| de::Error::custom(format!("{err} in [styles.{key}]")))?;
+                },
+                (_, value) => {
+                    base.insert(key, value);
+                },
+            }
+        }
+        flatten_styles("", base, &mut styles.base).map_err(de::Error::custom)?;
 
-        Ok(Styles(styles))
+        Ok(styles)
     }
 }
 
-pub fn parse_style(line: &str) -> Style {
-    let (foreground, background) = line.split_at(line.to_lowercase().find("on ").unwrap_or(line.len()));
-    let foreground = process_color_string(foreground);
-    let background = process_color_string(&background.replace("on ", ""));
+/// A syntax highlighting backend.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HighlighterBackend {
+    #[default]
+    Synoptic,
+    TreeSitter,
+}
+
+/// The highlighting backend per file extension.
+#[derive(Clone, Debug, Default, Deref, DerefMut, Deserialize)]
+pub struct Highlighters(pub HashMap<String, HighlighterBackend>);
+
+impl Highlighters {
+    /// Sets the configured highlighter for a file extension. Falls back to
+    /// `synoptic` if tree-sitter is not compiled in or has no grammar for it.
+    pub fn apply(&self, state: &mut EditorState, ext: &str) {
+        let backend = self.get(ext).copied().unwrap_or_default();
+        #[cfg(feature = "tree-sitter")]
+        if backend == HighlighterBackend::TreeSitter {
+            if let Some(highlighter) = edtui::highlight::TreeSitterHighlighter::new(ext) {
+                state.set_highlighter(highlighter);
+                return;
+            }
+        }
+        if backend != HighlighterBackend::Synoptic {
+            log::warn!("No {backend:?} highlighter available for .{ext} files, using synoptic");
+        }
+        state.set_highlighter(SynopticHighlighter::new(ext));
+    }
+}
 
+/// Parses a style like `bold #ffaa00 on dark gray`.
+///
+/// A style is a list of modifiers and colors. The first color is the
+/// foreground, colors after `on` are the background. Colors are
+///
+/// - the ratatui names, e.g. `red`, `light red` or `dark gray`,
+/// - `bright` names and `bright colorN` for the bright ANSI colors,
+/// - `#rrggbb`, `#rgb` and `rgb(r, g, b)` truecolors,
+/// - `colorN`, `grayN` and `rgbRGB` (each digit 0-5) indexed colors.
+///
+/// The modifiers are `bold`, `dim`, `italic`, `underline`, `undercurl`,
+/// `strikethrough`, `inverse`, `blink` and `hidden`. ratatui has no curly
+/// underline, so `undercurl` is drawn as an underline.
+pub fn parse_style(line: &str) -> Result<Style, String> {
     let mut style = Style::default();
-    if let Some(fg) = parse_color(&foreground.0) {
-        style = style.fg(fg);
+    let mut background = false;
+    let mut prefix: Option<&str> = None;
+    let words = split_style(line);
+    for token in words.iter().map(String::as_str) {
+        if let Some(modifier) = parse_modifier(token) {
+            style = style.add_modifier(modifier);
+            continue;
+        }
+        match (prefix, token) {
+            (None, "on") if !background => {
+                background = true;
+                prefix = None;
+            },
+            (None, "bright" | "light" | "dark") => prefix = Some(token),
+            _ => {
+                let color = match prefix.take() {
+                    Some(prefix) => parse_prefixed_color(prefix, token),
+                    None => parse_color(token),
+                };
+                let color = color.ok_or_else(|| format!("unknown color or modifier `{token}`"))?;
+                let (current, side) = if background { (style.bg, "background") } else { (style.fg, "foreground") };
+                if current.is_some() {
+                    return Err(format!("more than one {side} color"));
+                }
+                style = if background { style.bg(color) } else { style.fg(color) };
+            },
+        }
     }
-    if let Some(bg) = parse_color(&background.0) {
-        style = style.bg(bg);
+    match prefix {
+        Some(prefix) => Err(format!("missing color after `{prefix}`")),
+        None => Ok(style),
     }
-    style = style.add_modifier(foreground.1 | background.1);
-    style
 }
 
-fn process_color_string(color_str: &str) -> (String, Modifier) {
-    let color = color_str
-        .replace("grey", "gray")
-        .replace("bright ", "")
-        .replace("bold ", "")
-        .replace("underline ", "")
-        .replace("inverse ", "");
-
-    let mut modifiers = Modifier::empty();
-    if color_str.contains("underline") {
-        modifiers |= Modifier::UNDERLINED;
+/// Splits a style into lowercase words, keeping `rgb(r, g, b)` in one word.
+fn split_style(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_parens = false;
+    for ch in line.to_lowercase().chars() {
+        match ch {
+            '(' => in_parens = true,
+            ')' => in_parens = false,
+            _ => {},
+        }
+        if ch.is_whitespace() && !in_parens {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        } else if !ch.is_whitespace() {
+            word.push(ch);
+        }
     }
-    if color_str.contains("bold") {
-        modifiers |= Modifier::BOLD;
+    if !word.is_empty() {
+        words.push(word);
     }
-    if color_str.contains("inverse") {
-        modifiers |= Modifier::REVERSED;
+    words
+}
+
+fn parse_modifier(s: &str) -> Option<Modifier> {
+    match s {
+        "bold" => Some(Modifier::BOLD),
+        "dim" => Some(Modifier::DIM),
+        "italic" => Some(Modifier::ITALIC),
+        "underline" | "underlined" | "undercurl" => Some(Modifier::UNDERLINED),
+        "strikethrough" | "crossed_out" => Some(Modifier::CROSSED_OUT),
+        "inverse" | "reversed" => Some(Modifier::REVERSED),
+        "blink" => Some(Modifier::SLOW_BLINK),
+        "hidden" => Some(Modifier::HIDDEN),
+        _ => None,
     }
+}
 
-    (color, modifiers)
+fn parse_prefixed_color(prefix: &str, s: &str) -> Option<Color> {
+    if prefix == "bright" {
+        if let Some(Color::Indexed(c @ 0..=7)) = parse_indexed(s) {
+            return Some(Color::Indexed(c + 8));
+        }
+    }
+    match parse_color(&format!("{prefix}{s}"))? {
+        Color::Indexed(_) | Color::Rgb(..) => None,
+        color => Some(color),
+    }
 }
 
 fn parse_color(s: &str) -> Option<Color> {
-    let s = s.trim_start();
-    let s = s.trim_end();
-    if s.contains("bright color") {
-        let s = s.trim_start_matches("bright ");
-        let c = s.trim_start_matches("color").parse::<u8>().unwrap_or_default();
-        Some(Color::Indexed(c.wrapping_shl(8)))
-    } else if s.contains("color") {
-        let c = s.trim_start_matches("color").parse::<u8>().unwrap_or_default();
-        Some(Color::Indexed(c))
-    } else if s.contains("gray") {
-        let c = 232 + s.trim_start_matches("gray").parse::<u8>().unwrap_or_default();
-        Some(Color::Indexed(c))
-    } else if s.contains("rgb") {
-        let red = (s.as_bytes()[3] as char).to_digit(10).unwrap_or_default() as u8;
-        let green = (s.as_bytes()[4] as char).to_digit(10).unwrap_or_default() as u8;
-        let blue = (s.as_bytes()[5] as char).to_digit(10).unwrap_or_default() as u8;
-        let c = 16 + red * 36 + green * 6 + blue;
-        Some(Color::Indexed(c))
-    } else if s == "bold black" {
-        Some(Color::Indexed(8))
-    } else if s == "bold red" {
-        Some(Color::Indexed(9))
-    } else if s == "bold green" {
-        Some(Color::Indexed(10))
-    } else if s == "bold yellow" {
-        Some(Color::Indexed(11))
-    } else if s == "bold blue" {
-        Some(Color::Indexed(12))
-    } else if s == "bold magenta" {
-        Some(Color::Indexed(13))
-    } else if s == "bold cyan" {
-        Some(Color::Indexed(14))
-    } else if s == "bold white" {
-        Some(Color::Indexed(15))
-    } else if s == "black" {
-        Some(Color::Indexed(0))
-    } else if s == "red" {
-        Some(Color::Indexed(1))
-    } else if s == "green" {
-        Some(Color::Indexed(2))
-    } else if s == "yellow" {
-        Some(Color::Indexed(3))
-    } else if s == "blue" {
-        Some(Color::Indexed(4))
-    } else if s == "magenta" {
-        Some(Color::Indexed(5))
-    } else if s == "cyan" {
-        Some(Color::Indexed(6))
-    } else if s == "white" {
-        Some(Color::Indexed(7))
+    if let Some(hex) = s.strip_prefix('#') {
+        parse_hex(hex)
+    } else if let Some(rgb) = s.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+        let channels = rgb.split(',').map(|c| c.trim().parse::<u8>().ok()).collect::<Option<Vec<_>>>()?;
+        match channels[..] {
+            [r, g, b] => Some(Color::Rgb(r, g, b)),
+            _ => None,
+        }
+    } else if let Some(color) = parse_indexed(s) {
+        Some(color)
+    } else {
+        s.parse::<Color>().ok()
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok();
+    match hex.len() {
+        3 => Some(Color::Rgb(channel(0, 1)? * 17, channel(1, 1)? * 17, channel(2, 1)? * 17)),
+        6 => Some(Color::Rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+        _ => None,
+    }
+}
+
+/// Parses the `colorN`, `grayN` and `rgbRGB` indexed colors.
+fn parse_indexed(s: &str) -> Option<Color> {
+    if let Some(c) = s.strip_prefix("color") {
+        c.parse::<u8>().ok().map(Color::Indexed)
+    } else if let Some(c) = s.strip_prefix("gray").or_else(|| s.strip_prefix("grey")).filter(|c| !c.is_empty()) {
+        c.parse::<u8>().ok().filter(|c| *c < 24).map(|c| Color::Indexed(232 + c))
+    } else if let Some(c) = s.strip_prefix("rgb").filter(|c| c.len() == 3) {
+        let digits = c.chars().map(|c| c.to_digit(6).map(|d| d as u8)).collect::<Option<Vec<_>>>()?;
+        Some(Color::Indexed(16 + digits[0] * 36 + digits[1] * 6 + digits[2]))
     } else {
         None
     }
@@ -427,36 +565,70 @@ mod tests {
 
     #[test]
     fn test_parse_style_default() {
-        let style = parse_style("");
+        let style = parse_style("").unwrap();
         assert_eq!(style, Style::default());
     }
 
     #[test]
     fn test_parse_style_foreground() {
-        let style = parse_style("red");
-        assert_eq!(style.fg, Some(Color::Indexed(1)));
+        let style = parse_style("red").unwrap();
+        assert_eq!(style.fg, Some(Color::Red));
     }
 
     #[test]
     fn test_parse_style_background() {
-        let style = parse_style("on blue");
-        assert_eq!(style.bg, Some(Color::Indexed(4)));
+        let style = parse_style("on blue").unwrap();
+        assert_eq!(style.bg, Some(Color::Blue));
     }
 
     #[test]
     fn test_parse_style_modifiers() {
-        let style = parse_style("underline red on blue");
-        assert_eq!(style.fg, Some(Color::Indexed(1)));
-        assert_eq!(style.bg, Some(Color::Indexed(4)));
+        let style = parse_style("underline red on blue").unwrap();
+        assert_eq!(style.fg, Some(Color::Red));
+        assert_eq!(style.bg, Some(Color::Blue));
+        assert!(style.add_modifier.contains(Modifier::UNDERLINED));
+
+        let style = parse_style("Bold dim italic undercurl strikethrough inverse gray").unwrap();
+        assert_eq!(style.fg, Some(Color::Gray));
+        let expected = Modifier::BOLD
+            | Modifier::DIM
+            | Modifier::ITALIC
+            | Modifier::UNDERLINED
+            | Modifier::CROSSED_OUT
+            | Modifier::REVERSED;
+        assert_eq!(style.add_modifier, expected);
+    }
+
+    #[test]
+    fn test_parse_style_named() {
+        let style = parse_style("light red on dark gray").unwrap();
+        assert_eq!((style.fg, style.bg), (Some(Color::LightRed), Some(Color::DarkGray)));
+        assert_eq!(parse_style("bright blue").unwrap().fg, Some(Color::LightBlue));
+        assert_eq!(parse_style("on reset").unwrap().bg, Some(Color::Reset));
+    }
+
+    #[test]
+    fn test_parse_style_truecolor() {
+        let style = parse_style("#ff8000 on rgb(1, 2, 3)").unwrap();
+        assert_eq!((style.fg, style.bg), (Some(Color::Rgb(255, 128, 0)), Some(Color::Rgb(1, 2, 3))));
+        assert_eq!(parse_style("#F80").unwrap().fg, Some(Color::Rgb(255, 136, 0)));
+    }
+
+    #[test]
+    fn test_parse_style_errors() {
+        assert_eq!(parse_style("bold purple"), Err("unknown color or modifier `purple`".to_string()));
+        assert_eq!(parse_style("#12345"), Err("unknown color or modifier `#12345`".to_string()));
+        assert_eq!(parse_style("rgb(1, 2)"), Err("unknown color or modifier `rgb(1,2)`".to_string()));
+        assert_eq!(parse_style("red blue"), Err("more than one foreground color".to_string()));
+        assert_eq!(parse_style("red on bright"), Err("missing color after `bright`".to_string()));
     }
 
     #[test]
-    fn test_process_color_string() {
-        let (color, modifiers) = process_color_string("underline bold inverse gray");
-        assert_eq!(color, "gray");
-        assert!(modifiers.contains(Modifier::UNDERLINED));
-        assert!(modifiers.contains(Modifier::BOLD));
-        assert!(modifiers.contains(Modifier::REVERSED));
+    fn test_parse_color_indexed() {
+        assert_eq!(parse_color("color42"), Some(Color::Indexed(42)));
+        assert_eq!(parse_color("gray3"), Some(Color::Indexed(235)));
+        assert_eq!(parse_style("bright color3").unwrap().fg, Some(Color::Indexed(11)));
+        assert!(parse_style("bright color12").is_err());
     }
 
     #[test]
@@ -473,6 +645,71 @@ mod tests {
         assert_eq!(color, None);
     }
 
+    #[test]
+    fn test_styles_error() {
+        let err = toml::from_str::<Config>("[styles.insert]\ncursor = \"bold purple\"").unwrap_err();
+        assert!(err.to_string().contains(
+            "invalid style `cursor` = \"bold purple\": unknown color or modifier `purple` in [styles.insert]"
+        ));
+    }
+
+    #[test]
+    fn test_styles() {
+        let config: Config = toml::from_str(
+            r#"
+            [styles]
+            cursor = "black on white"
+            syntax = { keyword = "green" }
+            [styles.insert]
+            cursor = "underline"
+            "#,
+        )
+        .unwrap();
+        let styles = config.styles;
+        assert_eq!(styles.get(EditorMode::Normal, "cursor"), parse_style("black on white").ok());
+        assert_eq!(styles.get(EditorMode::Insert, "cursor"), parse_style("underline").ok());
+        assert_eq!(styles.get(EditorMode::Insert, "syntax.keyword"), parse_style("green").ok());
+        assert_eq!(styles.get(EditorMode::Normal, "selection"), None);
+    }
+
+    #[test]
+    fn test_key_event_round_trip() {
+        for raw in ["a", "G", "ctrl-r", "alt-enter", "shift-tab", "backtab", "delete", "home", "end", "f5", "space"] {
+            let key = parse_key_event(raw).unwrap();
+            assert_eq!(key_event_to_string(&key), raw);
+            assert_eq!(parse_key_event(&key_event_to_string(&key)), Ok(key));
+        }
+        assert_eq!(parse_key_sequence("<ctrl-w><G>"), Ok(vec![Key::ctrl('w').into(), Key::char('G').into()]));
+    }
+
+    #[test]
+    fn test_leader() {
+        let keys = parse_key_sequence("<leader>bn").unwrap();
+        assert_eq!(keys, vec![LEADER, Key::char('b').into(), Key::char('n').into()]);
+        assert!(parse_key_sequence("<leader").is_err());
+
+        let mut config: Config = toml::from_str(
+            r#"
+            leader = ","
+            [keybindings.normal]
+            "<leader>w" = { action = "Custom", payload = "Save" }
+            "#,
+        )
+        .unwrap();
+        let leader = config.leader().unwrap();
+        config.keybindings.set_leader(leader);
+        let keys = vec![Key::char(',').into(), Key::char('w').into()];
+        assert!(config.keybindings[&EditorMode::Normal].contains_key(&keys));
+        assert_eq!(Config::default().leader(), Ok(Key::char(' ').into()));
+    }
+
+    #[test]
+    fn test_default_highlighters() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert_eq!(config.highlighter.get("rs"), Some(&HighlighterBackend::TreeSitter));
+        assert_eq!(config.highlighter.get("md"), None);
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(parse_key_event("a").unwrap(), KeyEvent::new(KeyCode::Char('a'), KeyModifiers::empty()));
@@ -7,6 +7,7 @@ pub mod app;
 pub mod cli;
 pub mod components;
 pub mod config;
+pub mod theme;
 pub mod tui;
 pub mod utils;
 
@@ -18,6 +19,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     app::App,
+    components::editor::WindowDirection,
     utils::{initialize_logging, initialize_panic_handler, version},
 };
 
@@ -25,19 +27,65 @@ use crate::{
 pub enum PapierAction {
     #[default]
     Quit,
+    /// Quits, discarding the changes of the buffer.
+    ForceQuit,
+    /// Saves the buffer and quits.
+    WriteQuit,
+    /// Saves the buffer if it is modified and quits.
+    Exit,
     QuitAll,
+    ForceQuitAll,
+    WriteQuitAll,
     Save,
+    /// Saves the buffer, even if its file changed on disk.
+    ForceSave,
     SaveAll,
     SaveAs(String),
+    ColorScheme(String),
     PreviousBuffer,
     NextBuffer,
     Open(String),
+    /// Edits a file, or reads the current file again, e.g. with `++enc=`.
+    Edit(String),
+    /// Recovers the changes of the buffer from its recovery file.
+    Recover,
+    /// Checks whether the files of the buffers changed on disk.
+    CheckTime,
+    /// Finds a recent file with a fuzzy picker.
+    RecentFiles,
+    /// Shows the start screen.
+    Dashboard,
+    /// Saves the session, of the working directory if no name is given.
+    MkSession(String),
+    /// Saves or loads a session, e.g. `save work` or `load work`.
+    Session(String),
     ToggleExplorer,
     ToggleLogger,
+    /// Splits the window horizontally, opening a file if given.
+    Split(String),
+    /// Splits the window vertically, opening a file if given.
+    VSplit(String),
+    FocusWindow(WindowDirection),
+    /// Grows the window height by percent of the split, negative shrinks it.
+    ResizeHeight(i16),
+    /// Grows the window width by percent of the split, negative shrinks it.
+    ResizeWidth(i16),
+    CloseWindow,
+    OnlyWindow,
+    /// Shows the message history.
+    Messages,
+    /// Sets an option, e.g. `ff=dos`.
+    Set(String),
+    /// Opens a tab page, showing a file if given.
+    TabNew(String),
+    TabClose,
+    NextTabPage,
+    PreviousTabPage,
 }
 
 impl Execute for PapierAction {
-    fn execute(&mut self, state: &mut EditorState) {}
+    fn execute(&mut self, state: &mut EditorState) {
+    }
 }
 
 async fn tokio_main() -> Result<()> {
@@ -46,7 +94,7 @@ async fn tokio_main() -> Result<()> {
     initialize_panic_handler()?;
 
     let args = Cli::parse();
-    let mut app = App::new(args.tick_rate, args.frame_rate, args.files)?;
+    let mut app = App::new(args.tick_rate, args.frame_rate, args.files, args.theme, args.session)?;
     app.run().await?;
 
     Ok(())
@@ -0,0 +1,317 @@
+//! Maps the configured [`Styles`] onto the editor widgets.
+//!
+//! The style keys are `base`, `cursor`, `selection`, `line_numbers`,
+//! `status_mode`, `status_line`, `tab`, `tab_active`, `tab_modified`,
+//! `tab_overflow`, `tab_close`, `explorer`, `explorer_item`, `explorer_dir`,
+//! `explorer_selected_item`, `explorer_selected_dir`, `logger_<level>`,
+//! `hints`, `hints_key`, `message_info`, `message_warning`, `message_error`,
+//! `dashboard_title`, `dashboard_key`, `dashboard_selected`,
+//! `picker_selected`, `picker_match` and `syntax.<token>` for each token of
+//! [`Highlighting`]. Keys that are not configured keep their built-in
+//! default.
+//!
+//! A color scheme is a file of styles, see [`load_colorscheme`]. The built-in
+//! color schemes live in `.config/themes`.
+//!
+//! Truecolors are downsampled to the 256 color palette unless the terminal
+//! announces truecolor support in `COLORTERM`.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use edtui::{view::theme::Highlighting, EditorMode, EditorTheme, StatusLine};
+use log::Level;
+use ratatui::style::{Color, Style, Stylize};
+use ratatui_explorer::Theme as FileTheme;
+
+use crate::config::Styles;
+
+const LIGHT_GRAY: Color = Color::Rgb(248, 250, 252);
+
+const BUILTIN_COLORSCHEMES: [(&str, &str); 4] = [
+    ("default", ""),
+    ("dracula", include_str!("../.config/themes/dracula.toml")),
+    ("gruvbox", include_str!("../.config/themes/gruvbox.toml")),
+    ("solarized-light", include_str!("../.config/themes/solarized-light.toml")),
+];
+
+/// Returns the directory of the user's color schemes.
+pub fn colorscheme_dir() -> PathBuf {
+    crate::utils::get_config_dir().join("themes")
+}
+
+/// Returns the names of the built-in color schemes and the ones in `dir`,
+/// usually the [`colorscheme_dir`].
+pub fn colorscheme_names(dir: &Path) -> Vec<String> {
+    let mut names = BUILTIN_COLORSCHEMES.iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>();
+    if let Ok(entries) = fs::read_dir(dir) {
+        let paths = entries.filter_map(Result::ok).map(|entry| entry.path());
+        names.extend(
+            paths
+                .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string())),
+        );
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Loads the styles of a color scheme. A file `<name>.toml` in `dir`,
+/// usually the [`colorscheme_dir`], takes precedence over a built-in color
+/// scheme. Names cannot contain path separators.
+pub fn load_colorscheme(dir: &Path, name: &str) -> Result<Styles, String> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!("invalid color scheme name `{name}`"));
+    }
+    let path = dir.join(format!("{name}.toml"));
+    let raw = if path.is_file() {
+        fs::read_to_string(&path).map_err(|err| format!("{}: {err}", path.display()))?
+    } else {
+        let builtin = BUILTIN_COLORSCHEMES.iter().find(|(builtin, _)| *builtin == name);
+        builtin.map(|(_, raw)| raw.to_string()).ok_or_else(|| format!("unknown color scheme `{name}`"))?
+    };
+    toml::from_str(&raw).map_err(|err| format!("color scheme `{name}`: {err}"))
+}
+
+#[derive(Clone, Debug)]
+pub struct Theme {
+    styles: Styles,
+    truecolor: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(Styles::default())
+    }
+}
+
+impl Theme {
+    pub fn new(styles: Styles) -> Self {
+        let truecolor = std::env::var("COLORTERM").is_ok_and(|term| term == "truecolor" || term == "24bit");
+        Self { styles, truecolor }
+    }
+
+    /// Sets whether truecolors are drawn as is or downsampled.
+    #[must_use]
+    pub fn truecolor(mut self, truecolor: bool) -> Self {
+        self.truecolor = truecolor;
+        self
+    }
+
+    /// Returns the configured style of a key, or its default.
+    pub fn style(&self, mode: EditorMode, key: &str) -> Style {
+        self.downsample(self.styles.get(mode, key).unwrap_or_else(|| default_style(mode, key)))
+    }
+
+    fn downsample(&self, mut style: Style) -> Style {
+        if !self.truecolor {
+            style.fg = style.fg.map(to_indexed);
+            style.bg = style.bg.map(to_indexed);
+        }
+        style
+    }
+
+    /// Returns the editor theme for a mode.
+    pub fn editor<'a>(&self, mode: EditorMode) -> EditorTheme<'a> {
+        EditorTheme::default()
+            .base(self.style(mode, "base"))
+            .cursor_style(self.style(mode, "cursor"))
+            .selection_style(self.style(mode, "selection"))
+            .line_numbers_style(self.style(mode, "line_numbers"))
+            .status_line(self.status_line(mode))
+            .highlighting(self.highlighting(mode))
+    }
+
+    /// Returns the status line for a mode.
+    pub fn status_line(&self, mode: EditorMode) -> StatusLine {
+        StatusLine::default().style_mode(self.style(mode, "status_mode")).style_line(self.style(mode, "status_line"))
+    }
+
+    /// Returns the syntax highlighting styles for a mode.
+    pub fn highlighting(&self, mode: EditorMode) -> Highlighting {
+        let mut highlighting = Highlighting::default();
+        let defaults = highlighting.names().map(ToString::to_string).collect::<Vec<_>>();
+        for name in defaults {
+            let style = highlighting.get(&name).unwrap_or_default();
+            highlighting.set(name, self.downsample(style));
+        }
+        let keys = self.styles.base.keys().chain(self.styles.modes.get(&mode).into_iter().flat_map(|m| m.keys()));
+        for name in keys.filter_map(|key| key.strip_prefix("syntax.")) {
+            if let Some(style) = self.styles.get(mode, &format!("syntax.{name}")) {
+                highlighting.set(name, self.downsample(style));
+            }
+        }
+        highlighting
+    }
+
+    /// Returns the theme of the file explorer.
+    pub fn explorer(&self) -> FileTheme {
+        let style = |key| self.style(EditorMode::Normal, key);
+        FileTheme::default()
+            .add_default_title()
+            .with_style(style("explorer"))
+            .with_item_style(style("explorer_item"))
+            .with_dir_style(style("explorer_dir"))
+            .with_highlight_item_style(style("explorer_selected_item"))
+            .with_highlight_dir_style(style("explorer_selected_dir"))
+    }
+
+    /// Returns the style of a log level in the logger.
+    pub fn logger(&self, level: Level) -> Style {
+        self.style(EditorMode::Normal, &format!("logger_{}", level.as_str().to_lowercase()))
+    }
+
+    /// Returns the style of a tab in the tab line.
+    pub fn tab(&self, mode: EditorMode, active: bool) -> Style {
+        self.style(mode, if active { "tab_active" } else { "tab" })
+    }
+}
+
+fn default_style(mode: EditorMode, key: &str) -> Style {
+    match (key, mode) {
+        ("base", _) => Style::default().bold().bg(Color::Reset),
+        ("cursor", EditorMode::Insert) => Style::default().underlined(),
+        ("cursor", _) => Style::default().bg(Color::White).fg(Color::Black),
+        ("selection", _) => Style::default().bg(Color::LightMagenta).fg(Color::Reset),
+        ("line_numbers", _) => Style::default().fg(Color::DarkGray).bg(Color::Reset),
+        ("status_mode", EditorMode::Normal) => Style::default().bg(Color::Reset).fg(LIGHT_GRAY),
+        ("status_mode", EditorMode::Insert) => Style::default().bg(Color::LightYellow).fg(LIGHT_GRAY),
+        ("status_mode", EditorMode::Visual) => Style::default().bg(Color::LightMagenta).fg(LIGHT_GRAY),
+        ("status_mode", EditorMode::Search) => Style::default().bg(Color::LightBlue).fg(LIGHT_GRAY),
+        ("status_mode", EditorMode::Command) => Style::default().bg(Color::Gray).fg(LIGHT_GRAY),
+        ("status_line", EditorMode::Normal) => Style::default().bg(Color::Reset),
+        ("status_line", EditorMode::Insert) => Style::default().bg(Color::Yellow),
+        ("status_line", EditorMode::Visual) => Style::default().bg(Color::Magenta),
+        ("status_line", EditorMode::Search) => Style::default().bg(Color::Blue),
+        ("status_line", EditorMode::Command) => Style::default().bg(Color::DarkGray),
+        ("tab_active", _) => Style::default().bg(Color::DarkGray),
+        ("tab_modified", _) => Style::default().fg(Color::LightYellow),
+        ("tab_overflow", _) => Style::default().fg(Color::DarkGray),
+        ("explorer_item", _) => Style::default().fg(Color::White),
+        ("explorer_dir", _) => Style::default().fg(Color::LightBlue),
+        ("explorer_selected_item", _) => Style::default().fg(Color::White).bg(Color::DarkGray),
+        ("explorer_selected_dir", _) => Style::default().fg(Color::LightBlue).bg(Color::DarkGray),
+        ("logger_error", _) => Style::default().fg(Color::Red),
+        ("logger_warn", _) => Style::default().fg(Color::Yellow),
+        ("logger_info", _) => Style::default().fg(Color::Cyan),
+        ("logger_debug", _) => Style::default().fg(Color::Green),
+        ("logger_trace", _) => Style::default().fg(Color::Magenta),
+        ("hints_key", _) => Style::default().fg(Color::LightYellow).bold(),
+        ("dashboard_title", _) => Style::default().fg(Color::LightMagenta).bold(),
+        ("dashboard_key", _) => Style::default().fg(Color::LightYellow).bold(),
+        ("dashboard_selected" | "picker_selected", _) => Style::default().bg(Color::DarkGray),
+        ("picker_match", _) => Style::default().fg(Color::LightYellow).bold(),
+        ("message_warning", _) => Style::default().fg(Color::LightYellow),
+        ("message_error", _) => Style::default().fg(Color::LightRed).bold(),
+        _ => Style::default(),
+    }
+}
+
+/// Returns the closest color of the 256 color palette, either from the
+/// 6x6x6 color cube or the grayscale ramp.
+fn to_indexed(color: Color) -> Color {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let nearest_level = |c: u8| (0..6).min_by_key(|&i| LEVELS[i].abs_diff(c)).unwrap_or_default();
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let gray_index = ((u16::from(r) + u16::from(g) + u16::from(b)) / 3).saturating_sub(3) / 10;
+    let gray_index = gray_index.min(23) as u8;
+    let gray = 8 + 10 * gray_index;
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        [(r, cr), (g, cg), (b, cb)].iter().map(|&(c, t)| u32::from(c.abs_diff(t)).pow(2)).sum::<u32>()
+    };
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    if distance((gray, gray, gray)) < distance(cube) {
+        Color::Indexed(232 + gray_index)
+    } else {
+        Color::Indexed(16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::Config;
+
+    fn theme(toml: &str) -> Theme {
+        let config: Config = toml::from_str(toml).unwrap();
+        Theme::new(config.styles).truecolor(true)
+    }
+
+    #[test]
+    fn test_default_styles() {
+        let theme = theme("");
+        assert_eq!(theme.style(EditorMode::Insert, "cursor"), Style::default().underlined());
+        assert_eq!(theme.tab(EditorMode::Normal, true), Style::default().bg(Color::DarkGray));
+        assert_eq!(theme.highlighting(EditorMode::Normal), Highlighting::default());
+    }
+
+    #[test]
+    fn test_mode_overrides() {
+        let theme = theme(
+            r#"
+            [styles]
+            selection = "on blue"
+            syntax = { keyword = "red", comment = "green" }
+            [styles.visual]
+            selection = "on red"
+            syntax = { keyword = "blue" }
+            "#,
+        );
+        assert_eq!(theme.editor(EditorMode::Normal).selection_style, Style::default().bg(Color::Blue));
+        assert_eq!(theme.editor(EditorMode::Visual).selection_style, Style::default().bg(Color::Red));
+
+        let highlighting = theme.highlighting(EditorMode::Visual);
+        assert_eq!(highlighting.get("keyword"), Some(Style::default().fg(Color::Blue)));
+        assert_eq!(highlighting.get("comment"), Some(Style::default().fg(Color::Green)));
+        assert_eq!(highlighting.get("string"), Highlighting::default().get("string"));
+    }
+
+    #[test]
+    fn test_builtin_colorschemes() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, _) in BUILTIN_COLORSCHEMES {
+            let styles = load_colorscheme(dir.path(), name).unwrap();
+            assert!(name == "default" || styles.base.contains_key("syntax.keyword"), "{name}");
+        }
+        assert_eq!(colorscheme_names(dir.path()), ["default", "dracula", "gruvbox", "solarized-light"]);
+        assert_eq!(load_colorscheme(dir.path(), "unknown").unwrap_err(), "unknown color scheme `unknown`");
+        let invalid = load_colorscheme(dir.path(), "../config").unwrap_err();
+        assert_eq!(invalid, "invalid color scheme name `../config`");
+
+        // The user's color schemes take precedence
+        fs::write(dir.path().join("dracula.toml"), "base = \"red\"").unwrap();
+        fs::write(dir.path().join("mine.toml"), "").unwrap();
+        assert!(!load_colorscheme(dir.path(), "dracula").unwrap().base.contains_key("syntax.keyword"));
+        assert!(colorscheme_names(dir.path()).contains(&"mine".to_string()));
+    }
+
+    #[test]
+    fn test_logger_styles() {
+        let theme = theme("[styles]\nlogger_warn = \"bold yellow\"");
+        assert_eq!(theme.logger(Level::Warn), Style::default().fg(Color::Yellow).bold());
+        assert_eq!(theme.logger(Level::Error), Style::default().fg(Color::Red));
+    }
+
+    #[test]
+    fn test_downsample() {
+        assert_eq!(to_indexed(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(to_indexed(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(to_indexed(Color::Red), Color::Red);
+
+        let theme = theme("[styles]\nselection = \"#ff0000 on #000\"").truecolor(false);
+        assert_eq!(
+            theme.style(EditorMode::Normal, "selection"),
+            Style::default().fg(Color::Indexed(196)).bg(Color::Indexed(16))
+        );
+        let string = theme.highlighting(EditorMode::Normal).get("string").and_then(|style| style.fg);
+        assert!(matches!(string, Some(Color::Indexed(_))));
+    }
+}
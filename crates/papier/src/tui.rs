@@ -7,8 +7,8 @@ use color_eyre::eyre::Result;
 use crossterm::{
     cursor,
     event::{
-        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event as CrosstermEvent,
-        KeyEvent, KeyEventKind, MouseEvent,
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste, EnableFocusChange,
+        EnableMouseCapture, Event as CrosstermEvent, KeyEvent, KeyEventKind, MouseEvent,
     },
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -170,7 +170,7 @@ impl Tui {
 
     pub fn enter(&mut self) -> Result<()> {
         crossterm::terminal::enable_raw_mode()?;
-        crossterm::execute!(io(), EnterAlternateScreen, cursor::Hide)?;
+        crossterm::execute!(io(), EnterAlternateScreen, cursor::Hide, EnableFocusChange)?;
         if self.mouse {
             crossterm::execute!(io(), EnableMouseCapture)?;
         }
@@ -191,7 +191,7 @@ impl Tui {
             if self.mouse {
                 crossterm::execute!(io(), DisableMouseCapture)?;
             }
-            crossterm::execute!(io(), LeaveAlternateScreen, cursor::Show)?;
+            crossterm::execute!(io(), DisableFocusChange, LeaveAlternateScreen, cursor::Show)?;
             crossterm::terminal::disable_raw_mode()?;
         }
         Ok(())